@@ -1,5 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
+use std::future::Future;
 use std::hint::black_box;
+use std::task::Poll;
 use std::time::{Duration, Instant};
 
 use import::rt::*;
@@ -90,8 +92,52 @@ async fn spawn_sleep_job(iters: usize, procs: usize) {
     }
 }
 
+fn single_thread_scheduler_idle_timers(c: &mut Criterion) {
+    do_idle_timers_test(c, 10_000, "single_thread_idle_timers-10000");
+}
+
+/// Inserts and cancels timeouts while `idle` timers are pending, this is the
+/// typical load of a server holding many idle connections.
+fn do_idle_timers_test(c: &mut Criterion, idle: u64, name: &str) {
+    let runtime = Runtime::timer_rt(1);
+
+    c.bench_function(name, |b| {
+        b.iter_custom(|iters| {
+            runtime.block_on(async move {
+                spawn_pinned(move || async move {
+                    // The concrete type keeps the future `Send` if the timers are.
+                    let mut timers: Vec<_> = (0..idle)
+                        .map(|i| {
+                            Box::pin(sleep(Duration::from_secs(60) + Duration::from_millis(i)))
+                        })
+                        .collect();
+
+                    // Register every timer.
+                    std::future::poll_fn(|cx| {
+                        for timer in &mut timers {
+                            assert!(timer.as_mut().poll(cx).is_pending());
+                        }
+                        Poll::Ready(())
+                    })
+                    .await;
+
+                    let start = Instant::now();
+                    for _ in 0..iters {
+                        let h = timeout(Duration::from_secs(1), quick_job());
+                        assert_eq!(black_box(h.await.unwrap()), 1);
+                    }
+                    start.elapsed()
+                })
+                .await
+                .unwrap()
+            })
+        })
+    });
+}
+
 criterion_group!(
     timeout_benchmark,
+    single_thread_scheduler_idle_timers,
     single_thread_scheduler_timeout,
     multi_thread_scheduler_timeout,
     single_thread_scheduler_sleep,
@@ -89,3 +89,52 @@ impl Wake for Signal {
         self.wake_by_ref();
     }
 }
+
+/// A future that stores its output once it completes, used by `join!`.
+pub enum MaybeDone<F: Future> {
+    Future(F),
+    Done(F::Output),
+    Gone,
+}
+
+impl<F: Future> MaybeDone<F> {
+    pub fn new(future: F) -> Self {
+        MaybeDone::Future(future)
+    }
+
+    /// Polls the inner future, returns `true` once the output is available.
+    pub fn poll_done(self: Pin<&mut Self>, cx: &mut Context) -> bool {
+        // Safety: the inner future is never moved, `Done` and `Gone` are not pinned.
+        let this = unsafe { self.get_unchecked_mut() };
+        match this {
+            MaybeDone::Future(fut) => match unsafe { Pin::new_unchecked(fut) }.poll(cx) {
+                Poll::Ready(output) => {
+                    *this = MaybeDone::Done(output);
+                    true
+                }
+                Poll::Pending => false,
+            },
+            MaybeDone::Done(_) => true,
+            MaybeDone::Gone => panic!("`MaybeDone` polled after its output was taken"),
+        }
+    }
+
+    pub fn output_mut(self: Pin<&mut Self>) -> Option<&mut F::Output> {
+        match unsafe { self.get_unchecked_mut() } {
+            MaybeDone::Done(output) => Some(output),
+            _ => None,
+        }
+    }
+
+    /// Takes the output out, if the future has completed.
+    pub fn take_output(self: Pin<&mut Self>) -> Option<F::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        match this {
+            MaybeDone::Done(_) => match mem::replace(this, MaybeDone::Gone) {
+                MaybeDone::Done(output) => Some(output),
+                _ => unreachable!(),
+            },
+            _ => None,
+        }
+    }
+}
@@ -1,12 +1,31 @@
 use quote2::{Quote, proc_macro2::TokenStream, quote, utils::quote_rep};
 use syn::{
-    Attribute, MacroDelimiter, Meta, MetaNameValue, Signature, Token, Visibility,
+    Attribute, Expr, ExprLit, Lit, Meta, MetaNameValue, Signature, Token, Visibility,
     parse::{Parse, ParseStream, Parser},
     punctuated::Punctuated,
+    spanned::Spanned,
 };
 
 type AttributeArgs = Punctuated<Meta, Token![,]>;
 
+const KEYS: &str = "`crate`, `flavor`, `worker_threads`, `event_interval`, \
+                    `max_blocking_threads`, `start_paused`, `measurement`";
+
+/// Other `RuntimeBuilder` methods, passed through as `.name(value)`, `.name(args..)` or `.name()`.
+const BUILDER_METHODS: &[&str] = &[
+    "min_tasks_per_worker",
+    "worker_stack_size",
+    "thread_stack_size",
+    "threadpool_load_factor",
+    "timer_resolution",
+    "thread_timeout",
+    "thread_name",
+    "worker_name",
+    "watchdog",
+    "on_stall",
+    "instrument",
+];
+
 /// Same as: [`syn::ItemFn`]
 ///
 /// But [`ItemFn::body`] is [`TokenStream`] instead of [`syn::Block`]
@@ -28,44 +47,189 @@ impl Parse for ItemFn {
     }
 }
 
-pub fn nio_main(
-    mut crate_path: TokenStream,
-    is_test: bool,
-    args: TokenStream,
-    item_fn: ItemFn,
-) -> TokenStream {
-    let _metadata = match AttributeArgs::parse_terminated.parse2(args) {
-        Ok(args) => args,
-        Err(err) => return err.into_compile_error(),
-    };
+#[derive(PartialEq)]
+enum Flavor {
+    /// `RuntimeBuilder::build`, the function runs on the calling thread.
+    Local,
+    /// `RuntimeBuilder::rt`, the function runs on a worker thread.
+    Multi,
+}
 
-    let mut metadata = Vec::new();
-    for meta in _metadata {
-        match meta {
-            Meta::Path(path) if path.is_ident("crate") => {
-                crate_path = TokenStream::new();
-                quote!(crate_path, { crate });
+struct Config {
+    crate_path: TokenStream,
+    flavor: Flavor,
+    worker_threads: Option<Lit>,
+    /// Other `RuntimeBuilder` methods, `(name, argument)`.
+    methods: Vec<(syn::Ident, TokenStream)>,
+}
+
+impl Config {
+    fn parse(crate_path: TokenStream, args: TokenStream) -> syn::Result<Config> {
+        let mut config = Config {
+            crate_path,
+            flavor: Flavor::Local,
+            worker_threads: None,
+            methods: Vec::new(),
+        };
+        let mut seen = Vec::new();
+
+        for meta in AttributeArgs::parse_terminated.parse2(args)? {
+            let Some(name) = meta.path().get_ident().cloned() else {
+                return Err(unknown_key(&meta));
+            };
+            if seen.contains(&name) {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    format!("`{name}` is set more than once"),
+                ));
             }
-            Meta::NameValue(MetaNameValue { path, value, .. }) if path.is_ident("crate") => {
-                crate_path = quote2::ToTokens::to_token_stream(&value);
+            seen.push(name.clone());
+
+            match name.to_string().as_str() {
+                "crate" => {
+                    config.crate_path = match &meta {
+                        Meta::Path(_) => {
+                            let mut path = TokenStream::new();
+                            quote!(path, { crate });
+                            path
+                        }
+                        Meta::NameValue(MetaNameValue { value, .. }) => {
+                            quote2::ToTokens::to_token_stream(value)
+                        }
+                        Meta::List(_) => {
+                            return Err(syn::Error::new_spanned(
+                                meta,
+                                "expected `crate` or `crate = path`",
+                            ));
+                        }
+                    }
+                }
+                "flavor" => {
+                    let flavor = literal(&meta, "a string", |lit| match lit {
+                        Lit::Str(flavor) => Some(flavor.clone()),
+                        _ => None,
+                    })?;
+                    config.flavor = match flavor.value().as_str() {
+                        "local" => Flavor::Local,
+                        "multi" => Flavor::Multi,
+                        other => {
+                            return Err(syn::Error::new_spanned(
+                                flavor,
+                                format!(
+                                    "unknown flavor `{other}`, expected `\"local\"` or `\"multi\"`"
+                                ),
+                            ));
+                        }
+                    };
+                }
+                "worker_threads" => {
+                    let lit = literal(&meta, "an integer between 1 and 255", |lit| {
+                        integer(lit, 1u8)
+                    })?;
+                    config.worker_threads = Some(lit);
+                }
+                "event_interval" => {
+                    let lit = literal(&meta, "a positive integer", |lit| integer(lit, 1u32))?;
+                    config.push(name, lit);
+                }
+                "max_blocking_threads" => {
+                    let lit = literal(&meta, "an integer between 1 and 65535", |lit| {
+                        integer(lit, 1u16)
+                    })?;
+                    config.push(name, lit);
+                }
+                "start_paused" => {
+                    let lit = literal(&meta, "a boolean", |lit| {
+                        matches!(lit, Lit::Bool(_)).then(|| lit.clone())
+                    })?;
+                    config.push(name, lit);
+                }
+                "measurement" => {
+                    let value = name_value(&meta)?.clone();
+                    config.push(name, value);
+                }
+                method if BUILDER_METHODS.contains(&method) => {
+                    let args = match &meta {
+                        Meta::Path(_) => TokenStream::new(),
+                        Meta::List(list) => list.tokens.clone(),
+                        Meta::NameValue(MetaNameValue { value, .. }) => {
+                            quote2::ToTokens::to_token_stream(value)
+                        }
+                    };
+                    config.push(name, args);
+                }
+                _ => return Err(unknown_key(&meta)),
             }
-            _ => metadata.push(meta),
         }
+        Ok(config)
     }
 
-    let config = quote_rep(metadata, |t, meta| match meta {
-        Meta::List(list) if matches!(list.delimiter, MacroDelimiter::Paren(_)) => {
-            quote!(t, { .#list });
-        }
-        #[allow(unused)]
-        Meta::NameValue(MetaNameValue { path, value, .. }) => {
-            quote!(t, { .#path(#value) });
-        }
-        Meta::Path(path) => {
-            quote!(t, { .#path() });
+    fn push(&mut self, name: syn::Ident, value: impl quote2::ToTokens) {
+        self.methods.push((name, value.to_token_stream()));
+    }
+}
+
+fn unknown_key(meta: &Meta) -> syn::Error {
+    let path = meta.path();
+    let name = quote2::ToTokens::to_token_stream(path)
+        .to_string()
+        .replace(' ', "");
+    syn::Error::new_spanned(
+        path,
+        format!(
+            "unknown attribute `{name}`, expected one of: {KEYS}, \
+             or a `RuntimeBuilder` method: `{}`",
+            BUILDER_METHODS.join("`, `")
+        ),
+    )
+}
+
+fn name_value(meta: &Meta) -> syn::Result<&Expr> {
+    match meta {
+        Meta::NameValue(MetaNameValue { value, .. }) => Ok(value),
+        _ => {
+            let name = meta.path().get_ident().unwrap();
+            Err(syn::Error::new_spanned(
+                meta,
+                format!("expected `{name} = ...`"),
+            ))
         }
-        _ => {}
-    });
+    }
+}
+
+/// Parses the literal of `name = value` with `f`, or fails with "`name` expects {expected}".
+fn literal<T>(meta: &Meta, expected: &str, f: impl FnOnce(&Lit) -> Option<T>) -> syn::Result<T> {
+    let name = meta.path().get_ident().unwrap();
+    let value = name_value(meta)?;
+    match value {
+        Expr::Lit(ExprLit { lit, .. }) => f(lit),
+        _ => None,
+    }
+    .ok_or_else(|| syn::Error::new(value.span(), format!("`{name}` expects {expected}")))
+}
+
+/// An integer literal of type `T`, at least `min`.
+fn integer<T>(lit: &Lit, min: T) -> Option<Lit>
+where
+    T: std::str::FromStr + PartialOrd,
+    T::Err: std::fmt::Display,
+{
+    match lit {
+        Lit::Int(int) if int.base10_parse::<T>().is_ok_and(|n| n >= min) => Some(lit.clone()),
+        _ => None,
+    }
+}
+
+pub fn nio_main(
+    crate_path: TokenStream,
+    is_test: bool,
+    args: TokenStream,
+    item_fn: ItemFn,
+) -> TokenStream {
+    let config = match Config::parse(crate_path, args) {
+        Ok(config) => config,
+        Err(err) => return err.into_compile_error(),
+    };
 
     let ItemFn {
         attrs,
@@ -74,21 +238,68 @@ pub fn nio_main(
         body,
     } = item_fn;
 
-    let async_keyword = sig.asyncness.take();
     let attrs = quote_rep(attrs, |t, attr| {
         quote!(t, { #attr });
     });
 
+    let Some(async_keyword) = sig.asyncness.take() else {
+        let attr = if is_test { "test" } else { "main" };
+        let mut out = syn::Error::new_spanned(
+            sig.fn_token,
+            format!(
+                "the `async` keyword is missing from the function declaration, \
+                 `#[nio::{attr}]` only applies to async functions"
+            ),
+        )
+        .into_compile_error();
+        // Keep the function, so that the error above is the only one.
+        quote!(out, { #attrs #vis #sig #body });
+        return out;
+    };
+
     let test_attr = quote(|t| {
         if is_test {
             quote!(t, { #[::core::prelude::v1::test] });
         }
     });
 
-    let test_config = quote(|t| {
-        if is_test {
+    let Config {
+        crate_path,
+        flavor,
+        worker_threads,
+        methods,
+    } = config;
+
+    let worker_threads = quote(|t| match &worker_threads {
+        Some(lit) => {
+            quote!(t, { .worker_threads(#lit) });
+        }
+        // Tests run in parallel, one worker each is enough.
+        None if is_test => {
             quote!(t, { .worker_threads(1) });
         }
+        None => {}
+    });
+
+    let methods = quote_rep(methods, |t, (name, value)| {
+        quote!(t, { .#name(#value) });
+    });
+
+    let run = quote(|t| match flavor {
+        Flavor::Local => {
+            quote!(t, {
+                .build()
+                .unwrap()
+                .block_on(#async_keyword move #body)
+            });
+        }
+        Flavor::Multi => {
+            quote!(t, {
+                .rt()
+                .unwrap()
+                .block_on(move || #async_keyword move #body)
+            });
+        }
     });
 
     let mut out = TokenStream::new();
@@ -97,11 +308,9 @@ pub fn nio_main(
         #test_attr
         #vis #sig {
             #crate_path::RuntimeBuilder::new()
-                #test_config
-                #config
-                .build()
-                .unwrap()
-                .block_on(#async_keyword move #body)
+                #worker_threads
+                #methods
+                #run
         }
     });
     out
@@ -0,0 +1,102 @@
+use quote2::{Quote, proc_macro2::TokenStream, quote, utils::quote_rep};
+use syn::{Expr, Index, Token, punctuated::Punctuated};
+
+pub type Futures = Punctuated<Expr, Token![,]>;
+
+/// Expands `join!` and, with `is_try`, `try_join!`.
+pub fn join(crate_path: TokenStream, is_try: bool, futures: Futures) -> TokenStream {
+    let count = futures.len();
+    let mut out = TokenStream::new();
+    if count == 0 {
+        if is_try {
+            quote!(out, { ::core::result::Result::Ok(()) });
+        } else {
+            quote!(out, { () });
+        }
+        return out;
+    }
+
+    let futures = quote_rep(&futures, |t, fut| {
+        quote!(t, {
+            #crate_path::__private::MaybeDone::new(::core::future::IntoFuture::into_future(#fut)),
+        });
+    });
+
+    let poll_futures = quote_rep(0..count, |t, i| {
+        let index = Index::from(i);
+        let check_err = quote(|t| {
+            if is_try {
+                quote!(t, {
+                    if let ::core::option::Option::Some(::core::result::Result::Err(_)) = fut.as_mut().output_mut() {
+                        if let ::core::option::Option::Some(::core::result::Result::Err(err)) = fut.take_output() {
+                            return #crate_path::__private::Poll::Ready(::core::result::Result::Err(err));
+                        }
+                    }
+                });
+            }
+        });
+        quote!(t, {
+            #i => {
+                // Safety: the futures are never moved, see above.
+                let mut fut = unsafe { #crate_path::__private::Pin::new_unchecked(&mut __nio_futures.#index) };
+                if fut.as_mut().poll_done(cx) {
+                    #check_err
+                } else {
+                    is_pending = true;
+                }
+            }
+        });
+    });
+
+    let outputs = quote_rep(0..count, |t, i| {
+        let index = Index::from(i);
+        let take = quote(|t| {
+            quote!(t, {
+                unsafe { #crate_path::__private::Pin::new_unchecked(&mut __nio_futures.#index) }.take_output()
+            });
+        });
+        if is_try {
+            quote!(t, {
+                match #take {
+                    ::core::option::Option::Some(::core::result::Result::Ok(output)) => output,
+                    _ => ::core::unreachable!(),
+                },
+            });
+        } else {
+            quote!(t, { #take.unwrap(), });
+        }
+    });
+
+    let ready = quote(|t| {
+        if is_try {
+            quote!(t, { ::core::result::Result::Ok((#outputs)) });
+        } else {
+            quote!(t, { (#outputs) });
+        }
+    });
+
+    let count_u32 = count as u32;
+    quote!(out, {
+        {
+            let mut __nio_futures = (#futures);
+            // Shadowed, so the futures can't be moved until they are dropped in place.
+            let __nio_futures = &mut __nio_futures;
+            #crate_path::__private::poll_fn(|cx| {
+                let start = #crate_path::__private::rand_below(#count_u32) as usize;
+                let mut is_pending = false;
+                for branch in (start..#count).chain(0..start) {
+                    match branch {
+                        #poll_futures
+                        _ => ::core::unreachable!(),
+                    }
+                }
+                if is_pending {
+                    return #crate_path::__private::Poll::Pending;
+                }
+                #crate_path::__private::Poll::Ready(#ready)
+            })
+            .await
+        }
+    });
+    out
+}
@@ -1,7 +1,21 @@
 mod expend;
+mod join;
+mod select;
 
 use proc_macro::TokenStream;
 
+/// Runs an async function on a `RuntimeBuilder` runtime.
+///
+/// Accepted arguments, each mapped onto the `RuntimeBuilder` method of the same name:
+///
+/// - `flavor = "local" | "multi"`: `"local"` (default) runs on the calling thread,
+///   `"multi"` runs on worker `0` of a multi-threaded runtime.
+/// - `worker_threads = N`, `event_interval = N`, `max_blocking_threads = N`
+/// - `start_paused = bool`
+/// - `measurement = expr`
+/// - Other `RuntimeBuilder` methods, such as `min_tasks_per_worker = N` or
+///   `thread_timeout(None)`, are passed through unchecked.
+/// - `crate = path`: path to the `nio` crate.
 #[proc_macro_attribute]
 pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
     expend::nio_main(
@@ -13,6 +27,9 @@ pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
+/// Like [`macro@main`], for `#[test]` functions.
+///
+/// Defaults to `worker_threads = 1`, as tests already run in parallel.
 #[proc_macro_attribute]
 pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
     expend::nio_main(
@@ -24,6 +41,50 @@ pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
+/// Waits on multiple futures at once, and runs the handler of the first one to complete.
+///
+/// ```ignore
+/// nio::select! {
+///     biased;
+///     Some(msg) = rx.recv(), if open => handle(msg),
+///     _ = nio::sleep(timeout) => return,
+///     else => break,
+/// }
+/// ```
+///
+/// Each branch is `<pattern> = <future>, if <condition> => <handler>`, the
+/// condition is optional. Futures are polled in place, without boxing, starting
+/// from a random branch on every poll so that no branch starves the others.
+/// `biased;` polls them in order instead.
+///
+/// A branch is disabled when its condition is `false`, or when its future
+/// completes with a value that doesn't match the pattern. Once every branch
+/// is disabled, the `else` handler runs, or `select!` panics without one.
+/// The output is matched by value, like in a `match` arm. The other futures
+/// are dropped before the handler runs.
+#[proc_macro]
+pub fn select(input: TokenStream) -> TokenStream {
+    select::select(crate_path(), syn::parse_macro_input!(input)).into()
+}
+
+/// Polls multiple futures concurrently, and returns a tuple of their outputs.
+///
+/// Like [`select!`], each poll starts from a random future.
+#[proc_macro]
+pub fn join(input: TokenStream) -> TokenStream {
+    let futures = syn::parse_macro_input!(input with join::Futures::parse_terminated);
+    join::join(crate_path(), false, futures).into()
+}
+
+/// Like [`join!`], for futures returning [`Result`].
+///
+/// Returns the first error as soon as it occurs, dropping the other futures.
+#[proc_macro]
+pub fn try_join(input: TokenStream) -> TokenStream {
+    let futures = syn::parse_macro_input!(input with join::Futures::parse_terminated);
+    join::join(crate_path(), true, futures).into()
+}
+
 fn crate_path() -> quote2::proc_macro2::TokenStream {
     use quote2::*;
     let mut out = proc_macro2::TokenStream::new();
@@ -0,0 +1,327 @@
+use quote2::{Quote, format_ident, proc_macro2::TokenStream, quote, utils::quote_rep};
+use syn::{
+    Expr, Index, Pat, PatIdent, Token,
+    parse::{Parse, ParseStream},
+};
+
+/// `<pattern> = <future>, if <condition> => <handler>`
+struct Branch {
+    pat: Pat,
+    fut: Expr,
+    cond: Option<Expr>,
+    handler: Expr,
+}
+
+pub struct Select {
+    biased: bool,
+    branches: Vec<Branch>,
+    /// `else => <handler>`, runs when every branch is disabled.
+    otherwise: Option<Expr>,
+}
+
+/// One bit per branch in the `disabled` mask.
+const MAX_BRANCHES: usize = 64;
+
+mod kw {
+    syn::custom_keyword!(biased);
+}
+
+impl Parse for Select {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let biased = input.peek(kw::biased) && input.peek2(Token![;]);
+        if biased {
+            input.parse::<kw::biased>()?;
+            input.parse::<Token![;]>()?;
+        }
+
+        let mut branches = Vec::new();
+        let mut otherwise = None;
+        while !input.is_empty() {
+            if input.peek(Token![else]) {
+                let else_token = input.parse::<Token![else]>()?;
+                if otherwise.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        else_token,
+                        "`select!` has more than one `else` branch",
+                    ));
+                }
+                input.parse::<Token![=>]>()?;
+                otherwise = Some(parse_handler(input)?);
+                continue;
+            }
+
+            let pat = Pat::parse_multi_with_leading_vert(input)?;
+            input.parse::<Token![=]>()?;
+            let fut = input.parse()?;
+            let mut cond = None;
+            if input.peek(Token![,]) && input.peek2(Token![if]) {
+                input.parse::<Token![,]>()?;
+                input.parse::<Token![if]>()?;
+                cond = Some(input.parse()?);
+            }
+            input.parse::<Token![=>]>()?;
+            let handler = parse_handler(input)?;
+            branches.push(Branch {
+                pat,
+                fut,
+                cond,
+                handler,
+            });
+        }
+
+        if branches.is_empty() {
+            return Err(input.error("`select!` requires at least one branch"));
+        }
+        if branches.len() > MAX_BRANCHES {
+            return Err(input.error(format!(
+                "`select!` supports at most {MAX_BRANCHES} branches"
+            )));
+        }
+        Ok(Select {
+            biased,
+            branches,
+            otherwise,
+        })
+    }
+}
+
+/// Parses a handler like a `match` arm body, the trailing comma is optional after a block.
+fn parse_handler(input: ParseStream) -> syn::Result<Expr> {
+    let handler = Expr::parse_with_earlier_boundary_rule(input)?;
+    let is_block = matches!(
+        handler,
+        Expr::Block(_)
+            | Expr::If(_)
+            | Expr::Match(_)
+            | Expr::Loop(_)
+            | Expr::While(_)
+            | Expr::ForLoop(_)
+            | Expr::Unsafe(_)
+            | Expr::TryBlock(_)
+            | Expr::Const(_)
+    );
+    if input.is_empty() || (is_block && !input.peek(Token![,])) {
+        return Ok(handler);
+    }
+    input.parse::<Token![,]>()?;
+    Ok(handler)
+}
+
+pub fn select(crate_path: TokenStream, select: Select) -> TokenStream {
+    let Select {
+        biased,
+        branches,
+        otherwise,
+    } = select;
+
+    let count = branches.len();
+    let all = if count == MAX_BRANCHES {
+        u64::MAX
+    } else {
+        (1u64 << count) - 1
+    };
+
+    let variant = |i: usize| format_ident!("_{}", i);
+    let generics = quote_rep(0..count, |t, i| {
+        let ty = format_ident!("__T{}", i);
+        quote!(t, { #ty, });
+    });
+    let variants = quote_rep(0..count, |t, i| {
+        let name = variant(i);
+        let ty = format_ident!("__T{}", i);
+        quote!(t, { #name(#ty), });
+    });
+
+    let conditions = quote_rep(branches.iter().enumerate(), |t, (i, branch)| {
+        if let Some(cond) = &branch.cond {
+            let bit = 1u64 << i;
+            quote!(t, {
+                if !(#cond) {
+                    __nio_disabled |= #bit;
+                }
+            });
+        }
+    });
+
+    let futures = quote_rep(&branches, |t, branch| {
+        let fut = &branch.fut;
+        quote!(t, {
+            ::core::future::IntoFuture::into_future(#fut),
+        });
+    });
+
+    let start = quote(|t| {
+        if biased {
+            quote!(t, { 0 });
+        } else {
+            let count = count as u32;
+            quote!(t, { #crate_path::__private::rand_below(#count) as usize });
+        }
+    });
+
+    let poll_branches = quote_rep(0..count, |t, i| {
+        let bit = 1u64 << i;
+        let index = Index::from(i);
+        let name = variant(i);
+        quote!(t, {
+            #i => {
+                if __nio_disabled & #bit != 0 {
+                    continue;
+                }
+                // Safety: the futures are never moved, see above.
+                let fut = unsafe { #crate_path::__private::Pin::new_unchecked(&mut __nio_futures.#index) };
+                let out = match ::core::future::Future::poll(fut, cx) {
+                    #crate_path::__private::Poll::Ready(out) => out,
+                    #crate_path::__private::Poll::Pending => continue,
+                };
+                // A completed future must not be polled again.
+                __nio_disabled |= #bit;
+                return #crate_path::__private::Poll::Ready(__NioSelectOutput::#name(out));
+            }
+        });
+    });
+
+    // The output is matched by value in the loop, and its bindings are moved
+    // out of it, so that `break` and `continue` in a handler refer to the
+    // loops of the caller.
+    let matches = quote_rep(branches.iter().enumerate(), |t, (i, branch)| {
+        let name = variant(i);
+        let mut pat = branch.pat.clone();
+        let mut bindings = Vec::new();
+        strip_bindings(&mut pat, &mut bindings);
+        let idents = quote_rep(&bindings, |t, binding| {
+            let ident = &binding.ident;
+            quote!(t, { #ident, });
+        });
+        quote!(t, {
+            __NioSelectOutput::#name(out) => {
+                #[allow(unreachable_patterns, unused_variables)]
+                match out {
+                    #pat => break __NioSelectOutput::#name((#idents)),
+                    // The branch is disabled, poll the other ones.
+                    _ => continue,
+                }
+            }
+        });
+    });
+
+    let handlers = quote_rep(branches.iter().enumerate(), |t, (i, branch)| {
+        let name = variant(i);
+        let mut bindings = Vec::new();
+        strip_bindings(&mut branch.pat.clone(), &mut bindings);
+        let bindings = quote_rep(&bindings, |t, binding| {
+            quote!(t, { #binding, });
+        });
+        let handler = &branch.handler;
+        quote!(t, {
+            __NioSelectOutput::#name((#bindings)) => #handler,
+        });
+    });
+
+    let otherwise = quote(|t| match &otherwise {
+        Some(handler) => {
+            quote!(t, { __NioSelectOutput::Disabled => #handler, });
+        }
+        None => {
+            quote!(t, {
+                __NioSelectOutput::Disabled => {
+                    ::core::panic!("all branches are disabled and there is no else branch")
+                }
+            });
+        }
+    });
+
+    let mut out = TokenStream::new();
+    quote!(out, {
+        {
+            enum __NioSelectOutput<#generics> {
+                #variants
+                Disabled,
+            }
+
+            let mut __nio_disabled: u64 = 0;
+            #conditions
+
+            let __nio_output = {
+                let mut __nio_futures = (#futures);
+                // Shadowed, so the futures can't be moved until they are dropped in place.
+                let __nio_futures = &mut __nio_futures;
+                loop {
+                    let __nio_output = #crate_path::__private::poll_fn(|cx| {
+                        if __nio_disabled == #all {
+                            return #crate_path::__private::Poll::Ready(__NioSelectOutput::Disabled);
+                        }
+                        let start: usize = #start;
+                        for branch in (start..#count).chain(0..start) {
+                            match branch {
+                                #poll_branches
+                                _ => ::core::unreachable!(),
+                            }
+                        }
+                        if __nio_disabled == #all {
+                            #crate_path::__private::Poll::Ready(__NioSelectOutput::Disabled)
+                        } else {
+                            #crate_path::__private::Poll::Pending
+                        }
+                    })
+                    .await;
+                    match __nio_output {
+                        #matches
+                        __NioSelectOutput::Disabled => break __NioSelectOutput::Disabled,
+                    }
+                }
+            };
+
+            #[allow(unreachable_patterns)]
+            match __nio_output {
+                #handlers
+                #otherwise
+                _ => ::core::unreachable!("`select!` output does not match its pattern"),
+            }
+        }
+    });
+    out
+}
+
+/// Removes `ref` and `mut` from the bindings of `pat`, so it matches by value,
+/// and collects the original bindings, without their subpatterns.
+///
+/// Identifiers that name a constant or a unit variant are collected as well,
+/// they are moved out as a value equal to themselves.
+fn strip_bindings(pat: &mut Pat, bindings: &mut Vec<PatIdent>) {
+    match pat {
+        Pat::Ident(ident) => {
+            let mut binding = ident.clone();
+            binding.subpat = None;
+            bindings.push(binding);
+            ident.by_ref = None;
+            ident.mutability = None;
+            if let Some((_, subpat)) = &mut ident.subpat {
+                strip_bindings(subpat, bindings);
+            }
+        }
+        Pat::Or(or) => {
+            // Every alternative binds the same names.
+            for (i, case) in or.cases.iter_mut().enumerate() {
+                let mut ignored = Vec::new();
+                strip_bindings(case, if i == 0 { &mut *bindings } else { &mut ignored });
+            }
+        }
+        Pat::Paren(paren) => strip_bindings(&mut paren.pat, bindings),
+        Pat::Reference(reference) => strip_bindings(&mut reference.pat, bindings),
+        Pat::Struct(strukt) => {
+            for field in &mut strukt.fields {
+                strip_bindings(&mut field.pat, bindings);
+            }
+        }
+        Pat::Slice(syn::PatSlice { elems, .. })
+        | Pat::Tuple(syn::PatTuple { elems, .. })
+        | Pat::TupleStruct(syn::PatTupleStruct { elems, .. }) => {
+            for elem in elems {
+                strip_bindings(elem, bindings);
+            }
+        }
+        Pat::Type(ty) => strip_bindings(&mut ty.pat, bindings),
+        _ => {}
+    }
+}
@@ -0,0 +1,452 @@
+//! Renders runtime metrics in the [OpenMetrics] text format, understood by Prometheus.
+//!
+//! ```ignore
+//! let metrics = nio::RuntimeContext::current().metrics();
+//! let text = nio_metrics::export::prometheus(&metrics);
+//! ```
+//!
+//! Counters and histograms come from [`SimpleMeasurement`], and from the
+//! histogram hooks of any other [`Measurement`]. Queue depths are always
+//! available.
+//!
+//! [OpenMetrics]: https://github.com/prometheus/OpenMetrics/blob/main/specification/OpenMetrics.md
+
+use crate::{Histogram, Measurement, SimpleMeasurement, SpawnKind, WorkerMetrics};
+use std::{any::Any, fmt, io, time::Duration};
+
+/// `Content-Type` of the rendered text.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// What the exporter reads from a runtime, implemented by `nio::metrics::RuntimeMetrics`.
+pub trait Source {
+    fn num_workers(&self) -> usize;
+    /// Number of tasks in the `(local, shared)` queues of `worker`.
+    fn queue_depth(&self, worker: usize) -> (u64, u64);
+    fn measurement(&self) -> &dyn Measurement;
+    /// State of the blocking thread pool, if the runtime has one.
+    fn blocking_pool(&self) -> Option<BlockingPool> {
+        None
+    }
+}
+
+/// A snapshot of the blocking thread pool, see [`Source::blocking_pool`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockingPool {
+    pub max_threads: u64,
+    pub idle_threads: u64,
+    pub busy_threads: u64,
+    /// Tasks waiting for a thread.
+    pub queue_depth: u64,
+    pub tasks_executed: u64,
+    pub threads_spawned: u64,
+    /// Threads that exited after being idle for too long.
+    pub threads_exited: u64,
+    pub max_queue_latency: Duration,
+}
+
+/// Renders `metrics` to a string.
+pub fn prometheus(metrics: &impl Source) -> String {
+    let mut out = String::new();
+    render(metrics, &mut out).expect("a `String` never fails to write");
+    out
+}
+
+/// Renders `metrics` to `writer`.
+pub fn write_to(metrics: &impl Source, mut writer: impl io::Write) -> io::Result<()> {
+    writer.write_all(prometheus(metrics).as_bytes())
+}
+
+fn render(metrics: &impl Source, out: &mut impl fmt::Write) -> fmt::Result {
+    let workers = metrics.num_workers();
+    let measurement = metrics.measurement();
+
+    writeln!(out, "# HELP nio_workers Number of worker threads.")?;
+    writeln!(out, "# TYPE nio_workers gauge")?;
+    writeln!(out, "nio_workers {workers}")?;
+
+    family(
+        out,
+        "nio_worker_queue_depth",
+        "gauge",
+        "Number of tasks waiting in the queues of a worker.",
+    )?;
+    for worker in 0..workers {
+        let (local, shared) = metrics.queue_depth(worker);
+        writeln!(
+            out,
+            "nio_worker_queue_depth{{worker=\"{worker}\",queue=\"local\"}} {local}"
+        )?;
+        writeln!(
+            out,
+            "nio_worker_queue_depth{{worker=\"{worker}\",queue=\"shared\"}} {shared}"
+        )?;
+    }
+
+    let simple = (measurement as &dyn Any).downcast_ref::<SimpleMeasurement>();
+    if let Some(simple) = simple {
+        render_counters(simple, out)?;
+    }
+
+    if let Some(pool) = metrics.blocking_pool() {
+        render_blocking_pool(&pool, out)?;
+    }
+
+    histogram_family(
+        out,
+        "nio_task_poll_duration_seconds",
+        "Time spent in a single poll of a task.",
+        workers,
+        |worker| measurement.poll_time_histogram(worker),
+    )?;
+    histogram_family(
+        out,
+        "nio_task_scheduling_delay_seconds",
+        "Time tasks waited in the queues before being polled.",
+        workers,
+        |worker| measurement.scheduling_delay_histogram(worker),
+    )?;
+
+    writeln!(out, "# EOF")
+}
+
+fn render_counters(simple: &SimpleMeasurement, out: &mut impl fmt::Write) -> fmt::Result {
+    type Counter = fn(&WorkerMetrics) -> f64;
+    let counters: [(&str, &str, Counter); 12] = [
+        ("nio_task_polls", "Tasks polled.", |w| w.polled() as f64),
+        (
+            "nio_task_yields",
+            "Tasks that woke themselves while being polled.",
+            |w| w.yielded() as f64,
+        ),
+        ("nio_tasks_completed", "Tasks completed.", |w| {
+            w.completed() as f64
+        }),
+        ("nio_worker_parks", "Times the worker parked.", |w| {
+            w.parked() as f64
+        }),
+        ("nio_worker_unparks", "Times the worker unparked.", |w| {
+            w.unparked() as f64
+        }),
+        (
+            "nio_worker_busy_seconds",
+            "Time spent running tasks, timers and I/O events.",
+            |w| w.busy_time().as_secs_f64(),
+        ),
+        ("nio_worker_idle_seconds", "Time spent parked.", |w| {
+            w.idle_time().as_secs_f64()
+        }),
+        ("nio_io_events", "I/O events dispatched.", |w| {
+            w.io_events() as f64
+        }),
+        ("nio_timers_fired", "Timers fired.", |w| {
+            w.timers_fired() as f64
+        }),
+        (
+            "nio_tasks_transferred",
+            "Tasks moved from the shared queue to the local queue.",
+            |w| w.tasks_transferred() as f64,
+        ),
+        (
+            "nio_queue_drained",
+            "Times the worker found its queues empty.",
+            |w| w.queue_drained() as f64,
+        ),
+        (
+            "nio_queue_notified",
+            "Times another thread woke the worker up.",
+            |w| w.queue_notified() as f64,
+        ),
+    ];
+
+    for (name, help, value) in counters {
+        family(out, name, "counter", help)?;
+        for (worker, metrics) in simple.workers().iter().enumerate() {
+            writeln!(
+                out,
+                "{name}_total{{worker=\"{worker}\"}} {}",
+                value(metrics)
+            )?;
+        }
+    }
+
+    family(
+        out,
+        "nio_tasks_spawned",
+        "counter",
+        "Tasks spawned, by the worker they were sent to.",
+    )?;
+    for (worker, metrics) in simple.workers().iter().enumerate() {
+        for kind in [SpawnKind::Spawn, SpawnKind::Pinned, SpawnKind::Local] {
+            writeln!(
+                out,
+                "nio_tasks_spawned_total{{worker=\"{worker}\",kind=\"{}\"}} {}",
+                kind.as_str(),
+                metrics.spawned(kind)
+            )?;
+        }
+    }
+    family(
+        out,
+        "nio_task_remote_wakeups",
+        "counter",
+        "Pinned tasks woken from another thread, by their worker and the waking worker.",
+    )?;
+    let workers = simple.workers().len();
+    for (worker, metrics) in simple.workers().iter().enumerate() {
+        for from in (0..workers).filter(|&from| from != worker) {
+            writeln!(
+                out,
+                "nio_task_remote_wakeups_total{{worker=\"{worker}\",from=\"{from}\"}} {}",
+                metrics.woken_remote(Some(from))
+            )?;
+        }
+        writeln!(
+            out,
+            "nio_task_remote_wakeups_total{{worker=\"{worker}\",from=\"external\"}} {}",
+            metrics.woken_remote(None)
+        )?;
+    }
+    family(
+        out,
+        "nio_blocking_tasks_spawned",
+        "counter",
+        "Closures sent to the blocking thread pool.",
+    )?;
+    writeln!(
+        out,
+        "nio_blocking_tasks_spawned_total {}",
+        simple.blocking_spawned()
+    )?;
+
+    family(
+        out,
+        "nio_worker_local_queue_depth_sampled",
+        "gauge",
+        "Tasks in the local queue, sampled once per tick.",
+    )?;
+    for (worker, metrics) in simple.workers().iter().enumerate() {
+        writeln!(
+            out,
+            "nio_worker_local_queue_depth_sampled{{worker=\"{worker}\"}} {}",
+            metrics.local_queue_depth()
+        )?;
+    }
+    Ok(())
+}
+
+fn render_blocking_pool(pool: &BlockingPool, out: &mut impl fmt::Write) -> fmt::Result {
+    family(
+        out,
+        "nio_blocking_threads",
+        "gauge",
+        "Threads of the blocking thread pool.",
+    )?;
+    writeln!(
+        out,
+        "nio_blocking_threads{{state=\"idle\"}} {}",
+        pool.idle_threads
+    )?;
+    writeln!(
+        out,
+        "nio_blocking_threads{{state=\"busy\"}} {}",
+        pool.busy_threads
+    )?;
+
+    let gauges = [
+        (
+            "nio_blocking_max_threads",
+            "Maximum number of blocking threads.",
+            pool.max_threads as f64,
+        ),
+        (
+            "nio_blocking_queue_depth",
+            "Blocking tasks waiting for a thread.",
+            pool.queue_depth as f64,
+        ),
+        (
+            "nio_blocking_max_queue_latency_seconds",
+            "Longest time a blocking task waited for a thread.",
+            seconds(pool.max_queue_latency),
+        ),
+    ];
+    for (name, help, value) in gauges {
+        family(out, name, "gauge", help)?;
+        writeln!(out, "{name} {value}")?;
+    }
+
+    let counters = [
+        (
+            "nio_blocking_tasks_executed",
+            "Blocking tasks run to completion.",
+            pool.tasks_executed,
+        ),
+        (
+            "nio_blocking_threads_spawned",
+            "Blocking threads spawned.",
+            pool.threads_spawned,
+        ),
+        (
+            "nio_blocking_threads_exited",
+            "Blocking threads that exited after being idle.",
+            pool.threads_exited,
+        ),
+    ];
+    for (name, help, value) in counters {
+        family(out, name, "counter", help)?;
+        writeln!(out, "{name}_total {value}")?;
+    }
+    Ok(())
+}
+
+fn family(out: &mut impl fmt::Write, name: &str, kind: &str, help: &str) -> fmt::Result {
+    writeln!(out, "# HELP {name} {help}")?;
+    writeln!(out, "# TYPE {name} {kind}")
+}
+
+fn histogram_family<'a>(
+    out: &mut impl fmt::Write,
+    name: &str,
+    help: &str,
+    workers: usize,
+    histogram: impl Fn(usize) -> Option<&'a Histogram>,
+) -> fmt::Result {
+    let mut header = false;
+    for worker in 0..workers {
+        let Some(histogram) = histogram(worker) else {
+            continue;
+        };
+        if !header {
+            family(out, name, "histogram", help)?;
+            header = true;
+        }
+        let mut cumulative = 0;
+        let mut buckets = histogram.buckets().peekable();
+        while let Some(bucket) = buckets.next() {
+            cumulative += bucket.count;
+            // The last bucket also counts values above its range.
+            if buckets.peek().is_some() {
+                writeln!(
+                    out,
+                    "{name}_bucket{{worker=\"{worker}\",le=\"{}\"}} {cumulative}",
+                    seconds(bucket.range.end)
+                )?;
+            }
+        }
+        writeln!(
+            out,
+            "{name}_bucket{{worker=\"{worker}\",le=\"+Inf\"}} {cumulative}"
+        )?;
+        writeln!(
+            out,
+            "{name}_sum{{worker=\"{worker}\"}} {}",
+            seconds(histogram.sum())
+        )?;
+        writeln!(out, "{name}_count{{worker=\"{worker}\"}} {cumulative}")?;
+    }
+    Ok(())
+}
+
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HistogramConfig;
+
+    struct Runtime(SimpleMeasurement);
+
+    impl Source for Runtime {
+        fn num_workers(&self) -> usize {
+            2
+        }
+        fn queue_depth(&self, worker: usize) -> (u64, u64) {
+            (worker as u64 + 1, 0)
+        }
+        fn measurement(&self) -> &dyn Measurement {
+            &self.0
+        }
+        fn blocking_pool(&self) -> Option<BlockingPool> {
+            Some(BlockingPool {
+                max_threads: 4,
+                idle_threads: 1,
+                busy_threads: 2,
+                max_queue_latency: Duration::from_millis(3),
+                ..BlockingPool::default()
+            })
+        }
+    }
+
+    fn runtime() -> Runtime {
+        let mut measurement = SimpleMeasurement::new().histograms(
+            HistogramConfig::new()
+                .resolution(Duration::from_millis(1))
+                .precision(0)
+                .max(Duration::from_millis(4)),
+        );
+        measurement.init(2);
+        Runtime(measurement)
+    }
+
+    #[test]
+    fn counters_and_gauges() {
+        let rt = runtime();
+        rt.0.task_polled(1);
+        rt.0.task_polled(1);
+        rt.0.task_spawned(Some(0), SpawnKind::Local);
+        rt.0.task_spawned(None, SpawnKind::Blocking);
+        rt.0.task_woken_remote(1, Some(0));
+        rt.0.task_woken_remote(1, None);
+
+        let text = prometheus(&rt);
+        assert!(text.starts_with("# HELP nio_workers"));
+        assert!(text.ends_with("# EOF\n"));
+        for line in [
+            "nio_workers 2",
+            "# TYPE nio_task_polls counter",
+            "nio_task_polls_total{worker=\"0\"} 0",
+            "nio_task_polls_total{worker=\"1\"} 2",
+            "nio_tasks_spawned_total{worker=\"0\",kind=\"local\"} 1",
+            "nio_blocking_tasks_spawned_total 1",
+            "nio_task_remote_wakeups_total{worker=\"1\",from=\"0\"} 1",
+            "nio_task_remote_wakeups_total{worker=\"1\",from=\"external\"} 1",
+            "nio_task_remote_wakeups_total{worker=\"0\",from=\"1\"} 0",
+            "nio_worker_queue_depth{worker=\"1\",queue=\"local\"} 2",
+            "nio_blocking_threads{state=\"busy\"} 2",
+            "nio_blocking_max_queue_latency_seconds 0.003",
+            "nio_blocking_threads_exited_total 0",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing `{line}` in:\n{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let rt = runtime();
+        rt.0.task_poll_time(0, Duration::from_micros(500));
+        rt.0.task_poll_time(0, Duration::from_millis(2));
+        rt.0.task_poll_time(0, Duration::from_secs(1));
+
+        let text = prometheus(&rt);
+        let buckets: Vec<_> = text
+            .lines()
+            .filter(|l| l.starts_with("nio_task_poll_duration_seconds_bucket{worker=\"0\""))
+            .collect();
+        assert_eq!(
+            buckets.first().unwrap(),
+            &"nio_task_poll_duration_seconds_bucket{worker=\"0\",le=\"0.000524288\"} 1"
+        );
+        assert_eq!(
+            buckets.last().unwrap(),
+            &"nio_task_poll_duration_seconds_bucket{worker=\"0\",le=\"+Inf\"} 3"
+        );
+        assert!(
+            text.lines()
+                .any(|l| l == "nio_task_poll_duration_seconds_count{worker=\"0\"} 3")
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt,
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::Duration,
+};
+
+/// Layout of a [`Histogram`].
+///
+/// Buckets are log-linear: every power of two is split into
+/// `2^precision` buckets of equal width. So the relative error of a bucket
+/// is at most `1 / 2^precision`, at any scale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistogramConfig {
+    resolution: Duration,
+    precision: u32,
+    max: Duration,
+}
+
+impl Default for HistogramConfig {
+    /// From 1µs to about 16s, with 4 buckets per power of two (25% error).
+    fn default() -> Self {
+        HistogramConfig {
+            resolution: Duration::from_micros(1),
+            precision: 2,
+            max: Duration::from_secs(16),
+        }
+    }
+}
+
+impl HistogramConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Width of the smallest buckets, rounded down to a power of two nanoseconds.
+    pub fn resolution(mut self, resolution: Duration) -> Self {
+        assert!(!resolution.is_zero());
+        self.resolution = resolution;
+        self
+    }
+
+    /// `log2` of the number of buckets per power of two, between 0 and 10.
+    pub fn precision(mut self, precision: u32) -> Self {
+        assert!(precision <= 10);
+        self.precision = precision;
+        self
+    }
+
+    /// Values above `max` are counted in the last bucket.
+    pub fn max(mut self, max: Duration) -> Self {
+        self.max = max;
+        self
+    }
+
+    fn unit_shift(&self) -> u32 {
+        63 - nanos(self.resolution).max(1).leading_zeros()
+    }
+
+    /// Index of the bucket of `nanos`, ignoring the number of buckets.
+    fn index_of(&self, nanos: u64) -> usize {
+        let value = nanos >> self.unit_shift();
+        let linear = 1u64 << self.precision;
+        if value < linear {
+            return value as usize;
+        }
+        let msb = 63 - value.leading_zeros();
+        let group = msb - self.precision + 1;
+        let sub = (value >> (msb - self.precision)) & (linear - 1);
+        ((group as u64) << self.precision | sub) as usize
+    }
+
+    /// Range of nanoseconds counted by the bucket at `index`.
+    fn range_of(&self, index: usize) -> Range<u64> {
+        let index = index as u64;
+        let group = index >> self.precision;
+        let sub = index & ((1 << self.precision) - 1);
+        let (start, width) = match group {
+            0 => (sub, 1),
+            _ => (
+                ((1 << self.precision) | sub) << (group - 1),
+                1 << (group - 1),
+            ),
+        };
+        let shift = self.unit_shift();
+        (start << shift)..((start + width) << shift)
+    }
+
+    fn len(&self) -> usize {
+        self.index_of(nanos(self.max)) + 1
+    }
+}
+
+fn nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// A lock-free histogram of durations, see [`HistogramConfig`].
+pub struct Histogram {
+    config: HistogramConfig,
+    buckets: Box<[AtomicU64]>,
+    sum: AtomicU64,
+}
+
+/// A bucket of a [`Histogram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bucket {
+    /// The last bucket also counts every value above its range.
+    pub range: Range<Duration>,
+    pub count: u64,
+}
+
+impl Histogram {
+    pub fn new(config: HistogramConfig) -> Histogram {
+        Histogram {
+            config,
+            buckets: (0..config.len()).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &HistogramConfig {
+        &self.config
+    }
+
+    pub fn record(&self, duration: Duration) {
+        let nanos = nanos(duration);
+        let index = self.config.index_of(nanos).min(self.buckets.len() - 1);
+        self.buckets[index].fetch_add(1, Relaxed);
+        self.sum.fetch_add(nanos, Relaxed);
+    }
+
+    /// Number of recorded values.
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.load(Relaxed)).sum()
+    }
+
+    /// Sum of the recorded values.
+    pub fn sum(&self) -> Duration {
+        Duration::from_nanos(self.sum.load(Relaxed))
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = Bucket> + '_ {
+        self.buckets.iter().enumerate().map(|(index, count)| {
+            let range = self.config.range_of(index);
+            Bucket {
+                range: Duration::from_nanos(range.start)..Duration::from_nanos(range.end),
+                count: count.load(Relaxed),
+            }
+        })
+    }
+
+    /// Upper bound of the bucket containing the `q` quantile, `None` if empty.
+    ///
+    /// # Panics
+    /// If `q` is not between 0 and 1.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        assert!((0.0..=1.0).contains(&q));
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((q * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        let mut last = None;
+        for bucket in self.buckets() {
+            if bucket.count == 0 {
+                continue;
+            }
+            seen += bucket.count;
+            last = Some(bucket.range.end);
+            if seen >= rank {
+                break;
+            }
+        }
+        last
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count())
+            .field("sum", &self.sum())
+            .field("p50", &self.quantile(0.5))
+            .field("p99", &self.quantile(0.99))
+            .field("max", &self.quantile(1.0))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_are_contiguous() {
+        for precision in 0..4 {
+            let config = HistogramConfig::new()
+                .resolution(Duration::from_nanos(100))
+                .precision(precision);
+            let mut end = 0;
+            for index in 0..config.len() {
+                let range = config.range_of(index);
+                assert_eq!(range.start, end);
+                assert!(range.end > range.start);
+                end = range.end;
+            }
+            assert!(end > nanos(config.max));
+        }
+    }
+
+    #[test]
+    fn values_fall_in_their_bucket() {
+        let config = HistogramConfig::new();
+        for nanos in [
+            0,
+            1,
+            1023,
+            1024,
+            5000,
+            123_456,
+            1_000_000_007,
+            16_000_000_000,
+        ] {
+            let range = config.range_of(config.index_of(nanos));
+            assert!(range.contains(&nanos), "{nanos} not in {range:?}");
+        }
+    }
+
+    #[test]
+    fn record() {
+        let histogram = Histogram::new(HistogramConfig::new().max(Duration::from_millis(1)));
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_micros(100));
+        // Above `max`, counted in the last bucket.
+        histogram.record(Duration::from_secs(1));
+
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(
+            histogram.sum(),
+            Duration::from_secs(1) + Duration::from_micros(120)
+        );
+        let p50 = histogram.quantile(0.5).unwrap();
+        assert!(p50 > Duration::from_micros(10) && p50 <= Duration::from_micros(13));
+        let last = histogram.buckets().last().unwrap();
+        assert_eq!(last.count, 1);
+        assert!(histogram.quantile(1.0).unwrap() >= Duration::from_millis(1));
+    }
+}
@@ -1,29 +1,243 @@
+pub mod export;
+mod histogram;
+
+pub use histogram::{Bucket, Histogram, HistogramConfig};
+
 use std::{
     any::Any,
     fmt::Debug,
-    sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+    time::Duration,
 };
 
+/// How a task was spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpawnKind {
+    /// `spawn`, a `Send` task that may run on any worker.
+    Spawn,
+    /// `spawn_pinned` and `spawn_pinned_at`, a task bound to the worker it was sent to.
+    Pinned,
+    /// `spawn_local`, a task bound to the current worker.
+    Local,
+    /// `spawn_blocking`, a closure run on the blocking thread pool.
+    Blocking,
+}
+
+impl SpawnKind {
+    pub const ALL: [SpawnKind; 4] = [
+        SpawnKind::Spawn,
+        SpawnKind::Pinned,
+        SpawnKind::Local,
+        SpawnKind::Blocking,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SpawnKind::Spawn => "spawn",
+            SpawnKind::Pinned => "pinned",
+            SpawnKind::Local => "local",
+            SpawnKind::Blocking => "blocking",
+        }
+    }
+}
+
+/// Hooks called by the runtime, only with the `metrics` feature of `nio`.
+///
+/// Every hook is called on the hot path, implementations should be cheap.
 pub trait Measurement: Debug + Send + Sync + Any {
     fn init(&mut self, _worker_threads: usize) {}
     fn queue_drained(&self, _worker_id: usize) {}
     fn queue_notified(&self, _worker_id: usize) {}
+
+    /// A task was spawned onto `worker_id`, `None` for [`SpawnKind::Blocking`].
+    fn task_spawned(&self, _worker_id: Option<usize>, _kind: SpawnKind) {}
+    fn task_polled(&self, _worker_id: usize) {}
+    /// The task woke itself while being polled, and was put back in the local queue.
+    fn task_yielded(&self, _worker_id: usize) {}
+    fn task_completed(&self, _worker_id: usize) {}
+    /// A task pinned to `worker_id` was woken by worker `from`, or by a thread
+    /// outside the runtime, and sent through the shared queue of `worker_id`.
+    fn task_woken_remote(&self, _worker_id: usize, _from: Option<usize>) {}
+
+    /// The worker has no work left and waits for I/O, timers or other workers.
+    /// `busy` is the time spent since it was last unparked.
+    fn worker_parked(&self, _worker_id: usize, _busy: Duration) {}
+    /// The worker woke up after being `idle` for this long.
+    fn worker_unparked(&self, _worker_id: usize, _idle: Duration) {}
+
+    /// I/O events dispatched to their resources, after a poll of the driver.
+    fn io_events(&self, _worker_id: usize, _count: usize) {}
+    /// Timers fired, after each tick.
+    fn timers_fired(&self, _worker_id: usize, _count: usize) {}
+    /// Tasks moved from the shared queue of the worker to its local queue.
+    fn tasks_transferred(&self, _worker_id: usize, _count: usize) {}
+    /// Number of tasks in the local queue, sampled once per tick.
+    fn local_queue_depth(&self, _worker_id: usize, _depth: usize) {}
+
+    /// Whether the runtime should time task polls and scheduling delays.
+    ///
+    /// Queried once, after [`Measurement::init`]. Timing costs two clock
+    /// reads per poll.
+    fn timing_enabled(&self) -> bool {
+        false
+    }
+    /// Time spent in a single poll of a task.
+    fn task_poll_time(&self, _worker_id: usize, _duration: Duration) {}
+    /// Time a task waited in the queues, from being scheduled to being polled.
+    fn task_scheduling_delay(&self, _worker_id: usize, _duration: Duration) {}
+
+    fn poll_time_histogram(&self, _worker_id: usize) -> Option<&Histogram> {
+        None
+    }
+    fn scheduling_delay_histogram(&self, _worker_id: usize) -> Option<&Histogram> {
+        None
+    }
 }
 
+/// Counters of a single worker, see [`SimpleMeasurement::worker`].
 #[derive(Debug, Default)]
-struct TaskQueue {
+pub struct WorkerMetrics {
     drained: AtomicUsize,
     notified: AtomicUsize,
+
+    spawned: [AtomicU64; 3],
+    polled: AtomicU64,
+    yielded: AtomicU64,
+    completed: AtomicU64,
+    /// Indexed by the waking worker, the last slot counts other threads.
+    woken_remote: Box<[AtomicU64]>,
+
+    parked: AtomicU64,
+    unparked: AtomicU64,
+    busy_nanos: AtomicU64,
+    idle_nanos: AtomicU64,
+
+    io_events: AtomicU64,
+    timers_fired: AtomicU64,
+    tasks_transferred: AtomicU64,
+    local_queue_depth: AtomicUsize,
+
+    poll_time: Option<Histogram>,
+    scheduling_delay: Option<Histogram>,
+}
+
+impl WorkerMetrics {
+    /// How many times the worker found its queues empty and accepted notifications.
+    pub fn queue_drained(&self) -> usize {
+        self.drained.load(Relaxed)
+    }
+
+    /// How many times another thread woke the worker up to run a task.
+    pub fn queue_notified(&self) -> usize {
+        self.notified.load(Relaxed)
+    }
+
+    /// Tasks of `kind` sent to this worker, always `0` for [`SpawnKind::Blocking`].
+    pub fn spawned(&self, kind: SpawnKind) -> u64 {
+        match kind {
+            SpawnKind::Spawn => self.spawned[0].load(Relaxed),
+            SpawnKind::Pinned => self.spawned[1].load(Relaxed),
+            SpawnKind::Local => self.spawned[2].load(Relaxed),
+            SpawnKind::Blocking => 0,
+        }
+    }
+
+    pub fn polled(&self) -> u64 {
+        self.polled.load(Relaxed)
+    }
+
+    pub fn yielded(&self) -> u64 {
+        self.yielded.load(Relaxed)
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.completed.load(Relaxed)
+    }
+
+    /// Pinned tasks of this worker woken by worker `from`, or by a thread
+    /// outside the runtime, such as the blocking pool, for `None`.
+    ///
+    /// # Panics
+    /// If `from` is out of range.
+    pub fn woken_remote(&self, from: Option<usize>) -> u64 {
+        let external = self.woken_remote.len() - 1;
+        self.woken_remote[from.unwrap_or(external)].load(Relaxed)
+    }
+
+    /// Pinned tasks of this worker woken from any other thread.
+    pub fn woken_remote_total(&self) -> u64 {
+        self.woken_remote.iter().map(|c| c.load(Relaxed)).sum()
+    }
+
+    pub fn parked(&self) -> u64 {
+        self.parked.load(Relaxed)
+    }
+
+    pub fn unparked(&self) -> u64 {
+        self.unparked.load(Relaxed)
+    }
+
+    /// Total time spent running tasks, timers and I/O events.
+    pub fn busy_time(&self) -> Duration {
+        Duration::from_nanos(self.busy_nanos.load(Relaxed))
+    }
+
+    /// Total time spent parked.
+    pub fn idle_time(&self) -> Duration {
+        Duration::from_nanos(self.idle_nanos.load(Relaxed))
+    }
+
+    pub fn io_events(&self) -> u64 {
+        self.io_events.load(Relaxed)
+    }
+
+    pub fn timers_fired(&self) -> u64 {
+        self.timers_fired.load(Relaxed)
+    }
+
+    pub fn tasks_transferred(&self) -> u64 {
+        self.tasks_transferred.load(Relaxed)
+    }
+
+    /// Last sampled number of tasks in the local queue.
+    pub fn local_queue_depth(&self) -> usize {
+        self.local_queue_depth.load(Relaxed)
+    }
+
+    /// `None` unless enabled with [`SimpleMeasurement::histograms`].
+    pub fn poll_time(&self) -> Option<&Histogram> {
+        self.poll_time.as_ref()
+    }
+
+    /// `None` unless enabled with [`SimpleMeasurement::histograms`].
+    pub fn scheduling_delay(&self) -> Option<&Histogram> {
+        self.scheduling_delay.as_ref()
+    }
+}
+
+fn add_nanos(counter: &AtomicU64, duration: Duration) {
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    counter.fetch_add(nanos, Relaxed);
 }
 
 #[derive(Debug, Default)]
 pub struct SimpleMeasurement {
-    workers: Vec<TaskQueue>,
+    workers: Vec<WorkerMetrics>,
+    blocking_spawned: AtomicU64,
+    histograms: Option<HistogramConfig>,
 }
 
 impl Measurement for SimpleMeasurement {
     fn init(&mut self, worker_threads: usize) {
-        self.workers = (0..worker_threads).map(|_| TaskQueue::default()).collect();
+        let histogram = || self.histograms.map(Histogram::new);
+        self.workers = (0..worker_threads)
+            .map(|_| WorkerMetrics {
+                poll_time: histogram(),
+                scheduling_delay: histogram(),
+                woken_remote: (0..=worker_threads).map(|_| AtomicU64::new(0)).collect(),
+                ..WorkerMetrics::default()
+            })
+            .collect();
     }
 
     fn queue_drained(&self, worker_id: usize) {
@@ -33,10 +247,122 @@ impl Measurement for SimpleMeasurement {
     fn queue_notified(&self, worker_id: usize) {
         self.workers[worker_id].notified.fetch_add(1, Relaxed);
     }
+
+    fn task_spawned(&self, worker_id: Option<usize>, kind: SpawnKind) {
+        let counter = match (worker_id, kind) {
+            (_, SpawnKind::Blocking) | (None, _) => &self.blocking_spawned,
+            (Some(id), SpawnKind::Spawn) => &self.workers[id].spawned[0],
+            (Some(id), SpawnKind::Pinned) => &self.workers[id].spawned[1],
+            (Some(id), SpawnKind::Local) => &self.workers[id].spawned[2],
+        };
+        counter.fetch_add(1, Relaxed);
+    }
+
+    fn task_polled(&self, worker_id: usize) {
+        self.workers[worker_id].polled.fetch_add(1, Relaxed);
+    }
+
+    fn task_yielded(&self, worker_id: usize) {
+        self.workers[worker_id].yielded.fetch_add(1, Relaxed);
+    }
+
+    fn task_completed(&self, worker_id: usize) {
+        self.workers[worker_id].completed.fetch_add(1, Relaxed);
+    }
+
+    fn task_woken_remote(&self, worker_id: usize, from: Option<usize>) {
+        let counters = &self.workers[worker_id].woken_remote;
+        counters[from.unwrap_or(counters.len() - 1)].fetch_add(1, Relaxed);
+    }
+
+    fn worker_parked(&self, worker_id: usize, busy: Duration) {
+        let worker = &self.workers[worker_id];
+        worker.parked.fetch_add(1, Relaxed);
+        add_nanos(&worker.busy_nanos, busy);
+    }
+
+    fn worker_unparked(&self, worker_id: usize, idle: Duration) {
+        let worker = &self.workers[worker_id];
+        worker.unparked.fetch_add(1, Relaxed);
+        add_nanos(&worker.idle_nanos, idle);
+    }
+
+    fn io_events(&self, worker_id: usize, count: usize) {
+        self.workers[worker_id]
+            .io_events
+            .fetch_add(count as u64, Relaxed);
+    }
+
+    fn timers_fired(&self, worker_id: usize, count: usize) {
+        self.workers[worker_id]
+            .timers_fired
+            .fetch_add(count as u64, Relaxed);
+    }
+
+    fn tasks_transferred(&self, worker_id: usize, count: usize) {
+        self.workers[worker_id]
+            .tasks_transferred
+            .fetch_add(count as u64, Relaxed);
+    }
+
+    fn local_queue_depth(&self, worker_id: usize, depth: usize) {
+        self.workers[worker_id]
+            .local_queue_depth
+            .store(depth, Relaxed);
+    }
+
+    fn timing_enabled(&self) -> bool {
+        self.histograms.is_some()
+    }
+
+    fn task_poll_time(&self, worker_id: usize, duration: Duration) {
+        if let Some(histogram) = &self.workers[worker_id].poll_time {
+            histogram.record(duration);
+        }
+    }
+
+    fn task_scheduling_delay(&self, worker_id: usize, duration: Duration) {
+        if let Some(histogram) = &self.workers[worker_id].scheduling_delay {
+            histogram.record(duration);
+        }
+    }
+
+    fn poll_time_histogram(&self, worker_id: usize) -> Option<&Histogram> {
+        self.workers[worker_id].poll_time()
+    }
+
+    fn scheduling_delay_histogram(&self, worker_id: usize) -> Option<&Histogram> {
+        self.workers[worker_id].scheduling_delay()
+    }
 }
 
 impl SimpleMeasurement {
     pub fn new() -> SimpleMeasurement {
-        SimpleMeasurement { workers: vec![] }
+        SimpleMeasurement {
+            workers: vec![],
+            blocking_spawned: AtomicU64::new(0),
+            histograms: None,
+        }
+    }
+
+    /// Records poll times and scheduling delays of every worker in histograms.
+    pub fn histograms(mut self, config: HistogramConfig) -> Self {
+        self.histograms = Some(config);
+        self
+    }
+
+    pub fn workers(&self) -> &[WorkerMetrics] {
+        &self.workers
+    }
+
+    /// # Panics
+    /// If `worker_id` is out of range.
+    pub fn worker(&self, worker_id: usize) -> &WorkerMetrics {
+        &self.workers[worker_id]
+    }
+
+    /// Closures sent to the blocking thread pool.
+    pub fn blocking_spawned(&self) -> u64 {
+        self.blocking_spawned.load(Relaxed)
     }
 }
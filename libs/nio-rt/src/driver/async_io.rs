@@ -24,11 +24,35 @@ impl<Io: Source> AsyncIO<Io> {
         let waker = IoWaker::new();
         let token = mio::Token(waker.addr());
 
-        LocalContext::with(|ctx| ctx.io_registry.register(&mut io, token, interests))?;
+        let _worker = LocalContext::with(|ctx| {
+            ctx.io_registry.register(&mut io, token, interests)?;
+            Result::Ok(ctx.worker_id)
+        })?;
+
+        #[cfg(feature = "tracing")]
+        {
+            let resource = std::any::type_name::<Io>();
+            waker.origin.set(Some((resource, _worker)));
+            tracing::trace!(
+                target: "nio::io",
+                token = token.0,
+                ?interests,
+                resource,
+                worker = _worker.get(),
+                "register"
+            );
+        }
 
         Ok(Self { io, waker })
     }
 
+    /// Records what the current task waits for, with the `task-dump` feature.
+    #[inline]
+    fn awaiting(&self, _op: &'static str) {
+        #[cfg(feature = "task-dump")]
+        crate::rt::dump::awaiting(_op, std::any::type_name::<Io>());
+    }
+
     pub fn io_read<'a, F, T>(
         &'a self,
         mut f: F,
@@ -48,6 +72,7 @@ impl<Io: Source> AsyncIO<Io> {
                     res => return Poll::Ready(res),
                 }
             }
+            self.awaiting("read");
             Poll::Pending
         })
     }
@@ -71,6 +96,7 @@ impl<Io: Source> AsyncIO<Io> {
                     res => return Poll::Ready(res),
                 }
             }
+            self.awaiting("write");
             Poll::Pending
         })
     }
@@ -81,6 +107,7 @@ impl<Io: Source> AsyncIO<Io> {
             if self.waker.readiness().is_writable() {
                 return Poll::Ready(());
             }
+            self.awaiting("writable");
             Poll::Pending
         })
     }
@@ -116,6 +143,7 @@ impl<Io: Source> AsyncIO<Io> {
                 Err(err) => return Poll::Ready(Err(err)),
             }
         }
+        self.awaiting("read");
         Poll::Pending
     }
 
@@ -145,15 +173,43 @@ impl<Io: Source> AsyncIO<Io> {
                 Err(err) => return Poll::Ready(Err(err)),
             }
         }
+        self.awaiting("write");
         Poll::Pending
     }
-}
 
-impl<Io: Source> Drop for AsyncIO<Io> {
-    fn drop(&mut self) {
+    /// Deregisters the resource, and returns it without closing it.
+    pub fn into_inner(self) -> Io {
+        let mut this = std::mem::ManuallyDrop::new(self);
+        this.deregister();
+        // SAFETY: `this` is never used or dropped again.
+        unsafe {
+            drop(std::ptr::read(&this.waker));
+            std::ptr::read(&this.io)
+        }
+    }
+
+    fn deregister(&mut self) {
         LocalContext::with(|ctx| {
-            let _ = ctx.io_registry.deregister(&mut self.io);
+            let _result = ctx.io_registry.deregister(&mut self.io);
+
+            #[cfg(feature = "tracing")]
+            match _result {
+                Ok(()) => {
+                    tracing::trace!(target: "nio::io", token = self.waker.addr(), "deregister")
+                }
+                Err(err) => tracing::debug!(
+                    target: "nio::io",
+                    token = self.waker.addr(),
+                    "deregister failed: {err}"
+                ),
+            }
         });
         self.waker.drop_waker();
     }
 }
+
+impl<Io: Source> Drop for AsyncIO<Io> {
+    fn drop(&mut self) {
+        self.deregister();
+    }
+}
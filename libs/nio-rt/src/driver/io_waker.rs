@@ -29,6 +29,9 @@ pub struct IoWaker {
     readiness: Cell<u8>,
     pub reader: LocalWaker,
     pub writer: LocalWaker,
+    /// Type of the resource, and the worker it is registered with.
+    #[cfg(feature = "tracing")]
+    pub origin: Cell<Option<(&'static str, crate::WorkerId)>>,
 }
 
 impl IoWaker {
@@ -93,14 +96,34 @@ impl IoWaker {
 
         self.readiness.set(readiness);
 
-        if readiness & Readiness::READ_MASK != 0 {
-            self.reader.wake();
-        }
-        if readiness & Readiness::WRITE_MASK != 0 {
-            self.writer.wake();
+        let wake = || {
+            if readiness & Readiness::READ_MASK != 0 {
+                self.reader.wake();
+            }
+            if readiness & Readiness::WRITE_MASK != 0 {
+                self.writer.wake();
+            }
+        };
+        #[cfg(feature = "tracing")]
+        match self.origin.get() {
+            Some((resource, worker)) => {
+                let token = ev.token().0;
+                crate::rt::trace::io_dispatch(token, resource, worker, wake)
+            }
+            None => wake(),
         }
+        #[cfg(not(feature = "tracing"))]
+        wake();
 
-        #[cfg(debug_assertions)]
+        #[cfg(feature = "tracing")]
+        if readiness == 0 {
+            if ev.is_error() {
+                tracing::warn!(target: "nio::io", "error without readiness: {ev:?}");
+            } else {
+                tracing::debug!(target: "nio::io", "without readiness: {ev:?}");
+            }
+        }
+        #[cfg(all(debug_assertions, not(feature = "tracing")))]
         if readiness == 0 {
             if ev.is_error() {
                 eprintln!("error without readiness: {ev:#?}");
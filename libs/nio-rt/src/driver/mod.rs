@@ -1,5 +1,7 @@
 mod async_io;
 mod io_waker;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
 use std::{io::Result, time::Duration};
 
 pub use async_io::AsyncIO;
@@ -0,0 +1,216 @@
+//! Per-worker `io_uring` instance.
+//!
+//! The completion queue signals an `eventfd`, which is registered with the
+//! worker's [`mio::Poll`], so completions are handled by the same event loop
+//! as readiness events.
+//!
+//! Operations may complete on a worker other than the one that polls them,
+//! (`Send` tasks move between workers) so the completion state is shared
+//! through an [`Arc`].
+
+use crate::rt::context::NioContext;
+use io_uring::{IoUring, Probe, opcode, squeue};
+use mio::{Interest, Registry, Token, unix::SourceFd};
+use std::{
+    cell::RefCell,
+    io::{self, Read},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+/// Heap addresses of `IoWaker` are used as tokens, they are never `1`.
+pub const URING_TOKEN: Token = Token(1);
+
+const ENTRIES: u32 = 256;
+
+/// Number of entries submitted to any ring, to check that tests take this path.
+#[cfg(test)]
+pub static SUBMITTED: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+pub struct Uring {
+    ring: RefCell<IoUring>,
+    eventfd: std::fs::File,
+}
+
+impl Uring {
+    /// Returns an error if the kernel doesn't support `io_uring`, or any of
+    /// the operations used by Nio.
+    pub fn new(registry: &Registry) -> io::Result<Uring> {
+        let ring = IoUring::new(ENTRIES)?;
+
+        let mut probe = Probe::new();
+        ring.submitter().register_probe(&mut probe)?;
+        let supported = [
+            opcode::Read::CODE,
+            opcode::Write::CODE,
+            opcode::OpenAt::CODE,
+        ];
+        if !supported.into_iter().all(|code| probe.is_supported(code)) {
+            return Err(io::ErrorKind::Unsupported.into());
+        }
+
+        // SAFETY: `eventfd` has no memory safety requirements.
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a newly opened file descriptor owned by us.
+        let eventfd = std::fs::File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        ring.submitter().register_eventfd(eventfd.as_raw_fd())?;
+        registry.register(
+            &mut SourceFd(&eventfd.as_raw_fd()),
+            URING_TOKEN,
+            Interest::READABLE,
+        )?;
+
+        Ok(Uring {
+            ring: RefCell::new(ring),
+            eventfd,
+        })
+    }
+
+    /// Wakes operations whose completion entries are ready.
+    pub fn complete(&self) {
+        let mut buf = [0; 8];
+        let _ = (&self.eventfd).read(&mut buf);
+
+        let mut ring = self.ring.borrow_mut();
+        for cqe in ring.completion() {
+            // SAFETY: `user_data` was created by `Box::into_raw` in `submit`.
+            let op = unsafe { Box::from_raw(cqe.user_data() as *mut Arc<dyn Complete>) };
+            op.complete(cqe.result());
+        }
+    }
+
+    fn push(&self, entry: &squeue::Entry) -> bool {
+        let mut ring = self.ring.borrow_mut();
+        // SAFETY: Guaranteed by the caller of `submit`.
+        if unsafe { ring.submission().push(entry) }.is_err() {
+            // The queue is full, flush it and try again.
+            if ring.submit().is_err() || unsafe { ring.submission().push(entry) }.is_err() {
+                return false;
+            }
+        }
+        ring.submit().is_ok()
+    }
+}
+
+/// Submits `entry` to the ring of the current worker.
+///
+/// Returns `data` back, if the current thread has no ring, or the entry
+/// couldn't be submitted. In that case the caller should fall back to the
+/// blocking thread pool.
+///
+/// # Safety
+///
+/// Memory referenced by `entry` must be owned by `data` (and not move when
+/// `data` is moved, e.g. the heap buffer of a `Vec`), or be `'static`.
+pub unsafe fn submit<T>(entry: squeue::Entry, data: T) -> Result<Op<T>, T>
+where
+    T: Send + 'static,
+{
+    NioContext::get(|ctx| {
+        let NioContext::Local(ctx) = ctx else {
+            return Err(data);
+        };
+        let Some(uring) = &ctx.uring else {
+            return Err(data);
+        };
+
+        let shared = Arc::new(Shared {
+            state: Mutex::new(Lifecycle::Submitted),
+            data: Mutex::new(Some(data)),
+        });
+        let user_data = Box::into_raw(Box::new(shared.clone() as Arc<dyn Complete>));
+
+        if uring.push(&entry.user_data(user_data as u64)) {
+            #[cfg(test)]
+            SUBMITTED.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            return Ok(Op { shared });
+        }
+        // SAFETY: The entry wasn't submitted, we are the only owner of `user_data`.
+        drop(unsafe { Box::from_raw(user_data) });
+        let data = shared.data.lock().unwrap().take().unwrap();
+        Err(data)
+    })
+}
+
+/// Returns `true` if the current worker has a ring.
+pub fn is_available() -> bool {
+    NioContext::get(|ctx| match ctx {
+        NioContext::Local(ctx) => ctx.uring.is_some(),
+        NioContext::None | NioContext::Runtime(_) => false,
+    })
+}
+
+trait Complete: Send + Sync {
+    fn complete(&self, result: i32);
+}
+
+enum Lifecycle {
+    Submitted,
+    Waiting(Waker),
+    Completed(i32),
+}
+
+struct Shared<T> {
+    state: Mutex<Lifecycle>,
+    /// Owns the memory referenced by the submitted entry, until the
+    /// operation is completed.
+    data: Mutex<Option<T>>,
+}
+
+impl<T: Send> Complete for Shared<T> {
+    fn complete(&self, result: i32) {
+        let state = std::mem::replace(
+            &mut *self.state.lock().unwrap(),
+            Lifecycle::Completed(result),
+        );
+        if let Lifecycle::Waiting(waker) = state {
+            waker.wake();
+        }
+    }
+}
+
+/// An in-flight operation.
+///
+/// Dropping it doesn't cancel the operation, `data` is kept alive until the
+/// operation is completed.
+pub struct Op<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Future for Op<T> {
+    /// The result of the operation (`res` field of the completion entry)
+    /// and `data` passed to [`submit`].
+    type Output = (io::Result<u32>, T);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        match *state {
+            Lifecycle::Completed(res) => {
+                let data = self
+                    .shared
+                    .data
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .expect("polled after completion");
+                let res = if res < 0 {
+                    Err(io::Error::from_raw_os_error(-res))
+                } else {
+                    Ok(res as u32)
+                };
+                Poll::Ready((res, data))
+            }
+            Lifecycle::Waiting(ref waker) if waker.will_wake(cx.waker()) => Poll::Pending,
+            _ => {
+                *state = Lifecycle::Waiting(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -2,32 +2,36 @@
 //!
 //! [`File`]: File
 
-use crate::fs::{asyncify, OpenOptions};
-use crate::io::blocking::{Buf, DEFAULT_MAX_BUF_SIZE};
-use crate::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
-use crate::sync::Mutex;
+use crate::fs::{OpenOptions, asyncify};
+use crate::io::{Buf, DEFAULT_MAX_BUF_SIZE};
 
+use std::cmp;
 use std::fmt;
 use std::fs::{Metadata, Permissions};
-use std::future::Future;
+use std::future::poll_fn;
 use std::io::{self, Seek, SeekFrom};
 use std::path::Path;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::task::{ready, Context, Poll};
-
-// #[cfg(test)]
-// use super::mocks::JoinHandle;
-// #[cfg(test)]
-// use super::mocks::MockFile as StdFile;
-// #[cfg(test)]
-// use super::mocks::{spawn_blocking, spawn_mandatory_blocking};
-// #[cfg(not(test))]
-use crate::blocking::JoinHandle;
-// #[cfg(not(test))]
-use crate::blocking::spawn_blocking;
-// #[cfg(not(test))]
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, ready};
+
+#[cfg(test)]
+use super::mocks::JoinHandle;
+#[cfg(test)]
+use super::mocks::MockFile as StdFile;
+#[cfg(test)]
+use super::mocks::spawn_blocking;
+#[cfg(all(not(test), not(all(feature = "io-uring", target_os = "linux"))))]
+use crate::JoinHandle;
+#[cfg(all(not(test), not(all(feature = "io-uring", target_os = "linux"))))]
+use crate::spawn_blocking;
+#[cfg(not(test))]
 use std::fs::File as StdFile;
+#[cfg(all(feature = "io-uring", target_os = "linux", not(test)))]
+use uring::{JoinHandle, spawn_blocking};
+
+#[cfg(all(feature = "io-uring", target_os = "linux", not(test)))]
+mod uring;
 
 /// A reference to an open file on the filesystem.
 ///
@@ -47,13 +51,15 @@ use std::fs::File as StdFile;
 /// the data to disk.
 ///
 /// Reading and writing to a `File` is usually done using the convenience
-/// methods found on the [`AsyncReadExt`] and [`AsyncWriteExt`] traits.
+/// methods found on the [`AsyncReadExt`] and [`AsyncWriteExt`] traits of the
+/// `futures` crate. With the `tokio-io` feature, `File` implements the Tokio
+/// I/O traits as well.
 ///
-/// [`AsyncSeek`]: trait@crate::io::AsyncSeek
-/// [`flush`]: fn@crate::io::AsyncWriteExt::flush
+/// [`AsyncSeek`]: https://docs.rs/futures/latest/futures/io/trait.AsyncSeek.html
+/// [`flush`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.flush
 /// [`sync_all`]: fn@crate::fs::File::sync_all
-/// [`AsyncReadExt`]: trait@crate::io::AsyncReadExt
-/// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+/// [`AsyncReadExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncReadExt.html
+/// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
 ///
 /// # Examples
 ///
@@ -61,7 +67,7 @@ use std::fs::File as StdFile;
 ///
 /// ```no_run
 /// use nio::fs::File;
-/// use nio::io::AsyncWriteExt; // for write_all()
+/// use futures::AsyncWriteExt; // for write_all()
 ///
 /// # async fn dox() -> std::io::Result<()> {
 /// let mut file = File::create("foo.txt").await?;
@@ -74,7 +80,7 @@ use std::fs::File as StdFile;
 ///
 /// ```no_run
 /// use nio::fs::File;
-/// use nio::io::AsyncReadExt; // for read_to_end()
+/// use futures::AsyncReadExt; // for read_to_end()
 ///
 /// # async fn dox() -> std::io::Result<()> {
 /// let mut file = File::open("foo.txt").await?;
@@ -88,6 +94,7 @@ use std::fs::File as StdFile;
 /// ```
 pub struct File {
     std: Arc<StdFile>,
+    /// Only locked by `&self` methods, the lock is never held across an `.await`.
     inner: Mutex<Inner>,
     max_buf_size: usize,
 }
@@ -95,6 +102,9 @@ pub struct File {
 struct Inner {
     state: State,
 
+    /// Target of the seek started by `poll_seek` while it is in flight.
+    seeking: Option<SeekFrom>,
+
     /// Errors from writes/flushes are returned in write/flush calls. If a write
     /// error is observed while performing a read, it is saved until the next
     /// write / flush call.
@@ -131,7 +141,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncReadExt;
+    /// use futures::AsyncReadExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::open("foo.txt").await?;
@@ -146,8 +156,8 @@ impl File {
     ///
     /// The [`read_to_end`] method is defined on the [`AsyncReadExt`] trait.
     ///
-    /// [`read_to_end`]: fn@crate::io::AsyncReadExt::read_to_end
-    /// [`AsyncReadExt`]: trait@crate::io::AsyncReadExt
+    /// [`read_to_end`]: https://docs.rs/futures/latest/futures/io/trait.AsyncReadExt.html#method.read_to_end
+    /// [`AsyncReadExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncReadExt.html
     pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
         let path = path.as_ref().to_owned();
         let std = asyncify(|| StdFile::open(path)).await?;
@@ -173,7 +183,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::create("foo.txt").await?;
@@ -184,8 +194,8 @@ impl File {
     ///
     /// The [`write_all`] method is defined on the [`AsyncWriteExt`] trait.
     ///
-    /// [`write_all`]: fn@crate::io::AsyncWriteExt::write_all
-    /// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+    /// [`write_all`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.write_all
+    /// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
     pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
         let path = path.as_ref().to_owned();
         let std_file = asyncify(move || StdFile::create(path)).await?;
@@ -210,7 +220,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::create_new("foo.txt").await?;
@@ -221,8 +231,8 @@ impl File {
     ///
     /// The [`write_all`] method is defined on the [`AsyncWriteExt`] trait.
     ///
-    /// [`write_all`]: fn@crate::io::AsyncWriteExt::write_all
-    /// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+    /// [`write_all`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.write_all
+    /// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
     pub async fn create_new<P: AsRef<Path>>(path: P) -> std::io::Result<File> {
         Self::options()
             .read(true)
@@ -250,7 +260,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut f = File::options().append(true).open("example.log").await?;
@@ -278,6 +288,7 @@ impl File {
             std: Arc::new(std),
             inner: Mutex::new(Inner {
                 state: State::Idle(Some(Buf::with_capacity(0))),
+                seeking: None,
                 last_write_err: None,
                 pos: 0,
             }),
@@ -294,7 +305,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::create("foo.txt").await?;
@@ -306,11 +317,10 @@ impl File {
     ///
     /// The [`write_all`] method is defined on the [`AsyncWriteExt`] trait.
     ///
-    /// [`write_all`]: fn@crate::io::AsyncWriteExt::write_all
-    /// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+    /// [`write_all`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.write_all
+    /// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
     pub async fn sync_all(&self) -> io::Result<()> {
-        let mut inner = self.inner.lock().await;
-        inner.complete_inflight().await;
+        self.complete_inflight().await;
 
         let std = self.std.clone();
         asyncify(move || std.sync_all()).await
@@ -329,7 +339,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::create("foo.txt").await?;
@@ -341,11 +351,10 @@ impl File {
     ///
     /// The [`write_all`] method is defined on the [`AsyncWriteExt`] trait.
     ///
-    /// [`write_all`]: fn@crate::io::AsyncWriteExt::write_all
-    /// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+    /// [`write_all`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.write_all
+    /// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
     pub async fn sync_data(&self) -> io::Result<()> {
-        let mut inner = self.inner.lock().await;
-        inner.complete_inflight().await;
+        self.complete_inflight().await;
 
         let std = self.std.clone();
         asyncify(move || std.sync_data()).await
@@ -367,7 +376,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::create("foo.txt").await?;
@@ -379,50 +388,64 @@ impl File {
     ///
     /// The [`write_all`] method is defined on the [`AsyncWriteExt`] trait.
     ///
-    /// [`write_all`]: fn@crate::io::AsyncWriteExt::write_all
-    /// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+    /// [`write_all`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html#method.write_all
+    /// [`AsyncWriteExt`]: https://docs.rs/futures/latest/futures/io/trait.AsyncWriteExt.html
     pub async fn set_len(&self, size: u64) -> io::Result<()> {
-        let mut inner = self.inner.lock().await;
-        inner.complete_inflight().await;
-
-        let mut buf = match inner.state {
-            State::Idle(ref mut buf_cell) => buf_cell.take().unwrap(),
-            _ => unreachable!(),
-        };
-
-        let seek = if !buf.is_empty() {
-            Some(SeekFrom::Current(buf.discard_read()))
-        } else {
-            None
-        };
-
-        let std = self.std.clone();
+        // The operation may be completed by a concurrent call on this file,
+        // which discards its result, so it is also returned here.
+        let result = Arc::new(Mutex::new(None));
+        let mut started = false;
+        poll_fn(|cx| {
+            let mut inner = self.inner.lock().unwrap();
+
+            if !started {
+                ready!(inner.poll_complete_inflight(cx));
+
+                let mut buf = match inner.state {
+                    State::Idle(ref mut buf_cell) => buf_cell.take().unwrap(),
+                    State::Busy(_) => unreachable!(),
+                };
+
+                let seek = if !buf.is_empty() {
+                    Some(SeekFrom::Current(buf.discard_read()))
+                } else {
+                    None
+                };
+
+                let std = self.std.clone();
+                let result = result.clone();
 
-        inner.state = State::Busy(spawn_blocking(move || {
-            let res = if let Some(seek) = seek {
-                (&*std).seek(seek).and_then(|_| std.set_len(size))
-            } else {
-                std.set_len(size)
+                inner.state = State::Busy(spawn_blocking(move || {
+                    let res = if let Some(seek) = seek {
+                        (&*std).seek(seek).and_then(|_| std.set_len(size))
+                    } else {
+                        std.set_len(size)
+                    };
+                    // Return the result as a seek, the value is discarded later
+                    let op = Operation::Seek(match &res {
+                        Ok(()) => Ok(0),
+                        Err(err) => Err(err.kind().into()),
+                    });
+                    *result.lock().unwrap() = Some(res);
+                    (op, buf)
+                }));
+                started = true;
             }
-            .map(|()| 0); // the value is discarded later
-
-            // Return the result as a seek
-            (Operation::Seek(res), buf)
-        }));
-
-        let (op, buf) = match inner.state {
-            State::Idle(_) => unreachable!(),
-            State::Busy(ref mut rx) => rx.await?,
-        };
 
-        inner.state = State::Idle(Some(buf));
-
-        match op {
-            Operation::Seek(res) => res.map(|pos| {
-                inner.pos = pos;
-            }),
-            _ => unreachable!(),
-        }
+            if result.lock().unwrap().is_none() {
+                // Not completed, so the pending operation is this one.
+                let (op, buf) = match inner.state {
+                    State::Idle(_) => unreachable!(),
+                    State::Busy(ref mut rx) => ready!(Pin::new(rx).poll(cx)).map_err(join_error)?,
+                };
+                inner.state = State::Idle(Some(buf));
+                if let Operation::Seek(Ok(pos)) = op {
+                    inner.pos = pos;
+                }
+            }
+            Poll::Ready(result.lock().unwrap().take().unwrap())
+        })
+        .await
     }
 
     /// Queries metadata about the underlying file.
@@ -461,7 +484,7 @@ impl File {
     /// # }
     /// ```
     pub async fn try_clone(&self) -> io::Result<File> {
-        self.inner.lock().await.complete_inflight().await;
+        self.complete_inflight().await;
         let std = self.std.clone();
         let std_file = asyncify(move || std.try_clone()).await?;
         Ok(File::from_std(std_file))
@@ -483,8 +506,8 @@ impl File {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn into_std(mut self) -> StdFile {
-        self.inner.get_mut().complete_inflight().await;
+    pub async fn into_std(self) -> StdFile {
+        self.complete_inflight().await;
         Arc::try_unwrap(self.std).expect("Arc::try_unwrap failed")
     }
 
@@ -550,7 +573,7 @@ impl File {
         asyncify(move || std.set_permissions(perm)).await
     }
 
-    /// Set the maximum buffer size for the underlying [`AsyncRead`] / [`AsyncWrite`] operation.
+    /// Set the maximum buffer size for the underlying `AsyncRead` / `AsyncWrite` operation.
     ///
     /// Although Nio uses a sensible default value for this buffer size, this function would be
     /// useful for changing that default depending on the situation.
@@ -559,7 +582,7 @@ impl File {
     ///
     /// ```no_run
     /// use nio::fs::File;
-    /// use nio::io::AsyncWriteExt;
+    /// use futures::AsyncWriteExt;
     ///
     /// # async fn dox() -> std::io::Result<()> {
     /// let mut file = File::open("foo.txt").await?;
@@ -579,15 +602,18 @@ impl File {
     }
 }
 
-impl AsyncRead for File {
-    fn poll_read(
-        self: Pin<&mut Self>,
+impl File {
+    async fn complete_inflight(&self) {
+        poll_fn(|cx| self.inner.lock().unwrap().poll_complete_inflight(cx)).await
+    }
+
+    pub(crate) fn poll_read(
+        &mut self,
         cx: &mut Context<'_>,
-        dst: &mut ReadBuf<'_>,
-    ) -> Poll<io::Result<()>> {
-        ready!(crate::trace::trace_leaf(cx));
-        let me = self.get_mut();
-        let inner = me.inner.get_mut();
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let inner = self.inner.get_mut().unwrap();
+        inner.seeking = None;
 
         loop {
             match inner.state {
@@ -595,27 +621,36 @@ impl AsyncRead for File {
                     let mut buf = buf_cell.take().unwrap();
 
                     if !buf.is_empty() {
-                        buf.copy_to(dst);
+                        let n = buf.copy_to(dst);
                         *buf_cell = Some(buf);
-                        return Poll::Ready(Ok(()));
+                        return Poll::Ready(Ok(n));
                     }
 
-                    buf.ensure_capacity_for(dst, me.max_buf_size);
-                    let std = me.std.clone();
+                    let max_buf_size = cmp::min(dst.len(), self.max_buf_size);
+                    let std = self.std.clone();
+
+                    #[cfg(all(feature = "io-uring", target_os = "linux", not(test)))]
+                    let (std, mut buf) = match uring::read(std, buf, max_buf_size) {
+                        Ok(handle) => {
+                            inner.state = State::Busy(handle);
+                            continue;
+                        }
+                        Err(unsubmitted) => unsubmitted,
+                    };
 
                     inner.state = State::Busy(spawn_blocking(move || {
-                        let res = buf.read_from(&mut &*std);
+                        let res = buf.read_from(&mut &*std, max_buf_size);
                         (Operation::Read(res), buf)
                     }));
                 }
                 State::Busy(ref mut rx) => {
-                    let (op, mut buf) = ready!(Pin::new(rx).poll(cx))?;
+                    let (op, mut buf) = ready!(Pin::new(rx).poll(cx)).map_err(join_error)?;
 
                     match op {
                         Operation::Read(Ok(_)) => {
-                            buf.copy_to(dst);
+                            let n = buf.copy_to(dst);
                             inner.state = State::Idle(Some(buf));
-                            return Poll::Ready(Ok(()));
+                            return Poll::Ready(Ok(n));
                         }
                         Operation::Read(Err(e)) => {
                             assert!(buf.is_empty());
@@ -646,16 +681,13 @@ impl AsyncRead for File {
             }
         }
     }
-}
 
-impl AsyncSeek for File {
-    fn start_seek(self: Pin<&mut Self>, mut pos: SeekFrom) -> io::Result<()> {
-        let me = self.get_mut();
-        let inner = me.inner.get_mut();
+    pub(crate) fn start_seek(&mut self, mut pos: SeekFrom) -> io::Result<()> {
+        let inner = self.inner.get_mut().unwrap();
+        let target = pos;
 
         match inner.state {
-            State::Busy(_) => Err(io::Error::new(
-                io::ErrorKind::Other,
+            State::Busy(_) => Err(io::Error::other(
                 "other file operation is pending, call poll_complete before start_seek",
             )),
             State::Idle(ref mut buf_cell) => {
@@ -670,26 +702,26 @@ impl AsyncSeek for File {
                     }
                 }
 
-                let std = me.std.clone();
+                let std = self.std.clone();
 
                 inner.state = State::Busy(spawn_blocking(move || {
                     let res = (&*std).seek(pos);
                     (Operation::Seek(res), buf)
                 }));
+                inner.seeking = Some(target);
                 Ok(())
             }
         }
     }
 
-    fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
-        ready!(crate::trace::trace_leaf(cx));
-        let inner = self.inner.get_mut();
+    pub(crate) fn poll_complete(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let inner = self.inner.get_mut().unwrap();
 
         loop {
             match inner.state {
                 State::Idle(_) => return Poll::Ready(Ok(inner.pos)),
                 State::Busy(ref mut rx) => {
-                    let (op, buf) = ready!(Pin::new(rx).poll(cx))?;
+                    let (op, buf) = ready!(Pin::new(rx).poll(cx)).map_err(join_error)?;
                     inner.state = State::Idle(Some(buf));
 
                     match op {
@@ -700,6 +732,7 @@ impl AsyncSeek for File {
                         }
                         Operation::Write(_) => {}
                         Operation::Seek(res) => {
+                            inner.seeking = None;
                             if let Ok(pos) = res {
                                 inner.pos = pos;
                             }
@@ -710,17 +743,55 @@ impl AsyncSeek for File {
             }
         }
     }
-}
 
-impl AsyncWrite for File {
-    fn poll_write(
-        self: Pin<&mut Self>,
+    /// Seeks in a single call, as `futures_io::AsyncSeek` does.
+    ///
+    /// Pending operations are completed first, then the seek is started and
+    /// polled until it completes. A seek left in flight by a dropped call is
+    /// resumed if it has the same target, otherwise it is completed and its
+    /// result discarded.
+    #[cfg(feature = "futures-io")]
+    pub(crate) fn poll_seek(
+        &mut self,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        let seeking = self.inner.get_mut().unwrap().seeking;
+        if seeking != Some(pos) {
+            let res = ready!(self.poll_complete(cx));
+            if seeking.is_none() {
+                res?;
+            }
+            self.start_seek(pos)?;
+        }
+        self.poll_complete(cx)
+    }
+
+    pub(crate) fn poll_write(
+        &mut self,
         cx: &mut Context<'_>,
         src: &[u8],
     ) -> Poll<io::Result<usize>> {
-        ready!(crate::trace::trace_leaf(cx));
-        let me = self.get_mut();
-        let inner = me.inner.get_mut();
+        self.poll_write_with(cx, |buf, max_buf_size| buf.copy_from(src, max_buf_size))
+    }
+
+    pub(crate) fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_with(cx, |buf, max_buf_size| {
+            buf.copy_from_bufs(bufs, max_buf_size)
+        })
+    }
+
+    fn poll_write_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut copy_from: impl FnMut(&mut Buf, usize) -> usize,
+    ) -> Poll<io::Result<usize>> {
+        let inner = self.inner.get_mut().unwrap();
+        inner.seeking = None;
 
         if let Some(e) = inner.last_write_err.take() {
             return Poll::Ready(Err(e.into()));
@@ -737,10 +808,22 @@ impl AsyncWrite for File {
                         None
                     };
 
-                    let n = buf.copy_from(src, me.max_buf_size);
-                    let std = me.std.clone();
+                    let n = copy_from(&mut buf, self.max_buf_size);
+                    let std = self.std.clone();
 
-                    let blocking_task_join_handle = spawn_blocking(move || {
+                    #[cfg(all(feature = "io-uring", target_os = "linux", not(test)))]
+                    let (std, mut buf) = match seek {
+                        None => match uring::write(std, buf) {
+                            Ok(handle) => {
+                                inner.state = State::Busy(handle);
+                                return Poll::Ready(Ok(n));
+                            }
+                            Err(unsubmitted) => unsubmitted,
+                        },
+                        Some(_) => (std, buf),
+                    };
+
+                    inner.state = State::Busy(spawn_blocking(move || {
                         let res = if let Some(seek) = seek {
                             (&*std).seek(seek).and_then(|_| buf.write_to(&mut &*std))
                         } else {
@@ -748,17 +831,12 @@ impl AsyncWrite for File {
                         };
 
                         (Operation::Write(res), buf)
-                    })
-                    .ok_or_else(|| {
-                        io::Error::new(io::ErrorKind::Other, "background task failed")
-                    })?;
-
-                    inner.state = State::Busy(blocking_task_join_handle);
+                    }));
 
                     return Poll::Ready(Ok(n));
                 }
                 State::Busy(ref mut rx) => {
-                    let (op, buf) = ready!(Pin::new(rx).poll(cx))?;
+                    let (op, buf) = ready!(Pin::new(rx).poll(cx)).map_err(join_error)?;
                     inner.state = State::Idle(Some(buf));
 
                     match op {
@@ -784,90 +862,125 @@ impl AsyncWrite for File {
         }
     }
 
-    fn poll_write_vectored(
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.inner.get_mut().unwrap().poll_flush(cx)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for File {
+    #[inline]
+    fn poll_read(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
-        bufs: &[io::IoSlice<'_>],
-    ) -> Poll<Result<usize, io::Error>> {
-        ready!(crate::trace::trace_leaf(cx));
-        let me = self.get_mut();
-        let inner = me.inner.get_mut();
-
-        if let Some(e) = inner.last_write_err.take() {
-            return Poll::Ready(Err(e.into()));
-        }
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        File::poll_read(self.get_mut(), cx, buf)
+    }
+}
 
-        loop {
-            match inner.state {
-                State::Idle(ref mut buf_cell) => {
-                    let mut buf = buf_cell.take().unwrap();
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncWrite for File {
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        File::poll_write(self.get_mut(), cx, buf)
+    }
 
-                    let seek = if !buf.is_empty() {
-                        Some(SeekFrom::Current(buf.discard_read()))
-                    } else {
-                        None
-                    };
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        File::poll_write_vectored(self.get_mut(), cx, bufs)
+    }
 
-                    let n = buf.copy_from_bufs(bufs, me.max_buf_size);
-                    let std = me.std.clone();
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        File::poll_flush(self.get_mut(), cx)
+    }
 
-                    let blocking_task_join_handle = spawn_blocking(move || {
-                        let res = if let Some(seek) = seek {
-                            (&*std).seek(seek).and_then(|_| buf.write_to(&mut &*std))
-                        } else {
-                            buf.write_to(&mut &*std)
-                        };
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        File::poll_flush(self.get_mut(), cx)
+    }
+}
 
-                        (Operation::Write(res), buf)
-                    })
-                    .ok_or_else(|| {
-                        io::Error::new(io::ErrorKind::Other, "background task failed")
-                    })?;
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncSeek for File {
+    #[inline]
+    fn poll_seek(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        pos: SeekFrom,
+    ) -> Poll<io::Result<u64>> {
+        File::poll_seek(self.get_mut(), cx, pos)
+    }
+}
 
-                    inner.state = State::Busy(blocking_task_join_handle);
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        unsafe {
+            let b = &mut *(buf.unfilled_mut() as *mut _ as *mut [u8]);
+            let n = ready!(File::poll_read(self.get_mut(), cx, b))?;
+            buf.assume_init(n);
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+}
 
-                    return Poll::Ready(Ok(n));
-                }
-                State::Busy(ref mut rx) => {
-                    let (op, buf) = ready!(Pin::new(rx).poll(cx))?;
-                    inner.state = State::Idle(Some(buf));
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncWrite for File {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        File::poll_write(self.get_mut(), cx, buf)
+    }
 
-                    match op {
-                        Operation::Read(_) => {
-                            // We don't care about the result here. The fact
-                            // that the cursor has advanced will be reflected in
-                            // the next iteration of the loop
-                            continue;
-                        }
-                        Operation::Write(res) => {
-                            // If the previous write was successful, continue.
-                            // Otherwise, error.
-                            res?;
-                            continue;
-                        }
-                        Operation::Seek(_) => {
-                            // Ignore the seek
-                            continue;
-                        }
-                    }
-                }
-            }
-        }
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[io::IoSlice],
+    ) -> Poll<io::Result<usize>> {
+        File::poll_write_vectored(self.get_mut(), cx, bufs)
     }
 
+    #[inline]
     fn is_write_vectored(&self) -> bool {
         true
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        ready!(crate::trace::trace_leaf(cx));
-        let inner = self.inner.get_mut();
-        inner.poll_flush(cx)
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        File::poll_flush(self.get_mut(), cx)
     }
 
-    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        ready!(crate::trace::trace_leaf(cx));
-        self.poll_flush(cx)
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        File::poll_flush(self.get_mut(), cx)
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncSeek for File {
+    #[inline]
+    fn start_seek(self: Pin<&mut Self>, pos: SeekFrom) -> io::Result<()> {
+        File::start_seek(self.get_mut(), pos)
+    }
+
+    #[inline]
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        File::poll_complete(self.get_mut(), cx)
     }
 }
 
@@ -904,12 +1017,12 @@ impl std::os::unix::io::AsFd for File {
 #[cfg(unix)]
 impl std::os::unix::io::FromRawFd for File {
     unsafe fn from_raw_fd(fd: std::os::unix::io::RawFd) -> Self {
-        StdFile::from_raw_fd(fd).into()
+        unsafe { StdFile::from_raw_fd(fd).into() }
     }
 }
 
 cfg_windows! {
-    use crate::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle, AsHandle, BorrowedHandle};
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle, AsHandle, BorrowedHandle};
 
     impl AsRawHandle for File {
         fn as_raw_handle(&self) -> RawHandle {
@@ -929,20 +1042,17 @@ cfg_windows! {
 
     impl FromRawHandle for File {
         unsafe fn from_raw_handle(handle: RawHandle) -> Self {
-            StdFile::from_raw_handle(handle).into()
+            unsafe { StdFile::from_raw_handle(handle).into() }
         }
     }
 }
 
-impl Inner {
-    async fn complete_inflight(&mut self) {
-        use std::future::poll_fn;
-
-        poll_fn(|cx| self.poll_complete_inflight(cx)).await;
-    }
+fn join_error<E>(_: E) -> io::Error {
+    io::Error::other("background task failed")
+}
 
+impl Inner {
     fn poll_complete_inflight(&mut self, cx: &mut Context<'_>) -> Poll<()> {
-        // ready!(crate::trace::trace_leaf(cx));
         match self.poll_flush(cx) {
             Poll::Ready(Err(e)) => {
                 self.last_write_err = Some(e.kind());
@@ -954,13 +1064,15 @@ impl Inner {
     }
 
     fn poll_flush(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.seeking = None;
+
         if let Some(e) = self.last_write_err.take() {
             return Poll::Ready(Err(e.into()));
         }
 
         let (op, buf) = match self.state {
             State::Idle(_) => return Poll::Ready(Ok(())),
-            State::Busy(ref mut rx) => ready!(Pin::new(rx).poll(cx))?,
+            State::Busy(ref mut rx) => ready!(Pin::new(rx).poll(cx)).map_err(join_error)?,
         };
 
         // The buffer is not used here
@@ -969,10 +1081,15 @@ impl Inner {
         match op {
             Operation::Read(_) => Poll::Ready(Ok(())),
             Operation::Write(res) => Poll::Ready(res),
-            Operation::Seek(_) => Poll::Ready(Ok(())),
+            Operation::Seek(res) => {
+                if let Ok(pos) = res {
+                    self.pos = pos;
+                }
+                Poll::Ready(Ok(()))
+            }
         }
     }
 }
 
-// #[cfg(test)]
-// mod tests;
+#[cfg(all(test, feature = "futures-io"))]
+mod tests;
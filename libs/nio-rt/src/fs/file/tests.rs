@@ -1,9 +1,7 @@
 use super::*;
-use crate::{
-    fs::mocks::*,
-    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
-};
-use mockall::{predicate::eq, Sequence};
+use crate::fs::mocks::*;
+use futures::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use mockall::{Sequence, predicate::eq};
 use tokio_test::{assert_pending, assert_ready_err, assert_ready_ok, task};
 
 const HELLO: &[u8] = b"hello world...";
@@ -231,7 +229,7 @@ fn flush_while_idle() {
 #[cfg_attr(miri, ignore)] // takes a really long time with miri
 fn read_with_buffer_larger_than_max() {
     // Chunks
-    let chunk_a = crate::io::blocking::DEFAULT_MAX_BUF_SIZE;
+    let chunk_a = crate::io::DEFAULT_MAX_BUF_SIZE;
     let chunk_b = chunk_a * 2;
     let chunk_c = chunk_a * 3;
     let chunk_d = chunk_a * 4;
@@ -303,7 +301,7 @@ fn read_with_buffer_larger_than_max() {
 #[cfg_attr(miri, ignore)] // takes a really long time with miri
 fn write_with_buffer_larger_than_max() {
     // Chunks
-    let chunk_a = crate::io::blocking::DEFAULT_MAX_BUF_SIZE;
+    let chunk_a = crate::io::DEFAULT_MAX_BUF_SIZE;
     let chunk_b = chunk_a * 2;
     let chunk_c = chunk_a * 3;
     let chunk_d = chunk_a * 4;
@@ -956,23 +954,142 @@ fn partial_read_set_len_ok() {
     assert_eq!(&buf[..n], FOO);
 }
 
+#[test]
+fn concurrent_set_len_err() {
+    let mut file = MockFile::default();
+    let mut seq = Sequence::new();
+    file.expect_set_len()
+        .once()
+        .with(eq(123))
+        .in_sequence(&mut seq)
+        .returning(|_| Err(io::ErrorKind::Other.into()));
+    file.expect_sync_all()
+        .once()
+        .in_sequence(&mut seq)
+        .returning(|| Ok(()));
+
+    let file = File::from_std(file);
+    let mut set_len = task::spawn(file.set_len(123));
+    assert_pending!(set_len.poll());
+
+    let mut sync_all = task::spawn(file.sync_all());
+    assert_pending!(sync_all.poll());
+
+    pool::run_one();
+
+    // `sync_all` completes the `set_len` operation, and starts its own.
+    assert_pending!(sync_all.poll());
+    assert_ready_err!(set_len.poll());
+
+    pool::run_one();
+    assert_ready_ok!(sync_all.poll());
+}
+
 #[test]
 fn busy_file_seek_error() {
+    let mut file = MockFile::default();
+    file.expect_inner_write()
+        .once()
+        .returning(|_| Err(io::ErrorKind::Other.into()));
+
+    let mut file = File::from_std(file);
+    {
+        let mut t = task::spawn(file.write(HELLO));
+        assert_ready_ok!(t.poll());
+    }
+
+    pool::run_one();
+
+    // As `tokio::io::AsyncSeek` requires, `start_seek` does not wait for the
+    // pending write.
+    assert!(file.start_seek(SeekFrom::Start(0)).is_err());
+}
+
+#[test]
+fn busy_file_seek_waits() {
     let mut file = MockFile::default();
     let mut seq = Sequence::new();
     file.expect_inner_write()
         .once()
         .in_sequence(&mut seq)
-        .returning(|_| Err(io::ErrorKind::Other.into()));
+        .returning(|buf| Ok(buf.len()));
+    file.expect_inner_seek()
+        .once()
+        .with(eq(SeekFrom::Start(0)))
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(0));
 
-    let mut file = crate::io::BufReader::new(File::from_std(file));
+    let mut file = futures::io::BufReader::new(File::from_std(file));
     {
         let mut t = task::spawn(file.write(HELLO));
         assert_ready_ok!(t.poll());
     }
 
+    // `poll_seek` completes the pending write, before seeking.
+    let mut t = task::spawn(file.seek(SeekFrom::Start(0)));
+    assert_pending!(t.poll());
+
     pool::run_one();
+    assert!(t.is_woken());
+    assert_pending!(t.poll());
+
+    pool::run_one();
+    assert!(t.is_woken());
+    assert_eq!(assert_ready_ok!(t.poll()), 0);
+}
+
+#[test]
+fn dropped_seek_is_restarted() {
+    let mut file = MockFile::default();
+    let mut seq = Sequence::new();
+    file.expect_inner_seek()
+        .once()
+        .with(eq(SeekFrom::Start(5)))
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(5));
+    file.expect_inner_seek()
+        .once()
+        .with(eq(SeekFrom::Start(0)))
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(0));
+
+    let mut file = File::from_std(file);
+    {
+        let mut t = task::spawn(file.seek(SeekFrom::Start(5)));
+        assert_pending!(t.poll());
+    }
 
+    // The dropped seek has another target, it is completed before seeking again.
     let mut t = task::spawn(file.seek(SeekFrom::Start(0)));
-    assert_ready_err!(t.poll());
+    assert_pending!(t.poll());
+
+    pool::run_one();
+    assert!(t.is_woken());
+    assert_pending!(t.poll());
+
+    pool::run_one();
+    assert!(t.is_woken());
+    assert_eq!(assert_ready_ok!(t.poll()), 0);
+}
+
+#[test]
+fn dropped_seek_is_resumed() {
+    let mut file = MockFile::default();
+    file.expect_inner_seek()
+        .once()
+        .with(eq(SeekFrom::Start(5)))
+        .returning(|_| Ok(5));
+
+    let mut file = File::from_std(file);
+    {
+        let mut t = task::spawn(file.seek(SeekFrom::Start(5)));
+        assert_pending!(t.poll());
+    }
+
+    let mut t = task::spawn(file.seek(SeekFrom::Start(5)));
+    assert_pending!(t.poll());
+
+    pool::run_one();
+    assert!(t.is_woken());
+    assert_eq!(assert_ready_ok!(t.poll()), 5);
 }
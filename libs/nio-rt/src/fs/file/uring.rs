@@ -0,0 +1,139 @@
+//! Reads and writes of [`File`](super::File) submitted to the `io_uring` of
+//! the current worker.
+//!
+//! Both operations use the file position of the kernel (offset `-1`), just
+//! like `read(2)` and `write(2)` executed on the blocking thread pool, so the
+//! two can be mixed freely.
+
+use super::{Operation, StdFile};
+use crate::{driver::uring, io::Buf};
+use io_uring::{opcode, squeue, types};
+use std::{
+    fmt,
+    future::Future,
+    io::{self, Write},
+    os::fd::AsRawFd,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Offset `-1`: use (and update) the current file position.
+const CURRENT_POS: u64 = u64::MAX;
+
+/// On failure, the arguments are returned back.
+type Submitted = Result<JoinHandle<(Operation, Buf)>, (Arc<StdFile>, Buf)>;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, crate::JoinError>> + Send>>;
+
+/// An operation executed either by the blocking thread pool or by `io_uring`.
+pub(super) enum JoinHandle<T> {
+    Pool(crate::JoinHandle<T>),
+    Uring(BoxFuture<T>),
+}
+
+/// Runs `f` on the blocking thread pool.
+pub(super) fn spawn_blocking<F, T>(f: F) -> JoinHandle<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    JoinHandle::Pool(crate::spawn_blocking(f))
+}
+
+impl<T: Unpin> Future for JoinHandle<T> {
+    type Output = Result<T, crate::JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut() {
+            JoinHandle::Pool(handle) => Pin::new(handle).poll(cx),
+            JoinHandle::Uring(fut) => fut.as_mut().poll(cx),
+        }
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinHandle::Pool(handle) => f.debug_tuple("Pool").field(handle).finish(),
+            JoinHandle::Uring(_) => f.write_str("Uring"),
+        }
+    }
+}
+
+/// Reads up to `max_buf_size` bytes into `buf`.
+///
+/// Returns the arguments back if the operation couldn't be submitted.
+pub(super) fn read(std: Arc<StdFile>, mut buf: Buf, max_buf_size: usize) -> Submitted {
+    let (ptr, len) = buf.spare_capacity(max_buf_size);
+    let entry = opcode::Read::new(types::Fd(std.as_raw_fd()), ptr, len as u32)
+        .offset(CURRENT_POS)
+        .build();
+
+    // SAFETY: The spare capacity of `buf` is on the heap and owned by the operation.
+    let op = unsafe { uring::submit(entry, (std, buf)) }?;
+
+    Ok(JoinHandle::Uring(Box::pin(async move {
+        let (res, (_std, mut buf)) = op.await;
+        let res = res.map(|n| {
+            // SAFETY: The kernel has initialized `n` bytes.
+            unsafe { buf.assume_init(n as usize) };
+            n as usize
+        });
+        Ok((Operation::Read(res), buf))
+    })))
+}
+
+/// Writes all of `buf`.
+///
+/// Returns the arguments back if the operation couldn't be submitted.
+pub(super) fn write(std: Arc<StdFile>, buf: Buf) -> Submitted {
+    let entry = write_entry(&std, &buf);
+    // SAFETY: The bytes of `buf` are on the heap and owned by the operation.
+    let mut op = unsafe { uring::submit(entry, (std, buf)) }?;
+
+    Ok(JoinHandle::Uring(Box::pin(async move {
+        loop {
+            let (res, (std, mut buf)) = op.await;
+            let res = match res {
+                Ok(0) => Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    buf.advance(n as usize);
+                    Ok(())
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(()),
+                Err(e) => Err(e),
+            };
+            if res.is_err() || buf.is_empty() {
+                buf.discard_read();
+                return Ok((Operation::Write(res), buf));
+            }
+
+            // The task may have been moved to a worker without a ring.
+            let entry = write_entry(&std, &buf);
+            // SAFETY: See above.
+            op = match unsafe { uring::submit(entry, (std, buf)) } {
+                Ok(op) => op,
+                Err((std, mut buf)) => {
+                    return crate::spawn_blocking(move || {
+                        let res = (&*std).write_all(buf.bytes());
+                        buf.discard_read();
+                        (Operation::Write(res), buf)
+                    })
+                    .await;
+                }
+            };
+        }
+    })))
+}
+
+fn write_entry(std: &StdFile, buf: &Buf) -> squeue::Entry {
+    let bytes = buf.bytes();
+    opcode::Write::new(
+        types::Fd(std.as_raw_fd()),
+        bytes.as_ptr(),
+        bytes.len() as u32,
+    )
+    .offset(CURRENT_POS)
+    .build()
+}
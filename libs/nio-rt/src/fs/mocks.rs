@@ -1,7 +1,7 @@
 //! Mock version of std::fs::File;
 use mockall::mock;
 
-use crate::sync::oneshot;
+use futures::channel::oneshot;
 use std::{
     cell::RefCell,
     collections::VecDeque,
@@ -82,7 +82,7 @@ impl Write for &'_ MockFile {
     }
 }
 
-tokio_thread_local! {
+thread_local! {
     static QUEUE: RefCell<VecDeque<Box<dyn FnOnce() + Send>>> = RefCell::new(VecDeque::new())
 }
 
@@ -106,21 +106,6 @@ where
     JoinHandle { rx }
 }
 
-pub(super) fn spawn_mandatory_blocking<F, R>(f: F) -> Option<JoinHandle<R>>
-where
-    F: FnOnce() -> R + Send + 'static,
-    R: Send + 'static,
-{
-    let (tx, rx) = oneshot::channel();
-    let task = Box::new(move || {
-        let _ = tx.send(f());
-    });
-
-    QUEUE.with(|cell| cell.borrow_mut().push_back(task));
-
-    Some(JoinHandle { rx })
-}
-
 impl<T> Future for JoinHandle<T> {
     type Output = Result<T, io::Error>;
 
@@ -34,8 +34,8 @@ pub use self::create_dir_all::create_dir_all;
 mod dir_builder;
 pub use self::dir_builder::DirBuilder;
 
-// mod file;
-// pub use self::file::File;
+mod file;
+pub use self::file::File;
 
 mod hard_link;
 pub use self::hard_link::hard_link;
@@ -43,8 +43,8 @@ pub use self::hard_link::hard_link;
 mod metadata;
 pub use self::metadata::metadata;
 
-// mod open_options;
-// pub use self::open_options::OpenOptions;
+mod open_options;
+pub use self::open_options::OpenOptions;
 
 mod read;
 pub use self::read::read;
@@ -85,8 +85,11 @@ pub use self::copy::copy;
 mod try_exists;
 pub use self::try_exists::try_exists;
 
-// #[cfg(test)]
-// mod mocks;
+#[cfg(test)]
+mod mocks;
+
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
 
 feature! {
     #![unix]
@@ -95,6 +98,13 @@ feature! {
     pub use self::symlink::symlink;
 }
 
+feature! {
+    #![any(target_os = "linux", target_os = "android")]
+
+    mod watcher;
+    pub use self::watcher::{Event, Watcher};
+}
+
 cfg_windows! {
     mod symlink_dir;
     pub use self::symlink_dir::symlink_dir;
@@ -105,17 +115,17 @@ cfg_windows! {
 
 use std::io;
 
-// #[cfg(not(test))]
-// use crate::blocking::spawn_blocking;
-// #[cfg(test)]
-// use mocks::spawn_blocking;
+#[cfg(not(test))]
+use crate::spawn_blocking;
+#[cfg(test)]
+use mocks::spawn_blocking;
 
 pub(crate) async fn asyncify<F, T>(f: F) -> io::Result<T>
 where
     F: FnOnce() -> io::Result<T> + Send + 'static,
     T: Send + 'static,
 {
-    match crate::spawn_blocking(f).await {
+    match spawn_blocking(f).await {
         Ok(res) => res,
         Err(_) => Err(io::Error::other("background task failed")),
     }
@@ -1,13 +1,13 @@
-use crate::fs::{asyncify, File};
+use crate::fs::{File, asyncify};
 
 use std::io;
 use std::path::Path;
 
-// #[cfg(test)]
-// mod mock_open_options;
-// #[cfg(test)]
-// use mock_open_options::MockOpenOptions as StdOpenOptions;
-// #[cfg(not(test))]
+#[cfg(test)]
+mod mock_open_options;
+#[cfg(test)]
+use mock_open_options::MockOpenOptions as StdOpenOptions;
+#[cfg(not(test))]
 use std::fs::OpenOptions as StdOpenOptions;
 
 #[cfg(unix)]
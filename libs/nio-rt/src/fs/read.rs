@@ -2,6 +2,9 @@ use crate::fs::asyncify;
 
 use std::{io, path::Path};
 
+/// With the `io-uring` feature on Linux, the file is opened and read by the
+/// `io_uring` instance of the current worker, when the kernel supports it.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -16,6 +19,11 @@ use std::{io, path::Path};
 /// }
 /// ```
 pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    if crate::driver::uring::is_available() {
+        return super::uring::read(path.as_ref()).await;
+    }
+
     let path = path.as_ref().to_owned();
     asyncify(move || std::fs::read(path)).await
 }
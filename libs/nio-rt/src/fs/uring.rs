@@ -0,0 +1,156 @@
+//! [`read`](super::read) and [`write`](super::write) submitted to the
+//! `io_uring` of the current worker.
+//!
+//! Callers check [`uring::is_available`] first. The first operation is
+//! submitted in the same poll, but the task may move to a worker without a
+//! ring between operations, in which case the rest of the work is done on
+//! the blocking thread pool.
+
+use crate::{driver::uring, fs::asyncify};
+use io_uring::{opcode, types};
+use std::{
+    cmp,
+    ffi::CString,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    os::{
+        fd::{AsRawFd, FromRawFd},
+        unix::{ffi::OsStrExt, fs::FileExt},
+    },
+    path::Path,
+};
+
+const PROBE_SIZE: usize = 32 * 1024;
+
+/// Largest length of a single read or write operation.
+const MAX_LEN: usize = i32::MAX as usize;
+
+async fn open(path: &Path, flags: i32) -> io::Result<File> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contained a null byte"))?;
+    let flags = flags | libc::O_CLOEXEC;
+    let entry = opcode::OpenAt::new(types::Fd(libc::AT_FDCWD), path.as_ptr())
+        .flags(flags)
+        .mode(0o666)
+        .build();
+
+    // SAFETY: `path` is on the heap and owned by the operation.
+    match unsafe { uring::submit(entry, path) } {
+        Ok(op) => {
+            let (res, _) = op.await;
+            // SAFETY: On success, the result is a newly opened file descriptor.
+            Ok(unsafe { File::from_raw_fd(res? as i32) })
+        }
+        Err(path) => {
+            asyncify(move || {
+                // SAFETY: `path` is a valid C string.
+                let fd = unsafe { libc::open(path.as_ptr(), flags, 0o666) };
+                if fd < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                // SAFETY: `fd` is a newly opened file descriptor.
+                Ok(unsafe { File::from_raw_fd(fd) })
+            })
+            .await
+        }
+    }
+}
+
+pub(crate) async fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = open(path, libc::O_RDONLY).await?;
+    let mut buf = Vec::new();
+
+    loop {
+        if buf.capacity() - buf.len() < PROBE_SIZE {
+            buf.reserve(cmp::max(buf.len(), PROBE_SIZE));
+        }
+        let spare = buf.spare_capacity_mut();
+        let len = cmp::min(spare.len(), MAX_LEN);
+        let entry = opcode::Read::new(
+            types::Fd(file.as_raw_fd()),
+            spare.as_mut_ptr().cast(),
+            len as u32,
+        )
+        .offset(buf.len() as u64)
+        .build();
+
+        // SAFETY: The spare capacity of `buf` is on the heap and owned by the operation.
+        match unsafe { uring::submit(entry, (file, buf)) } {
+            Ok(op) => {
+                let (res, data) = op.await;
+                (file, buf) = data;
+                match res {
+                    Ok(0) => return Ok(buf),
+                    // SAFETY: The kernel has initialized `n` bytes.
+                    Ok(n) => unsafe { buf.set_len(buf.len() + n as usize) },
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Err((mut file, mut buf)) => {
+                return asyncify(move || {
+                    file.seek(SeekFrom::Start(buf.len() as u64))?;
+                    file.read_to_end(&mut buf)?;
+                    Ok(buf)
+                })
+                .await;
+            }
+        }
+    }
+}
+
+pub(crate) async fn write(path: &Path, contents: Vec<u8>) -> io::Result<()> {
+    let mut file = open(path, libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC).await?;
+    let mut contents = contents;
+    let mut pos = 0;
+
+    while pos < contents.len() {
+        let len = cmp::min(contents.len() - pos, MAX_LEN);
+        let entry = opcode::Write::new(
+            types::Fd(file.as_raw_fd()),
+            contents[pos..].as_ptr(),
+            len as u32,
+        )
+        .offset(pos as u64)
+        .build();
+
+        // SAFETY: `contents` is on the heap and owned by the operation.
+        match unsafe { uring::submit(entry, (file, contents)) } {
+            Ok(op) => {
+                let (res, data) = op.await;
+                (file, contents) = data;
+                match res {
+                    Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                    Ok(n) => pos += n as usize,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Err((file, contents)) => {
+                return asyncify(move || file.write_all_at(&contents[pos..], pos as u64)).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RuntimeBuilder, driver::uring, fs};
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    #[ignore = "needs a kernel that allows `io_uring`, run explicitly in CI"]
+    fn submitted_to_ring() {
+        let path = std::env::temp_dir().join(format!("nio-uring-unit-{}", std::process::id()));
+        RuntimeBuilder::new().build().unwrap().block_on(async {
+            assert!(uring::is_available(), "failed to create the ring");
+            let submitted = uring::SUBMITTED.load(Ordering::Relaxed);
+            fs::write(&path, b"hello").await.unwrap();
+            assert_eq!(fs::read(&path).await.unwrap(), b"hello");
+            // Open, write, open and at least one read.
+            assert!(uring::SUBMITTED.load(Ordering::Relaxed) >= submitted + 4);
+            std::fs::remove_file(&path).unwrap();
+        });
+    }
+}
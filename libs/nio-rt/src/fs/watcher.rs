@@ -0,0 +1,520 @@
+use crate::{driver::AsyncIO, rt::context::LocalContext, timer::sleep::Sleep};
+use mio::{Interest, Registry, Token, event::Source, unix::SourceFd};
+use std::{
+    collections::{HashMap, VecDeque},
+    ffi::{CString, OsStr},
+    fmt,
+    future::{Future, poll_fn},
+    io, mem,
+    os::{
+        fd::{AsRawFd, FromRawFd, OwnedFd},
+        unix::ffi::OsStrExt,
+    },
+    path::{Path, PathBuf},
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+const MASK: u32 = libc::IN_CREATE
+    | libc::IN_MODIFY
+    | libc::IN_DELETE
+    | libc::IN_DELETE_SELF
+    | libc::IN_MOVED_FROM
+    | libc::IN_MOVED_TO;
+
+/// Large enough for a few events with `NAME_MAX` long names.
+const BUF_SIZE: usize = 4096;
+
+/// How long an `IN_MOVED_FROM` event waits for its `IN_MOVED_TO`, without a
+/// debounce period.
+const RENAME_GRACE: Duration = Duration::from_millis(10);
+
+/// A change to the watched part of the filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A file or directory was created, or moved in from outside the watched
+    /// paths.
+    Create(PathBuf),
+    /// The contents of a file were modified.
+    Modify(PathBuf),
+    /// A file or directory was removed, or moved out of the watched paths.
+    Remove(PathBuf),
+    /// A file or directory was moved within the watched paths.
+    Rename { from: PathBuf, to: PathBuf },
+}
+
+impl Event {
+    /// Returns the path affected by the event, the new path of a rename.
+    pub fn path(&self) -> &Path {
+        match self {
+            Event::Create(path) | Event::Modify(path) | Event::Remove(path) => path,
+            Event::Rename { to, .. } => to,
+        }
+    }
+}
+
+/// Watches files and directories for changes, using Linux `inotify`.
+///
+/// Events are read by the reactor of the current worker and coalesced before
+/// they are returned: a file that is created and modified is reported once as
+/// [`Event::Create`], a file that is created and removed is not reported at
+/// all. With [`set_debounce`], events are collected for the given duration
+/// before they are returned, which gives coalescing a chance to work across
+/// bursts of writes.
+///
+/// `Watcher` implements [`Stream`], the stream never ends.
+///
+/// [`set_debounce`]: Watcher::set_debounce
+/// [`Stream`]: https://docs.rs/futures/latest/futures/stream/trait.Stream.html
+///
+/// # Examples
+///
+/// ```no_run
+/// use nio::fs::{Event, Watcher};
+///
+/// # async fn dox() -> std::io::Result<()> {
+/// let mut watcher = Watcher::new()?;
+/// watcher.watch_recursive("config")?;
+///
+/// loop {
+///     match watcher.next_event().await? {
+///         Event::Modify(path) => println!("reload {}", path.display()),
+///         event => println!("{event:?}"),
+///     }
+/// }
+/// # }
+/// ```
+pub struct Watcher {
+    io: AsyncIO<Inotify>,
+    watches: HashMap<i32, Watch>,
+    /// `IN_MOVED_FROM` events waiting for the `IN_MOVED_TO` with the same
+    /// cookie, until their deadline.
+    moved_from: HashMap<u32, (PathBuf, bool, Instant)>,
+    /// Wakes up at the earliest deadline of `moved_from`.
+    rename_delay: Option<Sleep>,
+    /// Coalesced events, waiting for the debounce period.
+    pending: Vec<Event>,
+    ready: VecDeque<Event>,
+    overflowed: bool,
+    debounce: Option<Duration>,
+    delay: Option<Sleep>,
+    buf: Box<[u8]>,
+}
+
+struct Watch {
+    path: PathBuf,
+    recursive: bool,
+    /// Added by the user, rather than discovered by a recursive watch.
+    root: bool,
+}
+
+struct Inotify(OwnedFd);
+
+impl Watcher {
+    /// Creates a new watcher without any watched paths.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Nio runtime.
+    pub fn new() -> io::Result<Watcher> {
+        // SAFETY: `inotify_init1` has no memory safety requirements.
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a newly opened file descriptor owned by us.
+        let inotify = Inotify(unsafe { OwnedFd::from_raw_fd(fd) });
+
+        Ok(Watcher {
+            io: AsyncIO::with_interest(inotify, Interest::READABLE)?,
+            watches: HashMap::new(),
+            moved_from: HashMap::new(),
+            rename_delay: None,
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+            overflowed: false,
+            debounce: None,
+            delay: None,
+            buf: vec![0; BUF_SIZE].into_boxed_slice(),
+        })
+    }
+
+    /// Watches a file, or the direct entries of a directory.
+    pub fn watch(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.add_watch(path.as_ref(), false, true).map(drop)
+    }
+
+    /// Watches a directory and all of its subdirectories.
+    ///
+    /// Directories created later are watched as well. The directory tree is
+    /// walked synchronously.
+    pub fn watch_recursive(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.add_tree(path.as_ref(), true, false)
+    }
+
+    /// Stops watching a path passed to [`watch`] or [`watch_recursive`].
+    ///
+    /// [`watch`]: Watcher::watch
+    /// [`watch_recursive`]: Watcher::watch_recursive
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let Some(root) = self
+            .watches
+            .values()
+            .find(|watch| watch.root && watch.path == path)
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "path is not watched",
+            ));
+        };
+        let recursive = root.recursive;
+        self.remove_watches(|watch| {
+            watch.path == path || (recursive && !watch.root && watch.path.starts_with(path))
+        });
+        Ok(())
+    }
+
+    /// Sets the period during which events are collected and coalesced,
+    /// before they are returned.
+    ///
+    /// The period starts with the first event. A zero duration (the default)
+    /// returns events as soon as they are read.
+    pub fn set_debounce(&mut self, duration: Duration) {
+        self.debounce = Some(duration).filter(|d| !d.is_zero());
+    }
+
+    /// Waits for the next event.
+    ///
+    /// An error is returned if the kernel event queue overflowed and events
+    /// were lost, the watcher can still be used afterwards.
+    pub fn next_event(&mut self) -> impl Future<Output = io::Result<Event>> + use<'_> {
+        poll_fn(|cx| self.poll_next_event(cx))
+    }
+
+    pub fn poll_next_event(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Event>> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Poll::Ready(Ok(event));
+            }
+            if mem::take(&mut self.overflowed) {
+                return Poll::Ready(Err(io::Error::other("inotify event queue overflowed")));
+            }
+
+            let mut buf = mem::take(&mut self.buf);
+            let res = {
+                let mut read = self.io.io_read(|inotify| inotify.read(&mut buf));
+                Pin::new(&mut read).poll(cx)
+            };
+            match res {
+                Poll::Ready(Ok(n)) => {
+                    self.parse(&buf[..n]);
+                    self.buf = buf;
+                    continue;
+                }
+                Poll::Ready(Err(err)) => {
+                    self.buf = buf;
+                    return Poll::Ready(Err(err));
+                }
+                Poll::Pending => self.buf = buf,
+            }
+
+            self.expire_renames(cx);
+
+            if self.pending.is_empty() {
+                return Poll::Pending;
+            }
+            if let Some(duration) = self.debounce {
+                let delay = self.delay.get_or_insert_with(|| crate::sleep(duration));
+                if Pin::new(delay).poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.delay = None;
+            }
+            self.ready.extend(self.pending.drain(..));
+        }
+    }
+
+    /// The halves of a rename are not always queued together. An
+    /// `IN_MOVED_FROM` that stays unmatched for the grace period (the debounce
+    /// period, if any) left the watched paths.
+    fn expire_renames(&mut self, cx: &mut Context<'_>) {
+        loop {
+            let now = current();
+            let expired: Vec<u32> = self
+                .moved_from
+                .iter()
+                .filter(|(_, (_, _, deadline))| *deadline <= now)
+                .map(|(cookie, _)| *cookie)
+                .collect();
+
+            for cookie in expired {
+                let (path, is_dir, _) = self.moved_from.remove(&cookie).unwrap();
+                if is_dir {
+                    self.remove_watches(|watch| !watch.root && watch.path.starts_with(&path));
+                }
+                self.push(Event::Remove(path));
+            }
+
+            let Some(deadline) = self.moved_from.values().map(|(_, _, d)| *d).min() else {
+                self.rename_delay = None;
+                return;
+            };
+            let delay = self.rename_delay.get_or_insert_with(|| Sleep::at(deadline));
+            if delay.deadline() != deadline {
+                delay.reset_at(deadline);
+            }
+            if Pin::new(delay).poll(cx).is_pending() {
+                return;
+            }
+        }
+    }
+
+    fn add_watch(&mut self, path: &Path, recursive: bool, root: bool) -> io::Result<i32> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+        // SAFETY: `c_path` is a valid C string.
+        let wd = unsafe { libc::inotify_add_watch(self.io.io.0.as_raw_fd(), c_path.as_ptr(), MASK) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // The same inode may be watched already.
+        let root = root || self.watches.get(&wd).is_some_and(|watch| watch.root);
+        let watch = Watch {
+            path: path.to_owned(),
+            recursive,
+            root,
+        };
+        self.watches.insert(wd, watch);
+        Ok(wd)
+    }
+
+    /// Watches `dir` and its subdirectories. If the directory was just
+    /// `created`, events are generated for its entries, which may have been
+    /// created before the watch was added.
+    fn add_tree(&mut self, dir: &Path, root: bool, created: bool) -> io::Result<()> {
+        self.add_watch(dir, true, root)?;
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if created {
+                self.push(Event::Create(path.clone()));
+            }
+            if entry.file_type()?.is_dir() {
+                self.add_tree(&path, false, created)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_watches(&mut self, mut f: impl FnMut(&Watch) -> bool) {
+        let fd = self.io.io.0.as_raw_fd();
+        self.watches.retain(|&wd, watch| {
+            if !f(watch) {
+                return true;
+            }
+            // SAFETY: `inotify_rm_watch` has no memory safety requirements.
+            unsafe { libc::inotify_rm_watch(fd, wd) };
+            false
+        });
+    }
+
+    fn parse(&mut self, mut buf: &[u8]) {
+        const HEADER: usize = mem::size_of::<libc::inotify_event>();
+
+        while buf.len() >= HEADER {
+            // SAFETY: The kernel writes complete events, the buffer may be unaligned.
+            let event = unsafe { buf.as_ptr().cast::<libc::inotify_event>().read_unaligned() };
+            let name = &buf[HEADER..HEADER + event.len as usize];
+            // The name is padded with null bytes.
+            let name = name.split(|&b| b == 0).next().filter(|name| !name.is_empty());
+            self.handle(event.wd, event.mask, event.cookie, name.map(OsStr::from_bytes));
+            buf = &buf[HEADER + event.len as usize..];
+        }
+    }
+
+    fn handle(&mut self, wd: i32, mask: u32, cookie: u32, name: Option<&OsStr>) {
+        if mask & libc::IN_Q_OVERFLOW != 0 {
+            self.overflowed = true;
+            return;
+        }
+        if mask & libc::IN_IGNORED != 0 {
+            self.watches.remove(&wd);
+            return;
+        }
+        let Some(watch) = self.watches.get(&wd) else {
+            return;
+        };
+        let path = match name {
+            Some(name) => watch.path.join(name),
+            None => watch.path.clone(),
+        };
+        let (recursive, root) = (watch.recursive, watch.root);
+        let is_dir = mask & libc::IN_ISDIR != 0;
+
+        if mask & libc::IN_CREATE != 0 {
+            self.push(Event::Create(path.clone()));
+            if is_dir && recursive {
+                // The directory may be gone already.
+                let _ = self.add_tree(&path, false, true);
+            }
+        }
+        if mask & libc::IN_MODIFY != 0 {
+            self.push(Event::Modify(path.clone()));
+        }
+        if mask & libc::IN_DELETE != 0 {
+            self.push(Event::Remove(path.clone()));
+        }
+        // Removal of other directories is reported by their parent.
+        if mask & libc::IN_DELETE_SELF != 0 && root {
+            self.push(Event::Remove(path.clone()));
+        }
+        if mask & libc::IN_MOVED_FROM != 0 {
+            let grace = self.debounce.unwrap_or(RENAME_GRACE);
+            self.moved_from.insert(cookie, (path.clone(), is_dir, current() + grace));
+        }
+        if mask & libc::IN_MOVED_TO != 0 {
+            match self.moved_from.remove(&cookie) {
+                Some((from, ..)) => {
+                    if is_dir {
+                        for watch in self.watches.values_mut() {
+                            if let Ok(rest) = watch.path.strip_prefix(&from) {
+                                watch.path = path.join(rest);
+                            }
+                        }
+                    }
+                    self.push(Event::Rename { from, to: path });
+                }
+                None => {
+                    self.push(Event::Create(path.clone()));
+                    if is_dir && recursive {
+                        let _ = self.add_tree(&path, false, true);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds `event` to the pending events, coalescing it with the last event
+    /// of the same path.
+    fn push(&mut self, event: Event) {
+        let last = self.pending.iter().rposition(|e| e.path() == event.path());
+        if let Some(i) = last {
+            match (&self.pending[i], &event) {
+                (Event::Create(_) | Event::Modify(_), Event::Modify(_)) => return,
+                (Event::Create(_), Event::Remove(_)) => {
+                    self.pending.remove(i);
+                    return;
+                }
+                (Event::Modify(_), Event::Remove(_)) => {
+                    self.pending.remove(i);
+                }
+                (Event::Remove(_), Event::Create(path)) => {
+                    let path = path.clone();
+                    self.pending.remove(i);
+                    self.pending.push(Event::Modify(path));
+                    return;
+                }
+                _ => {}
+            }
+        }
+        self.pending.push(event);
+    }
+}
+
+fn current() -> Instant {
+    LocalContext::with(|ctx| unsafe { ctx.timers(|timers| timers.clock.current()) })
+}
+
+impl futures_core::Stream for Watcher {
+    type Item = io::Result<Event>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_next_event(cx).map(Some)
+    }
+}
+
+impl fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut paths: Vec<_> = self.watches.values().map(|watch| &watch.path).collect();
+        paths.sort();
+        f.debug_struct("Watcher")
+            .field("paths", &paths)
+            .field("debounce", &self.debounce)
+            .finish()
+    }
+}
+
+impl Inotify {
+    fn read(&self, buf: &mut [u8]) -> io::Result<usize> {
+        // SAFETY: `buf` is valid for writes of `buf.len()` bytes.
+        let n = unsafe { libc::read(self.0.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(n as usize)
+    }
+}
+
+impl Source for Inotify {
+    fn register(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+        SourceFd(&self.0.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.0.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.0.as_raw_fd()).deregister(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RuntimeBuilder;
+    use std::task::Waker;
+
+    #[test]
+    fn split_rename() {
+        let dir = std::env::temp_dir().join(format!("nio-watch-split-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        RuntimeBuilder::new().build().unwrap().block_on(async {
+            let mut watcher = Watcher::new().unwrap();
+            watcher.watch_recursive(&dir).unwrap();
+            let wd = *watcher.watches.keys().next().unwrap();
+
+            let moved = libc::IN_ISDIR | libc::IN_MOVED_FROM;
+            watcher.handle(wd, moved, 7, Some(OsStr::new("a")));
+            // The other half is read in a later cycle.
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(watcher.poll_next_event(&mut cx).is_pending());
+
+            let moved = libc::IN_ISDIR | libc::IN_MOVED_TO;
+            watcher.handle(wd, moved, 7, Some(OsStr::new("b")));
+            let event = watcher.next_event().await.unwrap();
+            assert_eq!(
+                event,
+                Event::Rename {
+                    from: dir.join("a"),
+                    to: dir.join("b")
+                }
+            );
+
+            // Degraded to a removal after the grace period.
+            watcher.handle(wd, libc::IN_MOVED_FROM, 8, Some(OsStr::new("c")));
+            assert_eq!(watcher.next_event().await.unwrap(), Event::Remove(dir.join("c")));
+        });
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
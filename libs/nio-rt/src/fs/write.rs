@@ -10,6 +10,9 @@ use std::{io, path::Path};
 /// This operation is implemented by running the equivalent blocking operation
 /// on a separate thread pool using [`spawn_blocking`].
 ///
+/// With the `io-uring` feature on Linux, it is submitted to the `io_uring`
+/// instance of the current worker instead, when the kernel supports it.
+///
 /// [`spawn_blocking`]: crate::spawn_blocking
 /// [std]: fn@std::fs::write
 ///
@@ -27,5 +30,10 @@ pub async fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Re
     let path = path.as_ref().to_owned();
     let contents = contents.as_ref().to_owned();
 
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    if crate::driver::uring::is_available() {
+        return super::uring::write(&path, contents).await;
+    }
+
     asyncify(move || std::fs::write(path, contents)).await
 }
@@ -0,0 +1,311 @@
+//! Adapter running blocking I/O on the thread pool.
+
+use nio_task::JoinHandle;
+use std::{
+    cmp,
+    io::{self, IoSlice, Read, Write},
+    mem::MaybeUninit,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+pub(crate) const DEFAULT_MAX_BUF_SIZE: usize = 2 * 1024 * 1024;
+
+/// Wraps a blocking reader or writer, every operation is executed by
+/// [`crate::spawn_blocking`].
+///
+/// `T` should not implement _both_ Read and Write.
+#[derive(Debug)]
+pub(crate) struct Blocking<T> {
+    inner: Option<T>,
+    state: State<T>,
+    /// `true` if the lower IO layer needs flushing.
+    need_flush: bool,
+}
+
+#[derive(Debug)]
+enum State<T> {
+    Idle(Option<Buf>),
+    Busy(JoinHandle<(io::Result<usize>, Buf, T)>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Buf {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+fn background_task_failed<T>(result: Result<T, crate::JoinError>) -> io::Result<T> {
+    result.map_err(|_| io::Error::other("background task failed"))
+}
+
+impl<T> Blocking<T> {
+    pub(crate) fn new(inner: T) -> Blocking<T> {
+        Blocking {
+            inner: Some(inner),
+            state: State::Idle(Some(Buf::with_capacity(0))),
+            need_flush: false,
+        }
+    }
+}
+
+impl<T: Read + Unpin + Send + 'static> Blocking<T> {
+    pub(crate) fn poll_read(
+        &mut self,
+        cx: &mut Context,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            match self.state {
+                State::Idle(ref mut buf_cell) => {
+                    let mut buf = buf_cell.take().unwrap();
+
+                    if !buf.is_empty() {
+                        let n = buf.copy_to(dst);
+                        *buf_cell = Some(buf);
+                        return Poll::Ready(Ok(n));
+                    }
+
+                    let mut inner = self.inner.take().unwrap();
+
+                    let max_buf_size = cmp::min(dst.len(), DEFAULT_MAX_BUF_SIZE);
+                    self.state = State::Busy(crate::spawn_blocking(move || {
+                        let res = buf.read_from(&mut inner, max_buf_size);
+                        (res, buf, inner)
+                    }));
+                }
+                State::Busy(ref mut rx) => {
+                    let (res, mut buf, inner) =
+                        background_task_failed(ready!(Pin::new(rx).poll(cx)))?;
+                    self.inner = Some(inner);
+
+                    match res {
+                        Ok(_) => {
+                            let n = buf.copy_to(dst);
+                            self.state = State::Idle(Some(buf));
+                            return Poll::Ready(Ok(n));
+                        }
+                        Err(e) => {
+                            assert!(buf.is_empty());
+
+                            self.state = State::Idle(Some(buf));
+                            return Poll::Ready(Err(e));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Write + Unpin + Send + 'static> Blocking<T> {
+    pub(crate) fn poll_write(&mut self, cx: &mut Context, src: &[u8]) -> Poll<io::Result<usize>> {
+        loop {
+            match self.state {
+                State::Idle(ref mut buf_cell) => {
+                    let mut buf = buf_cell.take().unwrap();
+
+                    assert!(buf.is_empty());
+
+                    let n = buf.copy_from(src, DEFAULT_MAX_BUF_SIZE);
+                    let mut inner = self.inner.take().unwrap();
+
+                    self.state = State::Busy(crate::spawn_blocking(move || {
+                        let n = buf.len();
+                        let res = buf.write_to(&mut inner).map(|()| n);
+
+                        (res, buf, inner)
+                    }));
+                    self.need_flush = true;
+
+                    return Poll::Ready(Ok(n));
+                }
+                State::Busy(ref mut rx) => {
+                    let (res, buf, inner) = background_task_failed(ready!(Pin::new(rx).poll(cx)))?;
+                    self.state = State::Idle(Some(buf));
+                    self.inner = Some(inner);
+
+                    // If error, return
+                    res?;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn poll_flush(&mut self, cx: &mut Context) -> Poll<io::Result<()>> {
+        loop {
+            let need_flush = self.need_flush;
+            match self.state {
+                // The buffer is not used here
+                State::Idle(ref mut buf_cell) => {
+                    if need_flush {
+                        let buf = buf_cell.take().unwrap();
+                        let mut inner = self.inner.take().unwrap();
+
+                        self.state = State::Busy(crate::spawn_blocking(move || {
+                            let res = inner.flush().map(|()| 0);
+                            (res, buf, inner)
+                        }));
+
+                        self.need_flush = false;
+                    } else {
+                        return Poll::Ready(Ok(()));
+                    }
+                }
+                State::Busy(ref mut rx) => {
+                    let (res, buf, inner) = background_task_failed(ready!(Pin::new(rx).poll(cx)))?;
+                    self.state = State::Idle(Some(buf));
+                    self.inner = Some(inner);
+
+                    // If error, return
+                    res?;
+                }
+            }
+        }
+    }
+}
+
+/// Repeats operations that are interrupted.
+macro_rules! uninterruptibly {
+    ($e:expr) => {{
+        loop {
+            match $e {
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                res => break res,
+            }
+        }
+    }};
+}
+
+impl Buf {
+    pub(crate) fn with_capacity(n: usize) -> Buf {
+        Buf {
+            buf: Vec::with_capacity(n),
+            pos: 0,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    pub(crate) fn copy_to(&mut self, dst: &mut [u8]) -> usize {
+        let n = cmp::min(self.len(), dst.len());
+        dst[..n].copy_from_slice(&self.bytes()[..n]);
+        self.pos += n;
+
+        if self.pos == self.buf.len() {
+            self.buf.truncate(0);
+            self.pos = 0;
+        }
+
+        n
+    }
+
+    pub(crate) fn copy_from(&mut self, src: &[u8], max_buf_size: usize) -> usize {
+        assert!(self.is_empty());
+
+        let n = cmp::min(src.len(), max_buf_size);
+
+        self.buf.extend_from_slice(&src[..n]);
+        n
+    }
+
+    pub(crate) fn copy_from_bufs(&mut self, bufs: &[IoSlice<'_>], max_buf_size: usize) -> usize {
+        assert!(self.is_empty());
+
+        let mut rem = max_buf_size;
+        for buf in bufs {
+            if rem == 0 {
+                break;
+            }
+
+            let len = buf.len().min(rem);
+            self.buf.extend_from_slice(&buf[..len]);
+            rem -= len;
+        }
+
+        max_buf_size - rem
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.buf[self.pos..]
+    }
+
+    pub(crate) fn read_from<T: Read>(
+        &mut self,
+        rd: &mut T,
+        max_buf_size: usize,
+    ) -> io::Result<usize> {
+        assert!(self.is_empty());
+        self.buf.reserve(max_buf_size);
+
+        let buf = &mut self.buf.spare_capacity_mut()[..max_buf_size];
+        // `Read::read` may read from the buffer, so it must be initialized.
+        buf.fill(MaybeUninit::new(0));
+        // SAFETY: The whole slice was initialized above.
+        let buf = unsafe { &mut *(buf as *mut [MaybeUninit<u8>] as *mut [u8]) };
+        let res = uninterruptibly!(rd.read(buf));
+
+        if let Ok(n) = res {
+            // SAFETY: `n <= max_buf_size` bytes were initialized above.
+            unsafe { self.buf.set_len(n) }
+        } else {
+            self.buf.clear();
+        }
+
+        assert_eq!(self.pos, 0);
+
+        res
+    }
+
+    pub(crate) fn write_to<T: Write>(&mut self, wr: &mut T) -> io::Result<()> {
+        assert_eq!(self.pos, 0);
+
+        // `write_all` already ignores interrupts
+        let res = wr.write_all(&self.buf);
+        self.buf.clear();
+        res
+    }
+
+    pub(crate) fn discard_read(&mut self) -> i64 {
+        let ret = -(self.bytes().len() as i64);
+        self.pos = 0;
+        self.buf.truncate(0);
+        ret
+    }
+}
+
+#[cfg(all(feature = "io-uring", target_os = "linux", not(test)))]
+impl Buf {
+    /// Reserves `max_buf_size` bytes and returns the spare capacity to read into.
+    pub(crate) fn spare_capacity(&mut self, max_buf_size: usize) -> (*mut u8, usize) {
+        assert!(self.is_empty());
+        self.buf.reserve(max_buf_size);
+        (
+            self.buf.spare_capacity_mut().as_mut_ptr().cast(),
+            max_buf_size,
+        )
+    }
+
+    /// # Safety
+    ///
+    /// `n` bytes of the spare capacity must be initialized.
+    pub(crate) unsafe fn assume_init(&mut self, n: usize) {
+        assert_eq!(self.pos, 0);
+        unsafe { self.buf.set_len(n) }
+    }
+
+    /// Marks `n` bytes as written.
+    pub(crate) fn advance(&mut self, n: usize) {
+        self.pos += n;
+        if self.pos == self.buf.len() {
+            self.buf.truncate(0);
+            self.pos = 0;
+        }
+    }
+}
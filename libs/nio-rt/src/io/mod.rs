@@ -0,0 +1,13 @@
+//! Asynchronous standard I/O.
+//!
+//! [`stdin`], [`stdout`] and [`stderr`] return handles to the standard
+//! streams of the process. Pipes and terminals are registered with the
+//! reactor of the current worker, when they can be used in non-blocking mode.
+//! Otherwise (regular files, or platforms where the descriptor can't be
+//! reopened), every operation is executed on the blocking thread pool.
+
+mod blocking;
+mod stdio;
+
+pub(crate) use blocking::{Blocking, Buf, DEFAULT_MAX_BUF_SIZE};
+pub use stdio::{Stderr, Stdin, Stdout, stderr, stdin, stdout};
@@ -0,0 +1,283 @@
+use super::Blocking;
+use std::{
+    fmt,
+    future::poll_fn,
+    io::Result,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(unix)]
+use crate::pipe::{Receiver, Sender};
+#[cfg(not(unix))]
+use unsupported::{Unsupported as Receiver, Unsupported as Sender};
+
+enum Inner<Async, Sync> {
+    /// Registered with the reactor.
+    Async(Async),
+    /// Executed on the blocking thread pool.
+    Blocking(Blocking<Sync>),
+}
+
+/// A handle to the standard input stream of a process.
+///
+/// Created by the [`stdin`] function.
+pub struct Stdin(Inner<Receiver, std::io::Stdin>);
+
+/// A handle to the standard output stream of a process.
+///
+/// Created by the [`stdout`] function.
+pub struct Stdout(Inner<Sender, std::io::Stdout>);
+
+/// A handle to the standard error stream of a process.
+///
+/// Created by the [`stderr`] function.
+pub struct Stderr(Inner<Sender, std::io::Stderr>);
+
+/// Constructs a new handle to the standard input of the current process.
+///
+/// Reads are not synchronized with [`std::io::stdin`], the two should not be
+/// used at the same time.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Nio runtime.
+pub fn stdin() -> Stdin {
+    #[cfg(unix)]
+    if let Some(rx) = reopen(libc::STDIN_FILENO, false).and_then(|fd| Receiver::new(fd.into()).ok())
+    {
+        return Stdin(Inner::Async(rx));
+    }
+    Stdin(Inner::Blocking(Blocking::new(std::io::stdin())))
+}
+
+/// Constructs a new handle to the standard output of the current process.
+///
+/// Writes bypass the buffer of [`std::io::stdout`], output of the two may be
+/// interleaved.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Nio runtime.
+pub fn stdout() -> Stdout {
+    #[cfg(unix)]
+    if let Some(tx) = reopen(libc::STDOUT_FILENO, true).and_then(|fd| Sender::new(fd.into()).ok()) {
+        return Stdout(Inner::Async(tx));
+    }
+    Stdout(Inner::Blocking(Blocking::new(std::io::stdout())))
+}
+
+/// Constructs a new handle to the standard error of the current process.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Nio runtime.
+pub fn stderr() -> Stderr {
+    #[cfg(unix)]
+    if let Some(tx) = reopen(libc::STDERR_FILENO, true).and_then(|fd| Sender::new(fd.into()).ok()) {
+        return Stderr(Inner::Async(tx));
+    }
+    Stderr(Inner::Blocking(Blocking::new(std::io::stderr())))
+}
+
+/// Opens a new file description of a pipe or a terminal in non-blocking mode.
+///
+/// Setting `O_NONBLOCK` on the inherited descriptor would change the mode of
+/// a file description that is shared with other processes, such as the shell.
+/// Reopening it through `/proc` gives us a description of our own.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn reopen(fd: std::os::fd::RawFd, write: bool) -> Option<std::os::fd::OwnedFd> {
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+
+    let path = format!("/proc/self/fd/{fd}");
+    let file_type = std::fs::metadata(&path).ok()?.file_type();
+    if !file_type.is_fifo() && !file_type.is_char_device() {
+        return None;
+    }
+    let file = std::fs::OpenOptions::new()
+        .read(!write)
+        .write(write)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+        .open(path)
+        .ok()?;
+
+    Some(file.into())
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn reopen(_: std::os::fd::RawFd, _: bool) -> Option<std::os::fd::OwnedFd> {
+    None
+}
+
+#[cfg(not(unix))]
+mod unsupported {
+    use std::{
+        io::Result,
+        task::{Context, Poll},
+    };
+
+    /// Standard streams are never registered with the reactor.
+    pub enum Unsupported {}
+
+    impl Unsupported {
+        pub fn poll_read(&self, _: &mut Context, _: &mut [u8]) -> Poll<Result<usize>> {
+            match *self {}
+        }
+
+        pub fn poll_write(&self, _: &mut Context, _: &[u8]) -> Poll<Result<usize>> {
+            match *self {}
+        }
+    }
+}
+
+impl Stdin {
+    pub fn read<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_read(cx, buf))
+    }
+
+    pub(crate) fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>> {
+        match &mut self.0 {
+            Inner::Async(rx) => rx.poll_read(cx, buf),
+            Inner::Blocking(blocking) => blocking.poll_read(cx, buf),
+        }
+    }
+}
+
+macro_rules! stdio_output {
+    [$($name:ident),*] => [$(
+        impl $name {
+            pub fn write<'b>(
+                &mut self,
+                buf: &'b [u8],
+            ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+                poll_fn(|cx| self.poll_write(cx, buf))
+            }
+
+            /// Waits until all buffered data has been written.
+            pub fn flush(&mut self) -> impl Future<Output = Result<()>> + use<'_> {
+                poll_fn(|cx| self.poll_flush(cx))
+            }
+
+            pub(crate) fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+                match &mut self.0 {
+                    Inner::Async(tx) => tx.poll_write(cx, buf),
+                    Inner::Blocking(blocking) => blocking.poll_write(cx, buf),
+                }
+            }
+
+            pub(crate) fn poll_flush(&mut self, cx: &mut Context) -> Poll<Result<()>> {
+                match &mut self.0 {
+                    Inner::Async(_) => Poll::Ready(Ok(())),
+                    Inner::Blocking(blocking) => blocking.poll_flush(cx),
+                }
+            }
+        }
+
+        #[cfg(feature = "futures-io")]
+        impl futures_io::AsyncWrite for $name {
+            #[inline]
+            fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+                $name::poll_write(self.get_mut(), cx, buf)
+            }
+
+            #[inline]
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                $name::poll_flush(self.get_mut(), cx)
+            }
+
+            #[inline]
+            fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+                $name::poll_flush(self.get_mut(), cx)
+            }
+        }
+
+        #[cfg(feature = "tokio-io")]
+        impl tokio::io::AsyncWrite for $name {
+            #[inline]
+            fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+                $name::poll_write(self.get_mut(), cx, buf)
+            }
+
+            #[inline]
+            fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+                $name::poll_flush(self.get_mut(), cx)
+            }
+
+            #[inline]
+            fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+                $name::poll_flush(self.get_mut(), cx)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("async", &self.is_async())
+                    .finish()
+            }
+        }
+    )*]
+}
+
+stdio_output! {
+    Stdout, Stderr
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for Stdin {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Stdin::poll_read(self.get_mut(), cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncRead for Stdin {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<Result<()>> {
+        unsafe {
+            let b = &mut *(buf.unfilled_mut() as *mut _ as *mut [u8]);
+            let n = std::task::ready!(Stdin::poll_read(self.get_mut(), cx, b))?;
+            buf.assume_init(n);
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl fmt::Debug for Stdin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stdin")
+            .field("async", &self.is_async())
+            .finish()
+    }
+}
+
+macro_rules! is_async {
+    [$($name:ident),*] => [$(
+        impl $name {
+            /// Returns `true` if the stream is registered with the reactor,
+            /// `false` if it falls back to the blocking thread pool.
+            pub fn is_async(&self) -> bool {
+                match self.0 {
+                    Inner::Async(_) => true,
+                    Inner::Blocking(_) => false,
+                }
+            }
+        }
+    )*]
+}
+
+is_async! {
+    Stdin, Stdout, Stderr
+}
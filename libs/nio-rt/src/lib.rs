@@ -1,40 +1,76 @@
 #![doc = include_str!("../README.md")]
 
 pub mod fs;
+pub mod io;
 pub mod net;
+#[cfg(unix)]
+pub mod pipe;
+#[cfg(unix)]
+pub mod process;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod time;
 
 mod driver;
 mod local_waker;
+mod loom;
+mod rng;
 mod rt;
 mod timer;
 mod utils;
 
-use std::num::NonZero;
 use std::{num::NonZeroUsize, time::Duration};
 
 pub use nio_macros::*;
 pub use nio_task::id as task_id;
 pub use nio_task::{AbortHandle, JoinError, JoinHandle, TaskId};
+#[cfg(feature = "task-dump")]
+pub use rt::dump;
 pub use rt::{
-    LocalRuntime, Runtime, WorkerId,
+    ConfigError, LocalRuntime, Runtime, RuntimeConfig, WorkerId,
     context::{LocalContext, RuntimeContext},
-    metrics,
+    metrics, watchdog,
 };
 pub use timer::{
-    interval::{Interval, interval},
+    interval::{Interval, MissedTickBehavior, interval, interval_at},
     sleep::{Sleep, sleep},
-    timeout::{Timeout, timeout},
+    timeout::{Timeout, timeout, timeout_at},
 };
 
 use crate::rt::context::{NioContext, no_rt_found_panic};
 
+/// Used by the expansion of `select!`, `join!` and `try_join!`.
+#[doc(hidden)]
+pub mod __private {
+    pub use nio_future::MaybeDone;
+    pub use std::{future::poll_fn, pin::Pin, task::Poll};
+
+    use crate::{rng::Rng, rt::context::NioContext};
+    use std::hash::{BuildHasher, RandomState};
+
+    /// Returns a number in `0..n`, drawn from the worker's random number generator.
+    pub fn rand_below(n: u32) -> u32 {
+        thread_local! {
+            static RNG: Rng = Rng::new(RandomState::new().hash_one(0));
+        }
+        NioContext::get(|ctx| match ctx {
+            NioContext::Local(ctx) => ctx.rng.below(n as usize) as u32,
+            _ => RNG.with(|rng| rng.below(n as usize) as u32),
+        })
+    }
+}
+
 pub struct RuntimeBuilder {
     worker_threads: u8,
     worker_stack_size: Option<NonZeroUsize>,
     worker_name: Box<dyn Fn(u8) -> String + Send + Sync>,
 
     event_interval: u32,
-    min_tasks_per_worker: Option<NonZero<u64>>,
+    min_tasks_per_worker: Option<usize>,
+    start_paused: bool,
+    timer_resolution: Duration,
 
     threadpool_load_factor: usize,
     max_blocking_threads: u16,
@@ -44,6 +80,11 @@ pub struct RuntimeBuilder {
 
     #[cfg(feature = "metrics")]
     measurement: Option<Box<dyn metrics::Measurement>>,
+    #[cfg(feature = "metrics")]
+    instrument: Option<Box<dyn metrics::Instrument>>,
+
+    watchdog: Option<Duration>,
+    on_stall: Option<rt::watchdog::OnStall>,
 }
 
 impl Default for RuntimeBuilder {
@@ -52,14 +93,15 @@ impl Default for RuntimeBuilder {
             worker_threads: std::thread::available_parallelism()
                 .map(|nthread| nthread.get())
                 .unwrap_or(1)
-                .try_into()
-                .unwrap(),
+                .min(u8::MAX.into()) as u8,
 
             worker_stack_size: None,
             worker_name: Box::new(|id| format!("Worker: {id}")),
 
             event_interval: 61,
             min_tasks_per_worker: None,
+            start_paused: false,
+            timer_resolution: timer::DEFAULT_RESOLUTION,
 
             threadpool_load_factor: 2,
             max_blocking_threads: 512,
@@ -69,6 +111,11 @@ impl Default for RuntimeBuilder {
 
             #[cfg(feature = "metrics")]
             measurement: Some(Box::new(metrics::NoMeasurement)),
+            #[cfg(feature = "metrics")]
+            instrument: Some(Box::new(metrics::NoInstrument)),
+
+            watchdog: None,
+            on_stall: None,
         }
     }
 }
@@ -87,8 +134,17 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Receives the scheduling events of every task, see [`metrics::Instrument`].
+    #[allow(warnings)]
+    pub fn instrument(mut self, instrument: impl metrics::Instrument + 'static) -> Self {
+        #[cfg(feature = "metrics")]
+        {
+            self.instrument = Some(Box::new(instrument));
+        }
+        self
+    }
+
     pub fn worker_threads(mut self, val: u8) -> Self {
-        assert!(val > 0);
         self.worker_threads = val;
         self
     }
@@ -99,14 +155,27 @@ impl RuntimeBuilder {
     }
 
     pub fn event_interval(mut self, tick: u32) -> Self {
-        assert!(tick > 0);
         self.event_interval = tick;
         self
     }
 
     pub fn min_tasks_per_worker(mut self, count: usize) -> Self {
-        assert_ne!(count, 0);
-        self.min_tasks_per_worker = NonZero::new(count.try_into().unwrap());
+        self.min_tasks_per_worker = Some(count);
+        self
+    }
+
+    /// Freezes the clock of every worker at startup, see [`time::pause`].
+    pub fn start_paused(mut self, paused: bool) -> Self {
+        self.start_paused = paused;
+        self
+    }
+
+    /// Sets the granularity of the timing wheel, 1ms by default.
+    ///
+    /// Timers fire up to `resolution` after their deadline. Ignored with the
+    /// `timer-btree` feature, which keeps exact deadlines.
+    pub fn timer_resolution(mut self, resolution: Duration) -> Self {
+        self.timer_resolution = resolution;
         self
     }
 
@@ -145,6 +214,24 @@ impl RuntimeBuilder {
         self.worker_name = Box::new(f);
         self
     }
+
+    /// Starts a thread that reports workers stuck in a single poll for longer
+    /// than `threshold`, see [`watchdog`].
+    ///
+    /// Stalls are logged, unless a callback is set with [`RuntimeBuilder::on_stall`].
+    pub fn watchdog(mut self, threshold: Duration) -> Self {
+        self.watchdog = Some(threshold);
+        self
+    }
+
+    /// Called by the watchdog, on its own thread, for each stalled poll.
+    pub fn on_stall<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&watchdog::Stall) + 'static + Send + Sync,
+    {
+        self.on_stall = Some(Box::new(f));
+        self
+    }
 }
 
 pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
@@ -155,11 +242,15 @@ where
     RuntimeContext::with(|ctx| ctx.spawn_blocking(f))
 }
 
+#[track_caller]
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
     F: Future + Send + 'static,
     F::Output: Send + 'static,
 {
+    #[cfg(feature = "task-dump")]
+    rt::dump::set_caller(std::panic::Location::caller());
+
     NioContext::get(|ctx| match ctx {
         NioContext::None => no_rt_found_panic(),
         NioContext::Runtime(ctx) => ctx.spawn(future),
@@ -167,12 +258,16 @@ where
     })
 }
 
+#[track_caller]
 pub fn spawn_pinned<F, Fut>(future: F) -> JoinHandle<Fut::Output>
 where
     F: FnOnce() -> Fut + Send,
     Fut: Future + 'static,
     Fut::Output: Send + 'static,
 {
+    #[cfg(feature = "task-dump")]
+    rt::dump::set_caller(std::panic::Location::caller());
+
     NioContext::get(|ctx| match ctx {
         NioContext::None => no_rt_found_panic(),
         NioContext::Runtime(ctx) => ctx.spawn_pinned(future),
@@ -180,19 +275,27 @@ where
     })
 }
 
+#[track_caller]
 pub fn spawn_pinned_at<F, Fut>(worker: u8, future: F) -> JoinHandle<Fut::Output>
 where
     F: FnOnce() -> Fut + Send,
     Fut: Future + 'static,
     Fut::Output: Send + 'static,
 {
+    #[cfg(feature = "task-dump")]
+    rt::dump::set_caller(std::panic::Location::caller());
+
     RuntimeContext::with(|ctx| ctx.spawn_pinned_at(worker, future))
 }
 
+#[track_caller]
 pub fn spawn_local<Fut>(future: Fut) -> JoinHandle<Fut::Output>
 where
     Fut: Future + 'static,
     Fut::Output: 'static,
 {
+    #[cfg(feature = "task-dump")]
+    rt::dump::set_caller(std::panic::Location::caller());
+
     LocalContext::with(|ctx| ctx.spawn_local(future))
 }
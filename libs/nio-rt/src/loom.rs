@@ -0,0 +1,10 @@
+//! Atomics of the task queue counters, swapped for [`loom`]'s when built with
+//! `RUSTFLAGS="--cfg nio_loom"` to model check them.
+//!
+//! [`loom`]: https://docs.rs/loom
+
+#[cfg(nio_loom)]
+pub use ::loom::sync::atomic;
+
+#[cfg(not(nio_loom))]
+pub use std::sync::atomic;
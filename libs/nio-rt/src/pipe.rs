@@ -0,0 +1,393 @@
+//! Unix pipe types.
+//!
+//! Anonymous pipes are created with [`pipe`], named pipes (FIFOs) are opened
+//! with [`OpenOptions`]. Both ends are registered with the reactor of the
+//! current worker.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! #[nio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let (mut tx, mut rx) = nio::pipe::pipe()?;
+//!
+//!     tx.write(b"hello").await?;
+//!     let mut buf = [0; 5];
+//!     rx.read(&mut buf).await?;
+//!     assert_eq!(&buf, b"hello");
+//!     Ok(())
+//! }
+//! ```
+
+use crate::driver::AsyncIO;
+use std::{
+    fmt,
+    fs::File,
+    future::poll_fn,
+    io::{Error, ErrorKind, IoSlice, Result, Write},
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd},
+        unix::fs::{FileTypeExt, OpenOptionsExt},
+    },
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Creates a new anonymous Unix pipe.
+///
+/// Both ends are opened in non-blocking mode with `O_CLOEXEC` set.
+pub fn pipe() -> Result<(Sender, Receiver)> {
+    let (tx, rx) = mio::unix::pipe::new()?;
+    Ok((Sender::new(tx)?, Receiver::new(rx)?))
+}
+
+/// Options and flags which can be used to configure how a FIFO file is opened.
+///
+/// # Examples
+///
+/// ```no_run
+/// use nio::pipe::OpenOptions;
+///
+/// # async fn dox() -> std::io::Result<()> {
+/// let rx = OpenOptions::new().open_receiver("path/to/a/fifo")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct OpenOptions {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    read_write: bool,
+    unchecked: bool,
+}
+
+impl OpenOptions {
+    /// Creates a blank new set of options ready for configuration.
+    pub fn new() -> OpenOptions {
+        OpenOptions {
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            read_write: false,
+            unchecked: false,
+        }
+    }
+
+    /// Sets the option for read-write access.
+    ///
+    /// Opening the sending end of a FIFO fails with `ENXIO`, if there is no
+    /// reader. With read-write access the FIFO is opened regardless, and the
+    /// receiving end never observes EOF, since it keeps a writer itself.
+    ///
+    /// This behavior is Linux specific.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn read_write(&mut self, value: bool) -> &mut Self {
+        self.read_write = value;
+        self
+    }
+
+    /// Sets the option to skip the check for FIFO file type.
+    ///
+    /// By default, opening a file that is not a FIFO returns an error.
+    pub fn unchecked(&mut self, value: bool) -> &mut Self {
+        self.unchecked = value;
+        self
+    }
+
+    /// Creates a [`Receiver`] from a FIFO file with the options specified by `self`.
+    pub fn open_receiver<P: AsRef<Path>>(&self, path: P) -> Result<Receiver> {
+        let file = self.open(path.as_ref(), PipeEnd::Receiver)?;
+        Receiver::new(mio::unix::pipe::Receiver::from(OwnedFd::from(file)))
+    }
+
+    /// Creates a [`Sender`] from a FIFO file with the options specified by `self`.
+    pub fn open_sender<P: AsRef<Path>>(&self, path: P) -> Result<Sender> {
+        let file = self.open(path.as_ref(), PipeEnd::Sender)?;
+        Sender::new(mio::unix::pipe::Sender::from(OwnedFd::from(file)))
+    }
+
+    fn open(&self, path: &Path, end: PipeEnd) -> Result<File> {
+        let mut options = std::fs::OpenOptions::new();
+        options
+            .read(end == PipeEnd::Receiver)
+            .write(end == PipeEnd::Sender)
+            .custom_flags(libc::O_NONBLOCK);
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if self.read_write {
+            options.read(true).write(true);
+        }
+
+        let file = options.open(path)?;
+        if !self.unchecked && !is_fifo(&file)? {
+            return Err(Error::new(ErrorKind::InvalidInput, "not a pipe"));
+        }
+        Ok(file)
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions::new()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PipeEnd {
+    Sender,
+    Receiver,
+}
+
+fn is_fifo(file: &File) -> Result<bool> {
+    Ok(file.metadata()?.file_type().is_fifo())
+}
+
+/// Checks the access mode of `fd`, it must allow reading or writing for `end`.
+fn check_access_mode(fd: BorrowedFd, end: PipeEnd) -> Result<()> {
+    // SAFETY: `fd` is a valid file descriptor.
+    let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) };
+    if flags < 0 {
+        return Err(Error::last_os_error());
+    }
+    match (end, flags & libc::O_ACCMODE) {
+        (PipeEnd::Sender, libc::O_WRONLY | libc::O_RDWR)
+        | (PipeEnd::Receiver, libc::O_RDONLY | libc::O_RDWR) => Ok(()),
+        (PipeEnd::Sender, _) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "not in O_WRONLY or O_RDWR access mode",
+        )),
+        (PipeEnd::Receiver, _) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "not in O_RDONLY or O_RDWR access mode",
+        )),
+    }
+}
+
+/// Writing end of a Unix pipe.
+///
+/// Data written to a `Sender` can be read from the corresponding [`Receiver`].
+pub struct Sender(AsyncIO<mio::unix::pipe::Sender>);
+
+/// Reading end of a Unix pipe.
+///
+/// Data written to the corresponding [`Sender`] can be read from a `Receiver`.
+pub struct Receiver(AsyncIO<mio::unix::pipe::Receiver>);
+
+impl Sender {
+    pub(crate) fn new(io: mio::unix::pipe::Sender) -> Result<Sender> {
+        io.set_nonblocking(true)?;
+        Ok(Sender(AsyncIO::with_interest(io, mio::Interest::WRITABLE)?))
+    }
+
+    /// Creates a new `Sender` from a FIFO file.
+    ///
+    /// The file must be a FIFO opened in `O_WRONLY` or `O_RDWR` access mode.
+    /// It's set to non-blocking mode.
+    pub fn from_file(file: File) -> Result<Sender> {
+        if !is_fifo(&file)? {
+            return Err(Error::new(ErrorKind::InvalidInput, "not a pipe"));
+        }
+        Sender::from_owned_fd(file.into())
+    }
+
+    /// Creates a new `Sender` from an [`OwnedFd`], without checking its file type.
+    ///
+    /// The file descriptor must be in `O_WRONLY` or `O_RDWR` access mode.
+    /// It's set to non-blocking mode.
+    pub fn from_owned_fd(fd: OwnedFd) -> Result<Sender> {
+        check_access_mode(fd.as_fd(), PipeEnd::Sender)?;
+        Sender::new(mio::unix::pipe::Sender::from(fd))
+    }
+
+    pub fn write<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.0.poll_write(cx, buf))
+    }
+
+    pub fn write_vectored<'b>(
+        &mut self,
+        bufs: &'b [IoSlice],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        self.0
+            .io_write(|mut io| Write::write_vectored(&mut io, bufs))
+    }
+
+    /// Waits for the pipe to become writable.
+    pub async fn writable(&self) {
+        self.0.io_writable().await
+    }
+
+    #[inline]
+    pub(crate) fn poll_write(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    #[inline]
+    pub(crate) fn poll_write_vectored(
+        &self,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        let mut poll_fn = self
+            .0
+            .io_write(|mut io| Write::write_vectored(&mut io, bufs));
+
+        Pin::new(&mut poll_fn).poll(cx)
+    }
+}
+
+impl Receiver {
+    pub(crate) fn new(io: mio::unix::pipe::Receiver) -> Result<Receiver> {
+        io.set_nonblocking(true)?;
+        Ok(Receiver(AsyncIO::with_interest(
+            io,
+            mio::Interest::READABLE,
+        )?))
+    }
+
+    /// Creates a new `Receiver` from a FIFO file.
+    ///
+    /// The file must be a FIFO opened in `O_RDONLY` or `O_RDWR` access mode.
+    /// It's set to non-blocking mode.
+    pub fn from_file(file: File) -> Result<Receiver> {
+        if !is_fifo(&file)? {
+            return Err(Error::new(ErrorKind::InvalidInput, "not a pipe"));
+        }
+        Receiver::from_owned_fd(file.into())
+    }
+
+    /// Creates a new `Receiver` from an [`OwnedFd`], without checking its file type.
+    ///
+    /// The file descriptor must be in `O_RDONLY` or `O_RDWR` access mode.
+    /// It's set to non-blocking mode.
+    pub fn from_owned_fd(fd: OwnedFd) -> Result<Receiver> {
+        check_access_mode(fd.as_fd(), PipeEnd::Receiver)?;
+        Receiver::new(mio::unix::pipe::Receiver::from(fd))
+    }
+
+    pub fn read<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.0.poll_read(cx, buf))
+    }
+
+    #[inline]
+    pub(crate) fn poll_read(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.0.poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for Receiver {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Receiver::poll_read(&self, cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncRead for Receiver {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<Result<()>> {
+        unsafe {
+            let b = &mut *(buf.unfilled_mut() as *mut _ as *mut [u8]);
+            let n = std::task::ready!(Receiver::poll_read(&self, cx, b))?;
+            buf.assume_init(n);
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncWrite for Sender {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Sender::poll_write(&self, cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        Sender::poll_write_vectored(&self, cx, bufs)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncWrite for Sender {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        Sender::poll_write(&self, cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        Sender::poll_write_vectored(&self, cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+macro_rules! impl_fd {
+    [$($name:ty),*] => [$(
+        impl AsRawFd for $name {
+            fn as_raw_fd(&self) -> RawFd {
+                self.0.io.as_raw_fd()
+            }
+        }
+
+        impl AsFd for $name {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                self.0.io.as_fd()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*]
+}
+
+impl_fd! {
+    Sender, Receiver
+}
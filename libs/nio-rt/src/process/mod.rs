@@ -0,0 +1,370 @@
+//! An implementation of asynchronous process management.
+//!
+//! This module provides a [`Command`] struct that imitates the interface of
+//! [`std::process::Command`], but spawns a [`Child`] whose exit status and
+//! standard I/O can be awaited on the current worker.
+//!
+//! On Linux the child's exit is detected through a `pidfd` registered with
+//! the worker's reactor. Where `pidfd_open` is not available, Nio falls back
+//! to listening for `SIGCHLD`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use nio::process::Command;
+//!
+//! #[nio::main]
+//! async fn main() -> std::io::Result<()> {
+//!     let output = Command::new("echo").arg("hello world").output().await?;
+//!     assert!(output.status.success());
+//!     assert_eq!(output.stdout, b"hello world\n");
+//!     Ok(())
+//! }
+//! ```
+
+mod reap;
+mod sigchld;
+mod stdio;
+
+pub use stdio::{ChildStderr, ChildStdin, ChildStdout};
+
+use reap::Reaper;
+use std::{
+    ffi::OsStr,
+    fmt,
+    future::poll_fn,
+    io::{self, Result},
+    os::unix::process::CommandExt,
+    path::Path,
+    process::{ExitStatus, Output, Stdio},
+    task::{Context, Poll, ready},
+};
+
+/// This structure mimics the API of [`std::process::Command`] found in the
+/// standard library, but replaces functions that create a process with an
+/// asynchronous variant.
+pub struct Command {
+    std: std::process::Command,
+    kill_on_drop: bool,
+}
+
+impl Command {
+    /// Constructs a new `Command` for launching the program at
+    /// path `program`. See [`std::process::Command::new`] for more details.
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+        Command::from(std::process::Command::new(program))
+    }
+
+    /// Cheaply convert to a `&std::process::Command` for places where the type from the standard
+    /// library is expected.
+    pub fn as_std(&self) -> &std::process::Command {
+        &self.std
+    }
+
+    /// Cheaply convert to a `&mut std::process::Command` for places where the type from the
+    /// standard library is expected.
+    pub fn as_std_mut(&mut self) -> &mut std::process::Command {
+        &mut self.std
+    }
+
+    pub fn into_std(self) -> std::process::Command {
+        self.std
+    }
+
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command {
+        self.std.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.env(key, val);
+        self
+    }
+
+    pub fn envs<I, K, V>(&mut self, vars: I) -> &mut Command
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.envs(vars);
+        self
+    }
+
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Command {
+        self.std.env_remove(key);
+        self
+    }
+
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.std.env_clear();
+        self
+    }
+
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command {
+        self.std.current_dir(dir);
+        self
+    }
+
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stdin(cfg);
+        self
+    }
+
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stdout(cfg);
+        self
+    }
+
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stderr(cfg);
+        self
+    }
+
+    /// Sets the child process's user ID.
+    pub fn uid(&mut self, id: u32) -> &mut Command {
+        self.std.uid(id);
+        self
+    }
+
+    /// Similar to `uid` but sets the group ID of the child process.
+    pub fn gid(&mut self, id: u32) -> &mut Command {
+        self.std.gid(id);
+        self
+    }
+
+    /// Sets the process group ID (PGID) of the child process.
+    pub fn process_group(&mut self, pgroup: i32) -> &mut Command {
+        self.std.process_group(pgroup);
+        self
+    }
+
+    /// Controls whether a `kill` operation should be invoked on a spawned child
+    /// process when its corresponding `Child` handle is dropped.
+    ///
+    /// By default, this value is assumed to be `false`, meaning the next spawned
+    /// process will not be killed on drop, similar to the behavior of the standard
+    /// library.
+    ///
+    /// A child that is dropped before it exits is still reaped by the runtime,
+    /// so it does not linger as a zombie process.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Command {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Executes the command as a child process, returning a handle to it.
+    ///
+    /// The child is bound to the current worker: its exit and its standard
+    /// I/O handles are driven by that worker's reactor.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if called outside of a Nio worker thread.
+    pub fn spawn(&mut self) -> Result<Child> {
+        let mut child = self.std.spawn()?;
+
+        let stdin = child.stdin.take().map(ChildStdin::new).transpose();
+        let stdout = child.stdout.take().map(ChildStdout::new).transpose();
+        let stderr = child.stderr.take().map(ChildStderr::new).transpose();
+
+        let reaper = Reaper::new(child)?;
+        Ok(Child {
+            reaper,
+            kill_on_drop: self.kill_on_drop,
+            stdin: stdin?,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+
+    /// Executes the command as a child process, waiting for it to finish and
+    /// collecting its exit status.
+    ///
+    /// The standard I/O handles of the child are dropped before waiting, so
+    /// a child reading from a piped `stdin` observes EOF.
+    pub async fn status(&mut self) -> Result<ExitStatus> {
+        let mut child = self.spawn()?;
+        drop(child.stdin.take());
+        drop(child.stdout.take());
+        drop(child.stderr.take());
+        child.wait().await
+    }
+
+    /// Executes the command as a child process, waiting for it to finish and
+    /// collecting all of its output.
+    ///
+    /// By default, stdout and stderr are captured (and used to provide the
+    /// resulting output).
+    pub async fn output(&mut self) -> Result<Output> {
+        self.std.stdout(Stdio::piped());
+        self.std.stderr(Stdio::piped());
+        self.spawn()?.wait_with_output().await
+    }
+}
+
+impl From<std::process::Command> for Command {
+    fn from(std: std::process::Command) -> Command {
+        Command {
+            std,
+            kill_on_drop: false,
+        }
+    }
+}
+
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.std.fmt(f)
+    }
+}
+
+/// Representation of a child process spawned onto the current worker.
+///
+/// A `Child` is not [`Send`]; it must be awaited on the worker that spawned it.
+pub struct Child {
+    reaper: Reaper,
+    kill_on_drop: bool,
+
+    /// The handle for writing to the child's standard input (stdin), if it has
+    /// been captured.
+    pub stdin: Option<ChildStdin>,
+
+    /// The handle for reading from the child's standard output (stdout), if it
+    /// has been captured.
+    pub stdout: Option<ChildStdout>,
+
+    /// The handle for reading from the child's standard error (stderr), if it
+    /// has been captured.
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier associated with this child.
+    pub fn id(&self) -> u32 {
+        self.reaper.id()
+    }
+
+    /// Attempts to collect the exit status of the child if it has already
+    /// exited, without blocking the worker.
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        self.reaper.try_wait()
+    }
+
+    /// Waits for the child to exit completely, returning the status that it
+    /// exited with.
+    ///
+    /// The stdin handle to the child process, if any, will be closed before
+    /// waiting. This helps avoid deadlock: it ensures that the child does not
+    /// block waiting for input from the parent, while the parent waits for
+    /// the child to exit.
+    ///
+    /// This function is cancel safe.
+    pub async fn wait(&mut self) -> Result<ExitStatus> {
+        drop(self.stdin.take());
+        poll_fn(|cx| self.reaper.poll_wait(cx)).await
+    }
+
+    /// Attempts to force the child to exit, but does not wait for the request
+    /// to take effect.
+    pub fn start_kill(&mut self) -> Result<()> {
+        self.reaper.kill()
+    }
+
+    /// Forces the child to exit and waits for it.
+    ///
+    /// This is equivalent to sending a `SIGKILL` on unix platforms.
+    pub async fn kill(&mut self) -> Result<()> {
+        self.start_kill()?;
+        self.wait().await?;
+        Ok(())
+    }
+
+    /// Simultaneously waits for the child to exit and collect all remaining
+    /// output on the stdout/stderr handles, returning an `Output` instance.
+    ///
+    /// In order to capture the output into this `Output` it is necessary to
+    /// create new pipes between parent and child. Use `stdout(Stdio::piped())`
+    /// or `stderr(Stdio::piped())`, respectively, when creating a `Command`.
+    pub async fn wait_with_output(mut self) -> Result<Output> {
+        drop(self.stdin.take());
+
+        let mut stdout_pipe = self.stdout.take();
+        let mut stderr_pipe = self.stderr.take();
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        poll_fn(|cx| {
+            let stdout_done = poll_read_to_end(cx, &mut stdout_pipe, &mut stdout)?;
+            let stderr_done = poll_read_to_end(cx, &mut stderr_pipe, &mut stderr)?;
+            if stdout_done.is_ready() && stderr_done.is_ready() {
+                return Poll::Ready(Ok::<_, io::Error>(()));
+            }
+            Poll::Pending
+        })
+        .await?;
+
+        let status = self.wait().await?;
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+/// Reads `pipe` until EOF, then drops it.
+fn poll_read_to_end<P: stdio::PollRead>(
+    cx: &mut Context,
+    pipe: &mut Option<P>,
+    buf: &mut Vec<u8>,
+) -> Poll<Result<()>> {
+    let Some(io) = pipe else {
+        return Poll::Ready(Ok(()));
+    };
+    let mut chunk = [0; 4096];
+    loop {
+        match ready!(io.poll_read(cx, &mut chunk)) {
+            Ok(0) => {
+                *pipe = None;
+                return Poll::Ready(Ok(()));
+            }
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Poll::Ready(Err(err)),
+        }
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if self.kill_on_drop {
+            let _ = self.reaper.kill();
+        }
+    }
+}
+
+impl fmt::Debug for Child {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Child")
+            .field("pid", &self.id())
+            .field("kill_on_drop", &self.kill_on_drop)
+            .field("stdin", &self.stdin)
+            .field("stdout", &self.stdout)
+            .field("stderr", &self.stderr)
+            .finish()
+    }
+}
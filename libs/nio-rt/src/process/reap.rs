@@ -0,0 +1,284 @@
+use super::sigchld;
+use crate::{driver::AsyncIO, rt::context::NioContext};
+use mio::{Interest, Registry, Token, event::Source, unix::SourceFd};
+use std::{
+    future::poll_fn,
+    io::{ErrorKind, Result},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    pin::Pin,
+    process::ExitStatus,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    task::{Context, Poll, ready},
+};
+
+/// Children that were dropped before they exited.
+///
+/// They are reaped on `SIGCHLD` by [`reap_orphans_on_sigchld`], so they don't
+/// linger as zombie processes.
+static ORPHANS: Mutex<Vec<std::process::Child>> = Mutex::new(Vec::new());
+
+/// Set while a [`reap_orphans_on_sigchld`] task runs.
+static REAPING: AtomicBool = AtomicBool::new(false);
+
+pub fn reap_orphans() {
+    let Ok(mut orphans) = ORPHANS.try_lock() else {
+        return;
+    };
+    orphans.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+}
+
+fn orphan(child: std::process::Child) {
+    ORPHANS.lock().unwrap().push(child);
+    if REAPING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    // Outside of a worker, orphans are reaped by the next `Reaper::new`.
+    let spawned = NioContext::get(|ctx| match ctx {
+        NioContext::Local(ctx) => {
+            ctx.spawn_local(reap_orphans_on_sigchld());
+            true
+        }
+        _ => false,
+    });
+    if !spawned {
+        REAPING.store(false, Ordering::Release);
+    }
+}
+
+/// Reaps `ORPHANS` each time a child exits, until there are none left.
+async fn reap_orphans_on_sigchld() {
+    struct Reaping;
+    impl Drop for Reaping {
+        fn drop(&mut self) {
+            REAPING.store(false, Ordering::Release);
+        }
+    }
+    let _reaping = Reaping;
+    let Ok(mut listener) = sigchld::Listener::new() else {
+        return;
+    };
+    loop {
+        {
+            let mut orphans = ORPHANS.lock().unwrap();
+            orphans.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+            if orphans.is_empty() {
+                REAPING.store(false, Ordering::Release);
+                drop(orphans);
+                // An orphan pushed after the check, and not yet observed by this task.
+                if ORPHANS.lock().unwrap().is_empty() || REAPING.swap(true, Ordering::AcqRel) {
+                    return;
+                }
+                continue;
+            }
+        }
+        poll_fn(|cx| listener.poll_recv(cx)).await;
+    }
+}
+
+/// How the exit of a child is observed.
+enum Notifier {
+    /// `pidfd` becomes readable when the process exits.
+    Pidfd(AsyncIO<Pidfd>),
+    /// Fallback, when `pidfd_open` is not supported.
+    Signal(sigchld::Listener),
+}
+
+pub struct Reaper {
+    child: Option<std::process::Child>,
+    status: Option<ExitStatus>,
+    notifier: Notifier,
+}
+
+impl Reaper {
+    /// Fails if the exit of `child` can not be observed, `child` is then killed.
+    pub fn new(mut child: std::process::Child) -> Result<Reaper> {
+        reap_orphans();
+        let notifier = match Pidfd::open(child.id())
+            .and_then(|pidfd| AsyncIO::with_interest(pidfd, Interest::READABLE).ok())
+        {
+            Some(io) => Notifier::Pidfd(io),
+            None => match sigchld::Listener::new() {
+                Ok(listener) => Notifier::Signal(listener),
+                Err(err) => {
+                    let _ = child.kill();
+                    orphan(child);
+                    return Err(err);
+                }
+            },
+        };
+        Ok(Reaper {
+            child: Some(child),
+            status: None,
+            notifier,
+        })
+    }
+
+    #[cfg(test)]
+    pub fn with_signal(child: std::process::Child) -> Reaper {
+        Reaper {
+            child: Some(child),
+            status: None,
+            notifier: Notifier::Signal(sigchld::Listener::new().unwrap()),
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        unsafe { self.child.as_ref().unwrap_unchecked().id() }
+    }
+
+    pub fn kill(&mut self) -> Result<()> {
+        if self.status.is_some() {
+            return Ok(());
+        }
+        unsafe { self.child.as_mut().unwrap_unchecked().kill() }
+    }
+
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        let status = unsafe { self.child.as_mut().unwrap_unchecked().try_wait()? };
+        self.status = status;
+        Ok(status)
+    }
+
+    pub fn poll_wait(&mut self, cx: &mut Context) -> Poll<Result<ExitStatus>> {
+        if let Some(status) = self.status {
+            return Poll::Ready(Ok(status));
+        }
+        // `child` is only taken in `Drop`
+        let child = unsafe { self.child.as_mut().unwrap_unchecked() };
+        let status = match &mut self.notifier {
+            Notifier::Pidfd(io) => {
+                // `pidfd` becomes readable once the process has exited.
+                let mut exited = io.io_read(|_| match child.try_wait()? {
+                    Some(status) => Ok(status),
+                    None => Err(ErrorKind::WouldBlock.into()),
+                });
+                ready!(Pin::new(&mut exited).poll(cx))?
+            }
+            Notifier::Signal(listener) => loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
+                }
+                ready!(listener.poll_recv(cx));
+            },
+        };
+        self.status = Some(status);
+        Poll::Ready(Ok(status))
+    }
+}
+
+impl Drop for Reaper {
+    fn drop(&mut self) {
+        if let Ok(Some(_)) = self.try_wait() {
+            return;
+        }
+        if let Some(child) = self.child.take() {
+            orphan(child);
+        }
+    }
+}
+
+/// A file descriptor referring to a process.
+#[derive(Debug)]
+pub struct Pidfd(OwnedFd);
+
+impl Pidfd {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn open(pid: u32) -> Option<Pidfd> {
+        // SAFETY: `pidfd_open` has no memory safety requirements.
+        let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+        if fd < 0 {
+            // `ENOSYS`: kernel older than 5.3
+            return None;
+        }
+        // SAFETY: `fd` is a newly opened file descriptor owned by us.
+        Some(Pidfd(unsafe { OwnedFd::from_raw_fd(fd as i32) }))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn open(_: u32) -> Option<Pidfd> {
+        None
+    }
+}
+
+impl Source for Pidfd {
+    fn register(&mut self, registry: &Registry, token: Token, interests: Interest) -> Result<()> {
+        SourceFd(&self.0.as_raw_fd()).register(registry, token, interests)
+    }
+
+    fn reregister(&mut self, registry: &Registry, token: Token, interests: Interest) -> Result<()> {
+        SourceFd(&self.0.as_raw_fd()).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> Result<()> {
+        SourceFd(&self.0.as_raw_fd()).deregister(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+
+    #[test]
+    fn sigchld_fallback() {
+        let mut rt = crate::RuntimeBuilder::new()
+            .worker_threads(1)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let mut reapers: Vec<_> = (0..4)
+                .map(|code| {
+                    let child = std::process::Command::new("sh")
+                        .args(["-c", &format!("sleep 0.1; exit {code}")])
+                        .spawn()
+                        .unwrap();
+                    Reaper::with_signal(child)
+                })
+                .collect();
+
+            for (code, reaper) in reapers.iter_mut().enumerate() {
+                let status = poll_fn(|cx| reaper.poll_wait(cx)).await.unwrap();
+                assert_eq!(status.code(), Some(code as i32));
+            }
+        });
+    }
+
+    #[test]
+    fn dropped_children_are_reaped() {
+        let mut rt = crate::RuntimeBuilder::new()
+            .worker_threads(1)
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let pids: Vec<_> = (0..3)
+                .map(|_| {
+                    let child = std::process::Command::new("sh")
+                        .args(["-c", "sleep 0.05"])
+                        .spawn()
+                        .unwrap();
+                    let pid = child.id() as libc::pid_t;
+                    drop(Reaper::new(child).unwrap());
+                    pid
+                })
+                .collect();
+
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            let reaped = |pid| {
+                // `ECHILD` once the zombie was reaped, `WNOWAIT` leaves it to the runtime.
+                let mut info = unsafe { std::mem::zeroed() };
+                let flags = libc::WEXITED | libc::WNOHANG | libc::WNOWAIT;
+                unsafe { libc::waitid(libc::P_PID, pid as libc::id_t, &mut info, flags) == -1 }
+            };
+            while !pids.iter().all(|&pid| reaped(pid)) {
+                assert!(std::time::Instant::now() < deadline, "zombie children");
+                crate::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,242 @@
+//! `SIGCHLD` based child exit notification.
+//!
+//! Used where `pidfd` is not available. The signal handler writes one byte
+//! into a pipe of every worker that waits for a child, then each worker wakes
+//! all of its waiting children, which check their exit status.
+
+use crate::driver::AsyncIO;
+use mio::unix::pipe::{self, Receiver};
+use std::{
+    cell::RefCell,
+    io::{ErrorKind, Read},
+    mem::{self, ManuallyDrop},
+    os::fd::{AsFd, AsRawFd, RawFd},
+    rc::{Rc, Weak},
+    sync::{
+        Arc, Mutex, Once, OnceLock,
+        atomic::{AtomicI32, Ordering},
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+/// Write ends of the worker pipes, `-1` means the slot was never used.
+///
+/// A published fd is never closed, nor replaced: [`handler`] may load it on
+/// any thread and write to it later, possibly after the worker released the
+/// slot. The read end of a released slot is kept in [`IDLE`], so that such a
+/// write doesn't fail with `EPIPE`, and the pipe is reused by the next worker.
+static PIPES: [AtomicI32; 64] = [const { AtomicI32::new(-1) }; 64];
+
+/// Read ends of the slots that are not used by any worker.
+static IDLE: Mutex<Vec<(usize, Receiver)>> = Mutex::new(Vec::new());
+
+static INSTALL: Once = Once::new();
+
+/// The `SIGCHLD` action replaced by [`handler`], which is chained to it.
+static PREVIOUS: OnceLock<libc::sigaction> = OnceLock::new();
+
+thread_local! {
+    static SIGNAL: RefCell<Weak<Signal>> = const { RefCell::new(Weak::new()) };
+}
+
+extern "C" fn handler(signum: libc::c_int, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+    // SAFETY: Only async-signal-safe functions are called here.
+    unsafe {
+        let errno = *errno_location();
+        for slot in &PIPES {
+            let fd = slot.load(Ordering::Acquire);
+            if fd >= 0 {
+                libc::write(fd, [1u8].as_ptr().cast(), 1);
+            }
+        }
+        if let Some(previous) = PREVIOUS.get() {
+            chain(previous, signum, info, ctx);
+        }
+        *errno_location() = errno;
+    }
+}
+
+/// Calls the handler of `previous`, if it is not `SIG_DFL` or `SIG_IGN`.
+unsafe fn chain(
+    previous: &libc::sigaction,
+    signum: libc::c_int,
+    info: *mut libc::siginfo_t,
+    ctx: *mut libc::c_void,
+) {
+    let handler = previous.sa_sigaction;
+    if handler == libc::SIG_DFL || handler == libc::SIG_IGN {
+        return;
+    }
+    unsafe {
+        if previous.sa_flags & libc::SA_SIGINFO != 0 {
+            let handler: extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void) =
+                mem::transmute(handler);
+            handler(signum, info, ctx)
+        } else {
+            let handler: extern "C" fn(libc::c_int) = mem::transmute(handler);
+            handler(signum)
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    unsafe { libc::__errno_location() }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+unsafe fn errno_location() -> *mut libc::c_int {
+    unsafe { libc::__error() }
+}
+
+fn install_handler() {
+    INSTALL.call_once(|| unsafe {
+        let mut previous: libc::sigaction = mem::zeroed();
+        if libc::sigaction(libc::SIGCHLD, std::ptr::null(), &mut previous) != 0 {
+            panic!("failed to read the `SIGCHLD` handler");
+        }
+        let chained =
+            previous.sa_sigaction != libc::SIG_DFL && previous.sa_sigaction != libc::SIG_IGN;
+        // Only a chained handler may want to be notified of stopped children.
+        let nocldstop = match chained {
+            true => previous.sa_flags & libc::SA_NOCLDSTOP,
+            false => libc::SA_NOCLDSTOP,
+        };
+        let _ = PREVIOUS.set(previous);
+
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler as *const () as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO | nocldstop;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(libc::SIGCHLD, &action, std::ptr::null_mut()) != 0 {
+            panic!("failed to install `SIGCHLD` handler");
+        }
+    });
+}
+
+/// Wakes every child waiting on this worker.
+#[derive(Default)]
+struct Waiters(Mutex<Vec<Waker>>);
+
+impl Wake for Waiters {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let waiters = mem::take(&mut *self.0.lock().unwrap());
+        for waker in waiters {
+            waker.wake();
+        }
+    }
+}
+
+/// Worker local `SIGCHLD` receiver.
+struct Signal {
+    slot: usize,
+    reader: ManuallyDrop<AsyncIO<Receiver>>,
+    waiters: Arc<Waiters>,
+    waker: Waker,
+}
+
+impl Signal {
+    fn new() -> std::io::Result<Signal> {
+        let (slot, reader) = match IDLE.lock().unwrap().pop() {
+            // Bytes left in the pipe only cause a spurious wake up.
+            Some(idle) => idle,
+            None => Signal::claim_slot()?,
+        };
+        // The read end must outlive a failed registration.
+        let spare = match reader.as_fd().try_clone_to_owned() {
+            Ok(spare) => spare,
+            Err(err) => {
+                IDLE.lock().unwrap().push((slot, reader));
+                return Err(err);
+            }
+        };
+        let reader = AsyncIO::with_interest(reader, mio::Interest::READABLE).inspect_err(|_| {
+            IDLE.lock().unwrap().push((slot, Receiver::from(spare)));
+        })?;
+
+        let waiters = Arc::new(Waiters::default());
+        let signal = Signal {
+            slot,
+            reader: ManuallyDrop::new(reader),
+            waker: Waker::from(waiters.clone()),
+            waiters,
+        };
+        install_handler();
+        Ok(signal)
+    }
+
+    /// Publishes the write end of a new pipe in an empty slot.
+    fn claim_slot() -> std::io::Result<(usize, Receiver)> {
+        let (writer, reader) = pipe::new()?;
+        let fd: RawFd = writer.as_raw_fd();
+        let slot = PIPES
+            .iter()
+            .position(|slot| {
+                slot.compare_exchange(-1, fd, Ordering::AcqRel, Ordering::Relaxed)
+                    .is_ok()
+            })
+            .ok_or_else(|| std::io::Error::other("too many `SIGCHLD` listeners"))?;
+        // Never closed, see `PIPES`.
+        mem::forget(writer);
+        Ok((slot, reader))
+    }
+
+    /// Returns `Ready` if a signal was received since the last call.
+    fn poll_recv(&self, cx: &mut Context) -> Poll<()> {
+        {
+            let mut waiters = self.waiters.0.lock().unwrap();
+            if !waiters.iter().any(|w| w.will_wake(cx.waker())) {
+                waiters.push(cx.waker().clone());
+            }
+        }
+        let mut received = false;
+        let mut buf = [0; 64];
+        let mut cx = Context::from_waker(&self.waker);
+        loop {
+            let mut read = self.reader.io_read(|mut io| io.read(&mut buf));
+            match std::pin::Pin::new(&mut read).poll(&mut cx) {
+                Poll::Ready(Ok(n)) if n > 0 => received = true,
+                Poll::Ready(Err(err)) if err.kind() == ErrorKind::Interrupted => {}
+                _ => break,
+            }
+        }
+        if received {
+            // Other children on this worker may have exited too.
+            self.waiters.wake_by_ref();
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Signal {
+    fn drop(&mut self) {
+        // SAFETY: `reader` is not used again.
+        let reader = unsafe { ManuallyDrop::take(&mut self.reader) }.into_inner();
+        IDLE.lock().unwrap().push((self.slot, reader));
+    }
+}
+
+/// Shared by all children waiting on the current worker.
+pub struct Listener(Rc<Signal>);
+
+impl Listener {
+    pub fn new() -> std::io::Result<Listener> {
+        SIGNAL.with(|signal| {
+            if let Some(signal) = signal.borrow().upgrade() {
+                return Ok(Listener(signal));
+            }
+            let new = Rc::new(Signal::new()?);
+            *signal.borrow_mut() = Rc::downgrade(&new);
+            Ok(Listener(new))
+        })
+    }
+
+    pub fn poll_recv(&mut self, cx: &mut Context) -> Poll<()> {
+        self.0.poll_recv(cx)
+    }
+}
@@ -0,0 +1,226 @@
+use crate::pipe::{Receiver, Sender};
+use std::{
+    fmt,
+    io::{IoSlice, Result},
+    os::fd::{AsFd, AsRawFd, BorrowedFd, RawFd},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The standard input stream for spawned children.
+///
+/// This type implements the `AsyncWrite` trait to pass data to the stdin
+/// handle of a child process asynchronously.
+pub struct ChildStdin(Sender);
+
+/// The standard output stream for spawned children.
+///
+/// This type implements the `AsyncRead` trait to read data from the stdout
+/// handle of a child process asynchronously.
+pub struct ChildStdout(Receiver);
+
+/// The standard error stream for spawned children.
+///
+/// This type implements the `AsyncRead` trait to read data from the stderr
+/// handle of a child process asynchronously.
+pub struct ChildStderr(Receiver);
+
+pub(super) trait PollRead {
+    fn poll_read(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>>;
+}
+
+impl ChildStdin {
+    /// Creates an asynchronous `ChildStdin` from a synchronous one.
+    pub fn from_std(inner: std::process::ChildStdin) -> Result<ChildStdin> {
+        ChildStdin::new(inner)
+    }
+
+    pub(super) fn new(inner: std::process::ChildStdin) -> Result<ChildStdin> {
+        Ok(ChildStdin(Sender::new(inner.into())?))
+    }
+
+    pub fn write<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        self.0.write(buf)
+    }
+
+    pub fn write_vectored<'b>(
+        &mut self,
+        bufs: &'b [IoSlice],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        self.0.write_vectored(bufs)
+    }
+
+    #[inline]
+    pub(crate) fn poll_write(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.0.poll_write(cx, buf)
+    }
+
+    #[inline]
+    pub(crate) fn poll_write_vectored(
+        &self,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        self.0.poll_write_vectored(cx, bufs)
+    }
+}
+
+macro_rules! child_output {
+    [$($name:ident),*] => [$(
+        impl $name {
+            /// Creates an asynchronous handle from a synchronous one.
+            pub fn from_std(inner: std::process::$name) -> Result<$name> {
+                $name::new(inner)
+            }
+
+            pub(super) fn new(inner: std::process::$name) -> Result<$name> {
+                Ok($name(Receiver::new(inner.into())?))
+            }
+
+            pub fn read<'b>(
+                &mut self,
+                buf: &'b mut [u8],
+            ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+                self.0.read(buf)
+            }
+        }
+
+        impl PollRead for $name {
+            #[inline]
+            fn poll_read(&self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>> {
+                self.0.poll_read(cx, buf)
+            }
+        }
+
+        #[cfg(feature = "futures-io")]
+        impl futures_io::AsyncRead for $name {
+            #[inline]
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<Result<usize>> {
+                self.0.poll_read(cx, buf)
+            }
+        }
+
+        #[cfg(feature = "tokio-io")]
+        impl tokio::io::AsyncRead for $name {
+            fn poll_read(
+                self: Pin<&mut Self>,
+                cx: &mut Context,
+                buf: &mut tokio::io::ReadBuf,
+            ) -> Poll<Result<()>> {
+                unsafe {
+                    let b = &mut *(buf.unfilled_mut() as *mut _ as *mut [u8]);
+                    let n = std::task::ready!(self.0.poll_read(cx, b))?;
+                    buf.assume_init(n);
+                    buf.advance(n);
+                    Poll::Ready(Ok(()))
+                }
+            }
+        }
+
+        impl AsRawFd for $name {
+            fn as_raw_fd(&self) -> RawFd {
+                self.0.as_raw_fd()
+            }
+        }
+
+        impl AsFd for $name {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                self.0.as_fd()
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+    )*]
+}
+
+child_output! {
+    ChildStdout, ChildStderr
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncWrite for ChildStdin {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        ChildStdin::poll_write(&self, cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        ChildStdin::poll_write_vectored(&self, cx, bufs)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncWrite for ChildStdin {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        ChildStdin::poll_write(&self, cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        ChildStdin::poll_write_vectored(&self, cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsRawFd for ChildStdin {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0.as_raw_fd()
+    }
+}
+
+impl AsFd for ChildStdin {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.as_fd()
+    }
+}
+
+impl fmt::Debug for ChildStdin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
@@ -0,0 +1,48 @@
+use std::cell::Cell;
+#[cfg(feature = "sim")]
+use std::time::Duration;
+
+/// SplitMix64, small and good enough for simulations and scheduling fairness.
+pub(crate) struct Rng(Cell<u64>);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(Cell::new(seed))
+    }
+
+    #[cfg(feature = "sim")]
+    pub(crate) fn set_seed(&self, seed: u64) {
+        self.0.set(seed);
+    }
+
+    pub(crate) fn next_u64(&self) -> u64 {
+        let state = self.0.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        self.0.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    pub(crate) fn below(&self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns `true` with the given probability.
+    #[cfg(feature = "sim")]
+    pub(crate) fn chance(&self, probability: f64) -> bool {
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < probability
+    }
+
+    /// Returns a duration in `min..=max`.
+    #[cfg(feature = "sim")]
+    pub(crate) fn duration(&self, (min, max): (Duration, Duration)) -> Duration {
+        let range = (max - min).as_nanos() as u64;
+        match range.checked_add(1) {
+            Some(n) => min + Duration::from_nanos(self.next_u64() % n),
+            None => min + Duration::from_nanos(self.next_u64()),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::RuntimeBuilder;
+use std::{env, error::Error, fmt, io, str::FromStr, time::Duration};
+
+/// Settings of [`RuntimeBuilder`] that are tuned per host.
+///
+/// `None` keeps the default of the builder. Read from environment variables
+/// with [`RuntimeConfig::from_env`], or deserialized with the `serde` feature:
+///
+/// ```toml
+/// worker_threads = 8
+/// max_blocking_threads = 64
+/// thread_timeout = "30s"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct RuntimeConfig {
+    pub worker_threads: Option<u8>,
+    pub event_interval: Option<u32>,
+    pub min_tasks_per_worker: Option<usize>,
+    pub max_blocking_threads: Option<u16>,
+    /// `Some(None)` keeps idle blocking threads alive forever.
+    ///
+    /// Written as seconds, a duration such as `"500ms"`, `"10s"` or `"5m"`,
+    /// or `"none"`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "deserialize_thread_timeout")
+    )]
+    pub thread_timeout: Option<Option<Duration>>,
+}
+
+impl RuntimeConfig {
+    /// Reads `NIO_WORKER_THREADS`, `NIO_EVENT_INTERVAL`, `NIO_MIN_TASKS_PER_WORKER`,
+    /// `NIO_MAX_BLOCKING_THREADS` and `NIO_THREAD_TIMEOUT`, unset variables are `None`.
+    pub fn from_env() -> Result<RuntimeConfig, ConfigError> {
+        Ok(RuntimeConfig {
+            worker_threads: var("NIO_WORKER_THREADS")?,
+            event_interval: var("NIO_EVENT_INTERVAL")?,
+            min_tasks_per_worker: var("NIO_MIN_TASKS_PER_WORKER")?,
+            max_blocking_threads: var("NIO_MAX_BLOCKING_THREADS")?,
+            thread_timeout: var::<String>("NIO_THREAD_TIMEOUT")?
+                .map(|value| {
+                    parse_timeout(&value)
+                        .map_err(|reason| ConfigError::new("NIO_THREAD_TIMEOUT", reason))
+                })
+                .transpose()?,
+        })
+    }
+}
+
+fn var<T>(key: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    match env::var(key) {
+        Ok(value) => match value.trim().parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(err) => Err(ConfigError::new(key, format!("{value:?}, {err}"))),
+        },
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(ConfigError::new(key, "not valid unicode")),
+    }
+}
+
+fn parse_timeout(value: &str) -> Result<Option<Duration>, String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    let invalid = || format!("{value:?}, expected a duration such as `10s` or `500ms`, or `none`");
+    let (number, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    match unit.trim() {
+        "" | "s" => Ok(Some(Duration::from_secs(number))),
+        "ms" => Ok(Some(Duration::from_millis(number))),
+        "m" => Ok(Some(Duration::from_secs(number.saturating_mul(60)))),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_thread_timeout<'de, D>(deserializer: D) -> Result<Option<Option<Duration>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(serde::Deserialize)]
+    #[serde(untagged)]
+    enum Timeout {
+        Secs(u64),
+        Text(String),
+    }
+    match serde::Deserialize::deserialize(deserializer)? {
+        Timeout::Secs(secs) => Ok(Some(Some(Duration::from_secs(secs)))),
+        Timeout::Text(text) => parse_timeout(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// An invalid setting of [`RuntimeBuilder`], or an invalid environment variable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    key: &'static str,
+    reason: String,
+}
+
+impl ConfigError {
+    fn new(key: &'static str, reason: impl Into<String>) -> ConfigError {
+        ConfigError {
+            key,
+            reason: reason.into(),
+        }
+    }
+
+    /// Name of the setting, or of the environment variable.
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid `{}`: {}", self.key, self.reason)
+    }
+}
+
+impl Error for ConfigError {}
+
+impl From<ConfigError> for io::Error {
+    fn from(err: ConfigError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+impl RuntimeBuilder {
+    /// A builder configured by [`RuntimeConfig::from_env`], and validated.
+    pub fn from_env() -> Result<RuntimeBuilder, ConfigError> {
+        let builder = RuntimeBuilder::new().config(&RuntimeConfig::from_env()?);
+        builder.validate()?;
+        Ok(builder)
+    }
+
+    /// Overrides the settings that are `Some` in `config`.
+    pub fn config(mut self, config: &RuntimeConfig) -> Self {
+        if let Some(val) = config.worker_threads {
+            self.worker_threads = val;
+        }
+        if let Some(tick) = config.event_interval {
+            self.event_interval = tick;
+        }
+        if let Some(count) = config.min_tasks_per_worker {
+            self.min_tasks_per_worker = Some(count);
+        }
+        if let Some(val) = config.max_blocking_threads {
+            self.max_blocking_threads = val;
+        }
+        if let Some(dur) = config.thread_timeout {
+            self.thread_timeout = dur;
+        }
+        self
+    }
+
+    /// Checks the settings, called by [`RuntimeBuilder::rt`] and [`RuntimeBuilder::build`].
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let at_least_one = "must be at least 1";
+        if self.worker_threads == 0 {
+            return Err(ConfigError::new("worker_threads", at_least_one));
+        }
+        if self.event_interval == 0 {
+            return Err(ConfigError::new("event_interval", at_least_one));
+        }
+        if self.min_tasks_per_worker == Some(0) {
+            return Err(ConfigError::new("min_tasks_per_worker", at_least_one));
+        }
+        if self.max_blocking_threads == 0 {
+            return Err(ConfigError::new("max_blocking_threads", at_least_one));
+        }
+        if self.threadpool_load_factor == 0 {
+            return Err(ConfigError::new("threadpool_load_factor", at_least_one));
+        }
+        if self.timer_resolution.is_zero() {
+            return Err(ConfigError::new("timer_resolution", "must be non-zero"));
+        }
+        if self.watchdog.is_some_and(|threshold| threshold.is_zero()) {
+            return Err(ConfigError::new("watchdog", "must be non-zero"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts() {
+        assert_eq!(parse_timeout("10"), Ok(Some(Duration::from_secs(10))));
+        assert_eq!(parse_timeout(" 10s "), Ok(Some(Duration::from_secs(10))));
+        assert_eq!(parse_timeout("500ms"), Ok(Some(Duration::from_millis(500))));
+        assert_eq!(parse_timeout("5m"), Ok(Some(Duration::from_secs(300))));
+        assert_eq!(parse_timeout("None"), Ok(None));
+        assert!(parse_timeout("").is_err());
+        assert!(parse_timeout("10h").is_err());
+        assert!(parse_timeout("-1s").is_err());
+    }
+}
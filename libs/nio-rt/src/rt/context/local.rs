@@ -1,9 +1,14 @@
-use crate::timer::Timers;
+use crate::{rng::Rng, timer::Timers};
 
 use super::*;
 use task::*;
 
-use std::{cell::UnsafeCell, collections::VecDeque, rc::Rc};
+use std::{
+    cell::UnsafeCell,
+    collections::VecDeque,
+    hash::{BuildHasher, RandomState},
+    rc::Rc,
+};
 use task_queue::{Counter, TaskQueue};
 use worker::{SharedQueue, WorkerId};
 
@@ -12,8 +17,13 @@ pub struct LocalContext {
     local_queue: UnsafeCell<VecDeque<Task>>,
 
     pub(crate) worker_id: WorkerId,
+    /// Picks the first branch polled by `select!` and `join!`.
+    pub(crate) rng: Rng,
     pub(crate) runtime_ctx: Arc<RuntimeContext>,
     pub(crate) io_registry: driver::Registry,
+    /// `None` if the kernel doesn't support `io_uring`.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub(crate) uring: Option<driver::uring::Uring>,
 }
 
 impl LocalContext {
@@ -25,9 +35,12 @@ impl LocalContext {
     ) -> Rc<Self> {
         LocalContext {
             worker_id,
-            timers: UnsafeCell::new(Timers::new()),
+            rng: Rng::new(RandomState::new().hash_one(worker_id)),
+            timers: UnsafeCell::new(Timers::new(runtime_ctx.timer_resolution)),
             local_queue: UnsafeCell::new(VecDeque::with_capacity(cap)),
             runtime_ctx,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            uring: driver::uring::Uring::new(&io_registry).ok(),
             io_registry,
         }
         .into()
@@ -56,26 +69,84 @@ impl LocalContext {
         self.worker_id
     }
 
+    #[track_caller]
     pub fn spawn_local<Fut>(&self, future: Fut) -> JoinHandle<Fut::Output>
     where
         Fut: Future + 'static,
         Fut::Output: 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let (task, join) =
             unsafe { LocalScheduler::spawn(self.worker_id, self.runtime_ctx.clone(), future) };
 
+        #[cfg(feature = "metrics")]
+        {
+            self.measure(|m, id| m.task_spawned(Some(id), metrics::SpawnKind::Local));
+            (self.runtime_ctx.instrument).on_task_spawn(
+                task.id(),
+                self.worker_id,
+                metrics::SpawnKind::Local,
+            );
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<Fut>(),
+            metrics::SpawnKind::Local,
+            self.worker_id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.runtime_ctx.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<Fut>(),
+            location,
+            metrics::SpawnKind::Local,
+            self.worker_id,
+        );
+
         self.add_task_to_local_queue(task);
         join
     }
 
+    #[track_caller]
     pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let (task, join) = Scheduler::spawn(self.runtime_ctx.clone(), future);
         let id = self.runtime_ctx.workers.least_loaded_worker();
 
+        #[cfg(feature = "metrics")]
+        {
+            (self.runtime_ctx.measurement).task_spawned(Some(id.get()), metrics::SpawnKind::Spawn);
+            (self.runtime_ctx.instrument).on_task_spawn(task.id(), id, metrics::SpawnKind::Spawn);
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<F>(),
+            metrics::SpawnKind::Spawn,
+            id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.runtime_ctx.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<F>(),
+            location,
+            metrics::SpawnKind::Spawn,
+            id,
+        );
+
         if self.worker_id == id {
             self.add_task_to_local_queue(task);
         } else {
@@ -84,15 +155,42 @@ impl LocalContext {
         join
     }
 
+    #[track_caller]
     pub fn spawn_pinned<F, Fut>(&self, future: F) -> JoinHandle<Fut::Output>
     where
         F: FnOnce() -> Fut + Send,
         Fut: Future + 'static,
         Fut::Output: Send + 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let id = self.runtime_ctx.workers.least_loaded_worker();
         let (task, join) = unsafe { LocalScheduler::spawn(id, self.runtime_ctx.clone(), future()) };
 
+        #[cfg(feature = "metrics")]
+        {
+            (self.runtime_ctx.measurement).task_spawned(Some(id.get()), metrics::SpawnKind::Pinned);
+            (self.runtime_ctx.instrument).on_task_spawn(task.id(), id, metrics::SpawnKind::Pinned);
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<Fut>(),
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.runtime_ctx.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<Fut>(),
+            location,
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+
         if self.worker_id == id {
             self.add_task_to_local_queue(task);
         } else {
@@ -102,6 +200,9 @@ impl LocalContext {
     }
 
     pub(crate) fn add_task_to_local_queue(&self, task: Task) {
+        #[cfg(feature = "metrics")]
+        self.runtime_ctx.mark_scheduled(&task);
+
         unsafe { self.local_queue(|q| q.push_back(task)) };
         let counter = self.task_queue().increase_local();
         self.move_tasks_from_shared_to_local_queue(counter)
@@ -168,9 +269,19 @@ impl LocalContext {
                 unsafe { self.local_queue(|q| q.push_back(task)) };
             }
             self.task_queue().move_shared_to_local(counter);
+
+            #[cfg(feature = "metrics")]
+            self.measure(|m, id| m.tasks_transferred(id, count as usize));
         }
     }
 
+    /// Calls `f` with the measurement of the runtime and the id of this worker.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub(crate) fn measure(&self, f: impl FnOnce(&dyn metrics::Measurement, usize)) {
+        f(&*self.runtime_ctx.measurement, self.worker_id.get())
+    }
+
     #[inline]
     pub(crate) fn task_queue(&self) -> &TaskQueue {
         self.runtime_ctx.workers.task_queue(self.worker_id)
@@ -39,6 +39,14 @@ impl NioContext {
         }
     }
 
+    /// The worker of the current thread, if any.
+    pub(crate) fn worker_id(&self) -> Option<WorkerId> {
+        match self {
+            NioContext::Local(ctx) => Some(ctx.worker_id),
+            _ => None,
+        }
+    }
+
     fn init(local: Rc<LocalContext>) {
         CONTEXT.with(|ctx| unsafe {
             (*ctx.get()).panic_if_exist();
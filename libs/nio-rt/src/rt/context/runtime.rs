@@ -1,12 +1,23 @@
 use super::*;
 use task::*;
 
+use std::time::Duration;
+
 pub struct RuntimeContext {
     pub(crate) workers: Workers,
     pub(crate) threadpool: ThreadPool<BlockingTask>,
+    pub(crate) timer_resolution: Duration,
 
     #[cfg(feature = "metrics")]
     pub(crate) measurement: Box<dyn metrics::Measurement>,
+    #[cfg(feature = "metrics")]
+    pub(crate) instrument: Box<dyn metrics::Instrument>,
+    /// Cached [`metrics::Measurement::timing_enabled`].
+    #[cfg(feature = "metrics")]
+    pub(crate) timing: bool,
+    #[cfg(feature = "task-dump")]
+    pub(crate) task_registry: Arc<dump::Registry>,
+    pub(crate) watchdog: Option<watchdog::Watchdog>,
 }
 
 impl RuntimeContext {
@@ -29,6 +40,13 @@ impl RuntimeContext {
         metrics::RuntimeMetrics { ctx: self }
     }
 
+    /// Lists every live task of the runtime, see [`dump`](crate::dump).
+    #[cfg(feature = "task-dump")]
+    pub fn dump(&self) -> impl Future<Output = dump::Dump> + use<> {
+        let registry = self.task_registry.clone();
+        async move { registry.dump() }
+    }
+
     pub fn enter(self: Arc<Self>) {
         NioContext::enter(self);
     }
@@ -39,49 +57,152 @@ impl RuntimeContext {
         R: Send + 'static,
     {
         let (task, join) = BlockingTask::spawn(f);
+
+        #[cfg(feature = "metrics")]
+        self.measurement
+            .task_spawned(None, metrics::SpawnKind::Blocking);
+
         self.threadpool.execute(task);
         join
     }
 
+    #[track_caller]
     pub fn spawn<F>(self: &Arc<Self>, future: F) -> JoinHandle<F::Output>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let (task, join) = Scheduler::spawn(self.clone(), future);
-        self.send_task_at(self.workers.least_loaded_worker(), task);
+        let id = self.workers.least_loaded_worker();
+
+        #[cfg(feature = "metrics")]
+        {
+            self.measurement
+                .task_spawned(Some(id.get()), metrics::SpawnKind::Spawn);
+            (self.instrument).on_task_spawn(task.id(), id, metrics::SpawnKind::Spawn);
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<F>(),
+            metrics::SpawnKind::Spawn,
+            id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<F>(),
+            location,
+            metrics::SpawnKind::Spawn,
+            id,
+        );
+
+        self.send_task_at(id, task);
         join
     }
 
+    #[track_caller]
     pub fn spawn_pinned_at<F, Fut>(self: &Arc<Self>, id: u8, future: F) -> JoinHandle<Fut::Output>
     where
         F: FnOnce() -> Fut + Send,
         Fut: Future + 'static,
         Fut::Output: Send + 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let id = self.workers.id(id);
         let (task, join) = unsafe { LocalScheduler::spawn(id, self.clone(), future()) };
+
+        #[cfg(feature = "metrics")]
+        {
+            self.measurement
+                .task_spawned(Some(id.get()), metrics::SpawnKind::Pinned);
+            (self.instrument).on_task_spawn(task.id(), id, metrics::SpawnKind::Pinned);
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<Fut>(),
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<Fut>(),
+            location,
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+
         self.send_task_at(id, task);
         join
     }
 
+    #[track_caller]
     pub fn spawn_pinned<F, Fut>(self: &Arc<Self>, future: F) -> JoinHandle<Fut::Output>
     where
         F: FnOnce() -> Fut + Send,
         Fut: Future + 'static,
         Fut::Output: Send + 'static,
     {
+        #[cfg(feature = "task-dump")]
+        let location = dump::caller();
+
         let id = self.workers.least_loaded_worker();
         let (task, join) = unsafe { LocalScheduler::spawn(id, self.clone(), future()) };
+
+        #[cfg(feature = "metrics")]
+        {
+            self.measurement
+                .task_spawned(Some(id.get()), metrics::SpawnKind::Pinned);
+            (self.instrument).on_task_spawn(task.id(), id, metrics::SpawnKind::Pinned);
+        }
+        #[cfg(feature = "tracing")]
+        trace::spawned(
+            &task,
+            std::any::type_name::<Fut>(),
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+        #[cfg(feature = "task-dump")]
+        dump::register(
+            &self.task_registry,
+            task.metadata(),
+            task.id(),
+            std::any::type_name::<Fut>(),
+            location,
+            metrics::SpawnKind::Pinned,
+            id,
+        );
+
         self.send_task_at(id, task);
         join
     }
 
-    pub(crate) fn send_task_to_least_loaded_worker(&self, task: Task) {
-        self.send_task_at(self.workers.least_loaded_worker(), task);
+    /// Starts the scheduling delay of `task`, measured when it is next polled.
+    #[cfg(feature = "metrics")]
+    #[inline]
+    pub(crate) fn mark_scheduled(&self, task: &Task) {
+        if self.timing {
+            task.metadata()
+                .scheduled_at
+                .set(Some(std::time::Instant::now()));
+        }
     }
 
     pub(crate) fn send_task_at(&self, id: WorkerId, task: Task) {
+        #[cfg(feature = "metrics")]
+        self.mark_scheduled(&task);
+
         self.workers.shared_queue(id).push(task);
 
         let task_queue = self.workers.task_queue(id);
@@ -92,7 +213,9 @@ impl RuntimeContext {
 
             if let Err(_err) = self.workers.notifier(id).wake() {
                 task_queue.clear_notified_flag();
-                #[cfg(debug_assertions)]
+                #[cfg(feature = "tracing")]
+                tracing::warn!(target: "nio::worker", worker = id.get(), "notifier error: {_err}");
+                #[cfg(all(debug_assertions, not(feature = "tracing")))]
                 eprintln!("notifier error: {_err}");
             }
         }
@@ -0,0 +1,280 @@
+//! A snapshot of every live task, with the `task-dump` feature.
+//!
+//! ```ignore
+//! let dump = nio::RuntimeContext::current().dump().await;
+//! eprintln!("{dump}");
+//! ```
+//!
+//! Tasks are tracked in a registry shared by the workers, so a dump is taken
+//! without the help of any worker, and completes even if a worker is blocked.
+//! Blocking tasks and the main future of `block_on` are not tracked.
+
+use super::{task::TaskMeta, worker::WorkerId};
+use crate::TaskId;
+use nio_metrics::SpawnKind;
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt,
+    panic::Location,
+    ptr,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU8, AtomicU64, Ordering::Relaxed},
+    },
+    time::{Duration, Instant},
+};
+
+/// Scheduling state of a task, as in `nio_task`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// Woken, waiting in a queue to be polled.
+    Notified,
+    /// Being polled by its worker.
+    Running,
+    /// Waiting to be woken.
+    Sleep,
+    Complete,
+}
+
+impl TaskState {
+    fn from_u8(state: u8) -> TaskState {
+        match state {
+            0 => TaskState::Notified,
+            1 => TaskState::Running,
+            2 => TaskState::Sleep,
+            _ => TaskState::Complete,
+        }
+    }
+}
+
+/// A live task, see [`Dump`].
+#[derive(Debug, Clone)]
+pub struct TaskDump {
+    pub id: TaskId,
+    /// Type name of the future.
+    pub name: &'static str,
+    /// Where the task was spawned.
+    pub location: &'static Location<'static>,
+    pub kind: SpawnKind,
+    /// The worker that last polled the task, or that it was sent to.
+    pub worker: WorkerId,
+    pub state: TaskState,
+    pub polls: u64,
+    /// Time since the task was spawned.
+    pub age: Duration,
+    /// The operation and resource the task waited for when it last returned
+    /// `Pending`, if it was a nio I/O resource or timer.
+    pub awaiting: Option<(&'static str, &'static str)>,
+}
+
+/// Every live task of a runtime, see `RuntimeContext::dump`.
+#[derive(Debug, Clone)]
+pub struct Dump {
+    /// Sorted by worker, then by age, oldest first.
+    pub tasks: Vec<TaskDump>,
+}
+
+impl fmt::Display for Dump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} live tasks", self.tasks.len())?;
+        for task in &self.tasks {
+            write!(
+                f,
+                "{:?} {:?} {}, {:?}, {:?}, {} polls, {:?} old, spawned at {}",
+                task.worker,
+                task.id,
+                task.name,
+                task.kind,
+                task.state,
+                task.polls,
+                task.age,
+                task.location,
+            )?;
+            if let Some((op, resource)) = task.awaiting {
+                write!(f, ", awaiting {op} on {resource}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct TaskEntry {
+    id: TaskId,
+    name: &'static str,
+    location: &'static Location<'static>,
+    kind: SpawnKind,
+    spawned_at: Instant,
+    worker: AtomicU8,
+    state: AtomicU8,
+    polls: AtomicU64,
+    awaiting: Mutex<Option<(&'static str, &'static str)>>,
+}
+
+impl TaskEntry {
+    fn set_state(&self, state: TaskState) {
+        self.state.store(state as u8, Relaxed);
+    }
+
+    fn snapshot(&self, now: Instant) -> TaskDump {
+        TaskDump {
+            id: self.id,
+            name: self.name,
+            location: self.location,
+            kind: self.kind,
+            worker: WorkerId::new(self.worker.load(Relaxed)),
+            state: TaskState::from_u8(self.state.load(Relaxed)),
+            polls: self.polls.load(Relaxed),
+            age: now.saturating_duration_since(self.spawned_at),
+            awaiting: *self.awaiting.lock().unwrap(),
+        }
+    }
+}
+
+/// Live tasks of a runtime, shared by its workers.
+#[derive(Default)]
+pub(crate) struct Registry {
+    tasks: Mutex<HashMap<TaskId, Arc<TaskEntry>>>,
+}
+
+impl Registry {
+    pub(crate) fn dump(&self) -> Dump {
+        let now = Instant::now();
+        let mut tasks: Vec<_> = (self.tasks.lock().unwrap().values())
+            .map(|entry| entry.snapshot(now))
+            .filter(|task| task.state != TaskState::Complete)
+            .collect();
+        tasks.sort_by(|a, b| (a.worker, b.age).cmp(&(b.worker, a.age)));
+        Dump { tasks }
+    }
+}
+
+/// Registration of a task, removed from the registry when its metadata is dropped.
+pub(crate) struct Handle {
+    entry: Arc<TaskEntry>,
+    registry: Arc<Registry>,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let mut tasks = self.registry.tasks.lock().unwrap();
+        if tasks
+            .get(&self.entry.id)
+            .is_some_and(|entry| Arc::ptr_eq(entry, &self.entry))
+        {
+            tasks.remove(&self.entry.id);
+        }
+    }
+}
+
+thread_local! {
+    /// Override of the spawn location, set by the free spawn functions.
+    static CALLER: Cell<Option<&'static Location<'static>>> = const { Cell::new(None) };
+    /// The task being polled on this thread.
+    static CURRENT: Cell<*const TaskEntry> = const { Cell::new(ptr::null()) };
+}
+
+/// Location of the caller of a `#[track_caller]` spawn method.
+#[track_caller]
+pub(crate) fn caller() -> &'static Location<'static> {
+    CALLER
+        .with(Cell::take)
+        .unwrap_or_else(|| Location::caller())
+}
+
+/// Records the location of the caller of a free spawn function, which calls
+/// a spawn method from a closure.
+pub(crate) fn set_caller(location: &'static Location<'static>) {
+    CALLER.with(|caller| caller.set(Some(location)));
+}
+
+pub(crate) fn register(
+    registry: &Arc<Registry>,
+    meta: &TaskMeta,
+    id: TaskId,
+    name: &'static str,
+    location: &'static Location<'static>,
+    kind: SpawnKind,
+    worker: WorkerId,
+) {
+    let entry = Arc::new(TaskEntry {
+        id,
+        name,
+        location,
+        kind,
+        spawned_at: Instant::now(),
+        worker: AtomicU8::new(worker.get() as u8),
+        state: AtomicU8::new(TaskState::Notified as u8),
+        polls: AtomicU64::new(0),
+        awaiting: Mutex::new(None),
+    });
+    registry.tasks.lock().unwrap().insert(id, entry.clone());
+    let _ = meta.dump.set(Handle {
+        entry,
+        registry: registry.clone(),
+    });
+}
+
+/// `meta` was put in a queue.
+pub(crate) fn notified(meta: &TaskMeta) {
+    if let Some(handle) = meta.dump.get() {
+        handle.entry.set_state(TaskState::Notified);
+    }
+}
+
+/// Marks the task as running on `worker` until the guard is dropped.
+pub(crate) fn polling(meta: &TaskMeta, worker: WorkerId) -> Polling {
+    let entry = match meta.dump.get() {
+        Some(handle) => {
+            let entry = &handle.entry;
+            entry.set_state(TaskState::Running);
+            entry.worker.store(worker.get() as u8, Relaxed);
+            entry.polls.fetch_add(1, Relaxed);
+            *entry.awaiting.lock().unwrap() = None;
+            entry.clone()
+        }
+        None => return Polling(None),
+    };
+    CURRENT.with(|current| current.set(Arc::as_ptr(&entry)));
+    Polling(Some(entry))
+}
+
+pub(crate) struct Polling(Option<Arc<TaskEntry>>);
+
+impl Polling {
+    pub(crate) fn finish(self, status: &nio_task::Status<TaskMeta>) {
+        let Some(entry) = &self.0 else { return };
+        match status {
+            nio_task::Status::Yielded(_) => entry.set_state(TaskState::Notified),
+            // The task may already have been woken by another thread.
+            nio_task::Status::Pending => {
+                let _ = entry.state.compare_exchange(
+                    TaskState::Running as u8,
+                    TaskState::Sleep as u8,
+                    Relaxed,
+                    Relaxed,
+                );
+            }
+            nio_task::Status::Complete(_) => entry.set_state(TaskState::Complete),
+        }
+    }
+}
+
+impl Drop for Polling {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            CURRENT.with(|current| current.set(ptr::null()));
+        }
+    }
+}
+
+/// The task being polled waits for `op` on `resource`.
+pub(crate) fn awaiting(op: &'static str, resource: &'static str) {
+    CURRENT.with(|current| {
+        // SAFETY: set only while `Polling` holds the entry alive.
+        if let Some(entry) = unsafe { current.get().as_ref() } {
+            *entry.awaiting.lock().unwrap() = Some((op, resource));
+        }
+    });
+}
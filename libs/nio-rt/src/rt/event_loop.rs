@@ -1,7 +1,11 @@
 use crate::{
     LocalContext, RuntimeContext,
     driver::{self, Driver},
-    rt::{context::NioContext, task::LocalScheduler, task_queue::TaskQueue},
+    rt::{
+        context::NioContext,
+        task::{LocalScheduler, Task, TaskMeta},
+        task_queue::TaskQueue,
+    },
 };
 use nio_task::Status;
 use std::{
@@ -13,10 +17,16 @@ use std::{
     time::Duration,
 };
 
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
 pub struct EventLoop {
     tick: u32,
     driver: Driver,
     pub local_ctx: Rc<LocalContext>,
+    /// Picks the next task at random, instead of in FIFO order.
+    #[cfg(feature = "sim")]
+    pub(crate) shuffle: Option<crate::rng::Rng>,
 }
 
 impl EventLoop {
@@ -29,12 +39,17 @@ impl EventLoop {
     ) -> Self {
         let worker_id = runtime_ctx.workers.id(id);
         let io_registry = driver.registry_owned().unwrap();
+        if let Some(watchdog) = &runtime_ctx.watchdog {
+            watchdog.register_thread(worker_id);
+        }
         let local_ctx = LocalContext::new(worker_id, local_queue_cap, runtime_ctx, io_registry);
         local_ctx.clone().init();
         EventLoop {
             tick,
             driver,
             local_ctx,
+            #[cfg(feature = "sim")]
+            shuffle: None,
         }
     }
 
@@ -52,11 +67,17 @@ impl EventLoop {
 
         self.run_with(|this, task_queue| {
             for _ in 0..this.tick {
-                let Some(task) = (unsafe { this.local_ctx.local_queue(|q| q.pop_front()) }) else {
+                let Some(task) = this.next_task() else {
                     break;
                 };
-                match task.poll() {
+                match this.poll_task(task) {
                     Status::Yielded(task) => {
+                        #[cfg(feature = "metrics")]
+                        {
+                            this.local_ctx.measure(|m, id| m.task_yielded(id));
+                            this.local_ctx.runtime_ctx.mark_scheduled(&task);
+                        }
+
                         unsafe { this.local_ctx.local_queue(|q| q.push_back(task)) };
                     }
                     Status::Pending => {
@@ -65,6 +86,9 @@ impl EventLoop {
                             .move_tasks_from_shared_to_local_queue(counter);
                     }
                     Status::Complete(meta) => {
+                        #[cfg(feature = "metrics")]
+                        this.local_ctx.measure(|m, id| m.task_completed(id));
+
                         let counter = task_queue.decrease_local();
                         this.local_ctx
                             .move_tasks_from_shared_to_local_queue(counter);
@@ -85,20 +109,127 @@ impl EventLoop {
         }
     }
 
+    fn next_task(&self) -> Option<Task> {
+        #[cfg(feature = "sim")]
+        if let Some(rng) = &self.shuffle {
+            return unsafe {
+                self.local_ctx.local_queue(|q| match q.len() {
+                    0 => None,
+                    len => q.swap_remove_back(rng.below(len)),
+                })
+            };
+        }
+        unsafe { self.local_ctx.local_queue(|q| q.pop_front()) }
+    }
+
+    #[inline]
+    fn poll_task(&self, task: Task) -> Status<TaskMeta> {
+        let heartbeat = (self.local_ctx.runtime_ctx.watchdog.as_ref())
+            .map(|watchdog| watchdog.heartbeat(self.local_ctx.worker_id));
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.enter(task.id());
+        }
+
+        #[cfg(feature = "task-dump")]
+        let polling = crate::rt::dump::polling(task.metadata(), self.local_ctx.worker_id);
+
+        #[cfg(feature = "tracing")]
+        let status = {
+            let span = task.metadata().span().cloned();
+            let _entered = span.as_ref().map(|span| span.enter());
+            tracing::trace!(target: "nio::task", "poll");
+            let status = self.poll_task_measured(task);
+            crate::rt::trace::polled(&status);
+            status
+        };
+        #[cfg(not(feature = "tracing"))]
+        let status = self.poll_task_measured(task);
+
+        #[cfg(feature = "task-dump")]
+        polling.finish(&status);
+
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.exit();
+        }
+        status
+    }
+
+    #[inline]
+    fn poll_task_measured(&self, task: Task) -> Status<TaskMeta> {
+        #[cfg(feature = "metrics")]
+        {
+            let runtime_ctx = &self.local_ctx.runtime_ctx;
+            let (id, worker) = (task.id(), self.local_ctx.worker_id);
+
+            self.local_ctx.measure(|m, id| m.task_polled(id));
+            runtime_ctx.instrument.on_task_poll_start(id, worker);
+            let status = if runtime_ctx.timing {
+                self.poll_task_timed(task)
+            } else {
+                task.poll()
+            };
+            runtime_ctx.instrument.on_task_poll_end(id, worker);
+            match status {
+                Status::Yielded(_) => runtime_ctx
+                    .instrument
+                    .on_task_wake(id, Some(worker), worker),
+                Status::Complete(_) => runtime_ctx.instrument.on_task_terminate(id, worker),
+                Status::Pending => {}
+            }
+            status
+        }
+        #[cfg(not(feature = "metrics"))]
+        task.poll()
+    }
+
+    #[cfg(feature = "metrics")]
+    #[cold]
+    fn poll_task_timed(&self, task: Task) -> Status<TaskMeta> {
+        let start = Instant::now();
+        if let Some(scheduled_at) = task.metadata().scheduled_at.take() {
+            self.local_ctx.measure(|m, id| {
+                m.task_scheduling_delay(id, start.saturating_duration_since(scheduled_at))
+            });
+        }
+        let status = task.poll();
+        let elapsed = start.elapsed();
+        self.local_ctx
+            .measure(|m, id| m.task_poll_time(id, elapsed));
+        status
+    }
+
+    pub fn pause_clock(&self) {
+        unsafe { self.local_ctx.timers(|timer| timer.pause()) }
+    }
+
     pub fn run(&mut self) {
         self.run_with(Self::execute_tasks);
     }
 
     pub fn execute_tasks(&self, task_queue: &TaskQueue) -> ControlFlow<(), ()> {
         for _ in 0..self.tick {
-            let Some(task) = (unsafe { self.local_ctx.local_queue(|q| q.pop_front()) }) else {
+            let Some(task) = self.next_task() else {
                 break;
             };
-            match task.poll() {
+            match self.poll_task(task) {
                 Status::Yielded(task) => {
+                    #[cfg(feature = "metrics")]
+                    {
+                        self.local_ctx.measure(|m, id| m.task_yielded(id));
+                        self.local_ctx.runtime_ctx.mark_scheduled(&task);
+                    }
+
                     unsafe { self.local_ctx.local_queue(|q| q.push_back(task)) };
                 }
-                Status::Pending | Status::Complete(_) => {
+                Status::Pending => {
+                    let counter = task_queue.decrease_local();
+                    self.local_ctx
+                        .move_tasks_from_shared_to_local_queue(counter);
+                }
+                Status::Complete(_) => {
+                    #[cfg(feature = "metrics")]
+                    self.local_ctx.measure(|m, id| m.task_completed(id));
+
                     let counter = task_queue.decrease_local();
                     self.local_ctx
                         .move_tasks_from_shared_to_local_queue(counter);
@@ -110,6 +241,8 @@ impl EventLoop {
 
     pub fn run_with(&mut self, process_tasks: impl Fn(&Self, &TaskQueue) -> ControlFlow<(), ()>) {
         let task_queue = self.local_ctx.task_queue();
+        #[cfg(feature = "metrics")]
+        let mut unparked_at = Instant::now();
 
         loop {
             match process_tasks(self, task_queue) {
@@ -121,9 +254,27 @@ impl EventLoop {
                 self.local_ctx
                     .timers(|timer| timer.fetch(timer.clock.now()))
             };
-            expired_timers.notify_all();
+            let _fired = expired_timers.notify_all();
+            #[cfg(feature = "metrics")]
+            if _fired > 0 {
+                self.local_ctx.measure(|m, id| m.timers_fired(id, _fired));
+            }
+            #[cfg(feature = "tracing")]
+            if _fired > 0 {
+                tracing::trace!(
+                    target: "nio::timer",
+                    worker = self.local_ctx.worker_id.get(),
+                    fired = _fired,
+                    "timers fired"
+                );
+            }
 
-            let mut local_queue_is_empty = unsafe { self.local_ctx.local_queue(|q| q.is_empty()) };
+            let local_queue_len = unsafe { self.local_ctx.local_queue(|q| q.len()) };
+            let mut local_queue_is_empty = local_queue_len == 0;
+
+            #[cfg(feature = "metrics")]
+            self.local_ctx
+                .measure(|m, id| m.local_queue_depth(id, local_queue_len));
 
             let counter = if local_queue_is_empty {
                 // Accept notification from other threads.
@@ -149,20 +300,68 @@ impl EventLoop {
                 local_queue_is_empty = false
             }
 
-            let timeout = unsafe {
+            let (timeout, auto_advance) = unsafe {
                 self.local_ctx.timers(|timer| {
                     if local_queue_is_empty {
+                        let next_timeout = timer.next_timeout(timer.clock.current());
+                        if timer.clock.is_paused() && next_timeout.is_some() {
+                            if self.local_ctx.runtime_ctx.threadpool.pending_count() > 0 {
+                                // A blocking task (e.g. of `fs`) may wake a task when it completes,
+                                // which takes real time; Check again after a tick.
+                                return (Some(self.local_ctx.runtime_ctx.timer_resolution), None);
+                            }
+                            // Don't wait for a frozen clock; If there are no I/O events,
+                            // advance it to the next timer instead.
+                            return (Some(Duration::ZERO), next_timeout);
+                        }
                         // No immediate work; Sleep until the next timer fires,
                         // or until woken by an I/O event or another thread send more task.
-                        return timer.next_timeout(timer.clock.current());
+                        return (next_timeout, None);
                     }
                     // Do not sleep; We have more work to do.
-                    Some(Duration::ZERO)
+                    (Some(Duration::ZERO), None)
                 })
             };
 
+            // Anything but a zero timeout may block the thread.
+            #[cfg(feature = "metrics")]
+            let parked_at = (timeout != Some(Duration::ZERO)).then(|| {
+                let now = Instant::now();
+                self.local_ctx
+                    .measure(|m, id| m.worker_parked(id, now - unparked_at));
+                now
+            });
+
+            #[cfg(feature = "tracing")]
+            if timeout != Some(Duration::ZERO) {
+                tracing::trace!(
+                    target: "nio::worker",
+                    worker = self.local_ctx.worker_id.get(),
+                    ?timeout,
+                    "park"
+                );
+            }
+
             // `driver.poll` method clear wake up notifications.
-            let events = match self.driver.poll(timeout) {
+            let events = self.driver.poll(timeout);
+
+            #[cfg(feature = "tracing")]
+            if timeout != Some(Duration::ZERO) {
+                tracing::trace!(
+                    target: "nio::worker",
+                    worker = self.local_ctx.worker_id.get(),
+                    "unpark"
+                );
+            }
+
+            #[cfg(feature = "metrics")]
+            if let Some(parked_at) = parked_at {
+                unparked_at = Instant::now();
+                self.local_ctx
+                    .measure(|m, id| m.worker_unparked(id, unparked_at - parked_at));
+            }
+
+            let events = match events {
                 Ok(events) => events,
                 Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
                 #[cfg(target_os = "wasi")]
@@ -174,19 +373,56 @@ impl EventLoop {
                 Err(e) => panic!("unexpected error when polling the I/O driver: {e:?}"),
             };
 
+            if let Some(duration) = auto_advance
+                && events.is_empty()
+            {
+                unsafe { self.local_ctx.timers(|timer| timer.clock.advance(duration)) };
+            }
+
+            #[cfg(feature = "metrics")]
+            let mut io_events = 0;
+
             for event in events {
                 if Driver::has_woken(event) {
                     continue;
                 }
+                #[cfg(feature = "metrics")]
+                {
+                    io_events += 1;
+                }
+                #[cfg(all(feature = "io-uring", target_os = "linux"))]
+                if event.token() == driver::uring::URING_TOKEN {
+                    if let Some(uring) = &self.local_ctx.uring {
+                        #[cfg(feature = "tracing")]
+                        crate::rt::trace::io_dispatch(
+                            event.token().0,
+                            "io_uring",
+                            self.local_ctx.worker_id,
+                            || uring.complete(),
+                        );
+                        #[cfg(not(feature = "tracing"))]
+                        uring.complete();
+                    }
+                    continue;
+                }
                 let ptr = driver::IoWaker::from(event.token().0);
                 unsafe { (*ptr).notify(event) };
             }
+
+            #[cfg(feature = "metrics")]
+            if io_events > 0 {
+                self.local_ctx.measure(|m, id| m.io_events(id, io_events));
+            }
         }
     }
 }
 
 impl Drop for EventLoop {
     fn drop(&mut self) {
+        // Local tasks must be dropped while the context of their worker is set.
+        while let Some(task) = unsafe { self.local_ctx.local_queue(|q| q.pop_front()) } {
+            drop(task);
+        }
         NioContext::drop_local_context();
     }
 }
@@ -0,0 +1,41 @@
+//! Scheduling events of each task, see [`RuntimeBuilder::instrument`].
+//!
+//! [`RuntimeBuilder::instrument`]: crate::RuntimeBuilder::instrument
+
+use super::worker::WorkerId;
+use crate::TaskId;
+use nio_metrics::SpawnKind;
+
+/// Hooks called by the runtime for every task, only with the `metrics` feature.
+///
+/// Unlike [`Measurement`](super::metrics::Measurement), which aggregates per
+/// worker, these hooks identify the task, to build profilers or to study a
+/// scheduling policy without forking the runtime.
+///
+/// Hooks are called inline on the worker threads, implementations should be
+/// cheap and must not block. Blocking tasks are not reported.
+pub trait Instrument: Send + Sync {
+    /// `task` is spawned and will run on `worker`.
+    fn on_task_spawn(&self, _task: TaskId, _worker: WorkerId, _kind: SpawnKind) {}
+
+    /// `worker` starts polling `task`.
+    fn on_task_poll_start(&self, _task: TaskId, _worker: WorkerId) {}
+
+    /// `worker` returned from the poll of `task`.
+    fn on_task_poll_end(&self, _task: TaskId, _worker: WorkerId) {}
+
+    /// `task` is woken from worker `from`, or from a thread outside the runtime,
+    /// and is sent to worker `to`.
+    ///
+    /// A task woken while it is being polled is reported after the poll, as a
+    /// wake from the worker polling it.
+    fn on_task_wake(&self, _task: TaskId, _from: Option<WorkerId>, _to: WorkerId) {}
+
+    /// `task` completed or was aborted, on `worker`.
+    fn on_task_terminate(&self, _task: TaskId, _worker: WorkerId) {}
+}
+
+/// Ignores every event, the default.
+pub struct NoInstrument;
+
+impl Instrument for NoInstrument {}
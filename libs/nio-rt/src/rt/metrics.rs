@@ -1,10 +1,15 @@
-use std::sync::Arc;
+use std::{io, sync::Arc, time::Duration};
 
 use crate::RuntimeContext;
+use crate::net::{TcpListener, TcpStream};
 use crate::rt::task_queue::TaskQueue;
 
+pub use crate::rt::instrument::{Instrument, NoInstrument};
 pub use crate::rt::task_queue::Counter;
-pub use nio_metrics::Measurement;
+pub use nio_metrics::{
+    Bucket, Histogram, HistogramConfig, Measurement, SimpleMeasurement, SpawnKind, WorkerMetrics,
+    export,
+};
 
 pub struct RuntimeMetrics {
     pub(crate) ctx: Arc<RuntimeContext>,
@@ -29,6 +34,76 @@ impl RuntimeMetrics {
         &NoMeasurement
     }
 
+    /// How long each poll of a task took on `worker`, if the measurement records it.
+    ///
+    /// See [`SimpleMeasurement::histograms`].
+    pub fn poll_time_histogram(&self, worker: usize) -> Option<&Histogram> {
+        #[cfg(not(feature = "metrics"))]
+        {
+            let _ = worker;
+            None
+        }
+        #[cfg(feature = "metrics")]
+        self.ctx.measurement.poll_time_histogram(worker)
+    }
+
+    /// How long tasks waited in the queues of `worker` before being polled, if the
+    /// measurement records it.
+    pub fn scheduling_delay_histogram(&self, worker: usize) -> Option<&Histogram> {
+        #[cfg(not(feature = "metrics"))]
+        {
+            let _ = worker;
+            None
+        }
+        #[cfg(feature = "metrics")]
+        self.ctx.measurement.scheduling_delay_histogram(worker)
+    }
+
+    /// Maximum number of threads of the blocking thread pool.
+    pub fn blocking_max_threads(&self) -> usize {
+        self.ctx.threadpool.get_max_threads_limit().into()
+    }
+
+    /// Live threads of the blocking thread pool, idle or busy.
+    pub fn blocking_threads(&self) -> usize {
+        self.ctx.threadpool.thread_count()
+    }
+
+    /// Blocking threads waiting for a task.
+    pub fn blocking_idle_threads(&self) -> usize {
+        self.ctx.threadpool.idle_thread_count()
+    }
+
+    /// Blocking threads running a task.
+    pub fn blocking_busy_threads(&self) -> usize {
+        self.ctx.threadpool.busy_thread_count()
+    }
+
+    /// Blocking tasks waiting for a thread.
+    pub fn blocking_queue_depth(&self) -> usize {
+        self.ctx.threadpool.queue_depth()
+    }
+
+    /// Blocking tasks run to completion.
+    pub fn blocking_tasks_executed(&self) -> u64 {
+        self.ctx.threadpool.executed_count()
+    }
+
+    /// Blocking threads spawned since the runtime was built.
+    pub fn blocking_threads_spawned(&self) -> u64 {
+        self.ctx.threadpool.spawned_thread_count()
+    }
+
+    /// Blocking threads that exited after being idle for longer than the keep-alive timeout.
+    pub fn blocking_threads_exited(&self) -> u64 {
+        self.ctx.threadpool.exited_thread_count()
+    }
+
+    /// The longest time a blocking task waited for a thread.
+    pub fn blocking_max_queue_latency(&self) -> Duration {
+        self.ctx.threadpool.max_queue_latency()
+    }
+
     pub fn measurement_as<T: 'static>(&self) -> Option<&T> {
         #[cfg(not(feature = "metrics"))]
         return None;
@@ -40,6 +115,93 @@ impl RuntimeMetrics {
     }
 }
 
+impl export::Source for RuntimeMetrics {
+    fn num_workers(&self) -> usize {
+        RuntimeMetrics::num_workers(self)
+    }
+
+    fn queue_depth(&self, worker: usize) -> (u64, u64) {
+        let counter = self.ctx.workers.task_queues[worker].load();
+        (counter.local(), counter.shared())
+    }
+
+    fn measurement(&self) -> &dyn Measurement {
+        RuntimeMetrics::measurement(self)
+    }
+
+    fn blocking_pool(&self) -> Option<export::BlockingPool> {
+        Some(export::BlockingPool {
+            max_threads: self.blocking_max_threads() as u64,
+            idle_threads: self.blocking_idle_threads() as u64,
+            busy_threads: self.blocking_busy_threads() as u64,
+            queue_depth: self.blocking_queue_depth() as u64,
+            tasks_executed: self.blocking_tasks_executed(),
+            threads_spawned: self.blocking_threads_spawned(),
+            threads_exited: self.blocking_threads_exited(),
+            max_queue_latency: self.blocking_max_queue_latency(),
+        })
+    }
+}
+
+/// Serves the metrics of the current runtime on `GET /metrics`, see [`export::prometheus`].
+///
+/// Each connection is handled by a local task, and closed after one response,
+/// or if the response isn't sent within 5 seconds. Errors of a connection only
+/// close it; returns when `listener` fails to accept a connection.
+pub async fn serve(mut listener: TcpListener) -> io::Result<()> {
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    loop {
+        let conn = listener.accept().await?;
+        crate::spawn_local(async move {
+            if let Ok(mut stream) = conn.connect().await {
+                let _ = crate::timeout(RESPONSE_TIMEOUT, respond(&mut stream)).await;
+            }
+        });
+    }
+}
+
+async fn respond(stream: &mut TcpStream) -> io::Result<()> {
+    const MAX_REQUEST_LEN: usize = 8 * 1024;
+
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..n]);
+        if request.len() > MAX_REQUEST_LEN {
+            break;
+        }
+    }
+
+    let target = request.split(|&b| b == b' ').nth(1).unwrap_or_default();
+    let (status, content_type, body) = if request.starts_with(b"GET ")
+        && (target == b"/metrics" || target.starts_with(b"/metrics?"))
+    {
+        let body = export::prometheus(&RuntimeContext::current().metrics());
+        ("200 OK", export::CONTENT_TYPE, body)
+    } else {
+        let body = "not found\n".to_owned();
+        ("404 Not Found", "text/plain; charset=utf-8", body)
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let mut response = response.as_bytes();
+    while !response.is_empty() {
+        match stream.write(response).await? {
+            0 => return Err(io::ErrorKind::WriteZero.into()),
+            n => response = &response[n..],
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub(crate) struct NoMeasurement;
 impl Measurement for NoMeasurement {}
@@ -1,8 +1,15 @@
+mod config;
 pub mod context;
+#[cfg(feature = "task-dump")]
+pub mod dump;
 mod event_loop;
+mod instrument;
 pub mod metrics;
 pub mod task;
 mod task_queue;
+#[cfg(feature = "tracing")]
+pub(crate) mod trace;
+pub mod watchdog;
 mod worker;
 
 use crate::{LocalContext, RuntimeBuilder, driver, rt::event_loop::EventLoop};
@@ -11,37 +18,54 @@ use std::{io, rc::Rc, sync::Arc, thread};
 use nio_threadpool::ThreadPool;
 
 use context::RuntimeContext;
+use watchdog::Watchdog;
 use worker::Workers;
 
+pub use config::{ConfigError, RuntimeConfig};
 pub use worker::WorkerId;
 
 const LOCAL_QUEUE_CAP: usize = 512;
 
 impl RuntimeBuilder {
     pub fn rt(mut self) -> io::Result<Runtime> {
+        self.validate()?;
         let min_tasks_per_worker = match self.min_tasks_per_worker {
-            Some(count) => count.get(),
+            Some(count) => count as u64,
             None => (self.worker_threads as u64 / 2).max(1),
         };
 
         let (workers, drivers) = Workers::new(self.worker_threads, min_tasks_per_worker)?;
+        #[cfg(feature = "metrics")]
+        let measurement = {
+            let mut metrics = self.measurement.take().unwrap();
+            metrics.init(self.worker_threads.into());
+            metrics
+        };
         let context = Arc::new(RuntimeContext {
             workers,
             #[cfg(feature = "metrics")]
-            measurement: {
-                let mut metrics = self.measurement.take().unwrap();
-                metrics.init(self.worker_threads.into());
-                metrics
-            },
+            timing: measurement.timing_enabled(),
+            #[cfg(feature = "metrics")]
+            measurement,
+            #[cfg(feature = "metrics")]
+            instrument: self.instrument.take().unwrap(),
             threadpool: ThreadPool::new()
                 .max_threads_limit(self.max_blocking_threads)
                 .load_factor(self.threadpool_load_factor)
                 .stack_size(self.thread_stack_size)
                 .timeout(self.thread_timeout)
                 .name(self.thread_name.take().unwrap()),
+            timer_resolution: self.timer_resolution,
+            #[cfg(feature = "task-dump")]
+            task_registry: Arc::default(),
+            watchdog: self.watchdog.map(|threshold| {
+                Watchdog::new(threshold, self.worker_threads, self.on_stall.take())
+            }),
         });
+        watchdog::start(&context)?;
 
         let tick = self.event_interval;
+        let start_paused = self.start_paused;
 
         for (id, driver) in drivers.into_iter().enumerate() {
             let id = id as u8;
@@ -49,7 +73,12 @@ impl RuntimeBuilder {
 
             self.create_thread(id)
                 .spawn(move || {
-                    EventLoop::new(id, driver, runtime_ctx, tick, LOCAL_QUEUE_CAP).run();
+                    let mut event_loop =
+                        EventLoop::new(id, driver, runtime_ctx, tick, LOCAL_QUEUE_CAP);
+                    if start_paused {
+                        event_loop.pause_clock();
+                    }
+                    event_loop.run();
                 })
                 .unwrap_or_else(|err| panic!("failed to spawn worker thread {id}; {err}"));
         }
@@ -58,34 +87,52 @@ impl RuntimeBuilder {
     }
 
     pub fn build(mut self) -> io::Result<LocalRuntime> {
+        self.validate()?;
         let min_tasks_per_worker = match self.min_tasks_per_worker {
-            Some(count) => count.get(),
+            Some(count) => count as u64,
             None => (self.worker_threads as u64 / 2).max(1),
         };
 
         let (workers, drivers) = Workers::new(self.worker_threads, min_tasks_per_worker)?;
+        #[cfg(feature = "metrics")]
+        let measurement = {
+            let mut metrics = self.measurement.take().unwrap();
+            metrics.init(self.worker_threads.into());
+            metrics
+        };
         let runtime_ctx = Arc::new(RuntimeContext {
             workers,
             #[cfg(feature = "metrics")]
-            measurement: {
-                let mut metrics = self.measurement.take().unwrap();
-                metrics.init(self.worker_threads.into());
-                metrics
-            },
+            timing: measurement.timing_enabled(),
+            #[cfg(feature = "metrics")]
+            measurement,
+            #[cfg(feature = "metrics")]
+            instrument: self.instrument.take().unwrap(),
             threadpool: ThreadPool::new()
                 .max_threads_limit(self.max_blocking_threads)
                 .load_factor(self.threadpool_load_factor)
                 .stack_size(self.thread_stack_size)
                 .timeout(self.thread_timeout)
                 .name(self.thread_name.take().unwrap()),
+            timer_resolution: self.timer_resolution,
+            #[cfg(feature = "task-dump")]
+            task_registry: Arc::default(),
+            watchdog: self.watchdog.map(|threshold| {
+                Watchdog::new(threshold, self.worker_threads, self.on_stall.take())
+            }),
         });
+        watchdog::start(&runtime_ctx)?;
 
         let tick = self.event_interval;
+        let start_paused = self.start_paused;
         let mut drivers = drivers.into_iter().enumerate();
 
         let (id, driver) = drivers.next().unwrap();
         let main_event_loop =
             EventLoop::new(id as u8, driver, runtime_ctx.clone(), tick, LOCAL_QUEUE_CAP);
+        if start_paused {
+            main_event_loop.pause_clock();
+        }
 
         for (id, driver) in drivers {
             let id = id as u8;
@@ -93,7 +140,12 @@ impl RuntimeBuilder {
 
             self.create_thread(id)
                 .spawn(move || {
-                    EventLoop::new(id, driver, runtime_ctx, tick, LOCAL_QUEUE_CAP).run();
+                    let mut event_loop =
+                        EventLoop::new(id, driver, runtime_ctx, tick, LOCAL_QUEUE_CAP);
+                    if start_paused {
+                        event_loop.pause_clock();
+                    }
+                    event_loop.run();
                 })
                 .unwrap_or_else(|err| panic!("failed to spawn worker thread {id}; {err}"));
         }
@@ -118,6 +170,12 @@ impl LocalRuntime {
     pub fn block_on<Fut: Future>(&mut self, fut: Fut) -> Fut::Output {
         self.main_event_loop.run_until(fut)
     }
+
+    /// Runs the tasks of the main worker in a random order, drawn from `rng`.
+    #[cfg(feature = "sim")]
+    pub(crate) fn shuffle_tasks(&mut self, rng: crate::rng::Rng) {
+        self.main_event_loop.shuffle = Some(rng);
+    }
 }
 
 impl std::ops::Deref for LocalRuntime {
@@ -3,10 +3,15 @@ use nio_threadpool::Runnable;
 
 pub struct BlockingTask {
     task: nio_task::BlockingTask,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 impl Runnable for BlockingTask {
     fn run(self) {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.entered();
+
         self.task.run();
     }
 }
@@ -18,6 +23,16 @@ impl BlockingTask {
         R: Send + 'static,
     {
         let (task, join) = nio_task::BlockingTask::new(f);
-        (Self { task }, join)
+        let task = Self {
+            #[cfg(feature = "tracing")]
+            span: crate::rt::trace::task_span(
+                task.id(),
+                std::any::type_name::<F>(),
+                nio_metrics::SpawnKind::Blocking,
+                None,
+            ),
+            task,
+        };
+        (task, join)
     }
 }
@@ -1,4 +1,5 @@
-use nio_task::{JoinHandle, Task};
+use super::{Task, TaskMeta};
+use nio_task::JoinHandle;
 use std::{mem::ManuallyDrop, pin::Pin, sync::Arc, task::Poll};
 
 use crate::rt::{
@@ -11,13 +12,31 @@ pub struct LocalScheduler {
     runtime_ctx: Arc<RuntimeContext>,
 }
 
-impl nio_task::Scheduler for LocalScheduler {
+impl nio_task::Scheduler<TaskMeta> for LocalScheduler {
     fn schedule(&self, task: Task) {
-        NioContext::get(|ctx| match ctx {
-            NioContext::Local(ctx) if self.pinned == ctx.worker_id => {
-                ctx.add_task_to_local_queue(task)
+        #[cfg(feature = "tracing")]
+        crate::rt::trace::woken(&task);
+        #[cfg(feature = "task-dump")]
+        crate::rt::dump::notified(task.metadata());
+
+        NioContext::get(|ctx| {
+            let from = ctx.worker_id();
+            #[cfg(feature = "metrics")]
+            (self.runtime_ctx.instrument).on_task_wake(task.id(), from, self.pinned);
+
+            match ctx {
+                NioContext::Local(ctx) if from == Some(self.pinned) => {
+                    ctx.add_task_to_local_queue(task)
+                }
+                _ => {
+                    #[cfg(feature = "tracing")]
+                    crate::rt::trace::woken_remote(&task, self.pinned);
+                    #[cfg(feature = "metrics")]
+                    (self.runtime_ctx.measurement)
+                        .task_woken_remote(self.pinned.get(), from.map(WorkerId::get));
+                    self.runtime_ctx.send_task_at(self.pinned, task)
+                }
             }
-            _ => self.runtime_ctx.send_task_at(self.pinned, task),
         });
     }
 }
@@ -35,7 +54,7 @@ impl LocalScheduler {
         };
         unsafe {
             Task::new_unchecked(
-                (),
+                TaskMeta::default(),
                 future,
                 LocalScheduler {
                     pinned,
@@ -2,7 +2,31 @@ mod blocking;
 mod local;
 mod multi_thread;
 
-pub use nio_task::{JoinHandle, Task};
+pub use nio_task::JoinHandle;
+
+pub type Task = nio_task::Task<TaskMeta>;
+
+/// Data stored alongside every task, zero sized without the `metrics`, `tracing` and
+/// `task-dump` features.
+#[derive(Default)]
+pub struct TaskMeta {
+    /// When the task was last put in a queue, if timing is enabled.
+    #[cfg(feature = "metrics")]
+    pub(crate) scheduled_at: std::cell::Cell<Option<std::time::Instant>>,
+    /// The `runtime.spawn` span, set once the task is spawned.
+    #[cfg(feature = "tracing")]
+    pub(crate) span: std::cell::OnceCell<tracing::Span>,
+    /// Registration in the task dump, set once the task is spawned.
+    #[cfg(feature = "task-dump")]
+    pub(crate) dump: std::cell::OnceCell<super::dump::Handle>,
+}
+
+#[cfg(feature = "tracing")]
+impl TaskMeta {
+    pub(crate) fn span(&self) -> Option<&tracing::Span> {
+        self.span.get()
+    }
+}
 
 pub use blocking::BlockingTask;
 pub use local::LocalScheduler;
@@ -1,4 +1,5 @@
-use nio_task::{JoinHandle, Task};
+use super::{Task, TaskMeta};
+use nio_task::JoinHandle;
 use std::sync::Arc;
 
 use crate::rt::context::RuntimeContext;
@@ -7,9 +8,21 @@ pub struct Scheduler {
     runtime_ctx: Arc<RuntimeContext>,
 }
 
-impl nio_task::Scheduler for Scheduler {
+impl nio_task::Scheduler<TaskMeta> for Scheduler {
     fn schedule(&self, task: Task) {
-        self.runtime_ctx.send_task_to_least_loaded_worker(task);
+        #[cfg(feature = "tracing")]
+        crate::rt::trace::woken(&task);
+        #[cfg(feature = "task-dump")]
+        crate::rt::dump::notified(task.metadata());
+
+        let id = self.runtime_ctx.workers.least_loaded_worker();
+        #[cfg(feature = "metrics")]
+        (self.runtime_ctx.instrument).on_task_wake(
+            task.id(),
+            crate::rt::context::NioContext::get(|ctx| ctx.worker_id()),
+            id,
+        );
+        self.runtime_ctx.send_task_at(id, task);
     }
 }
 
@@ -19,6 +32,6 @@ impl Scheduler {
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        unsafe { Task::new_unchecked((), future, Scheduler { runtime_ctx: ctx }) }
+        unsafe { Task::new_unchecked(TaskMeta::default(), future, Scheduler { runtime_ctx: ctx }) }
     }
 }
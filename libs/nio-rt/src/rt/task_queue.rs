@@ -1,8 +1,6 @@
+use crate::loom::atomic::{AtomicU64, Ordering};
 use crossbeam_utils::CachePadded;
-use std::{
-    fmt,
-    sync::atomic::{AtomicU64, Ordering},
-};
+use std::fmt;
 
 /// ## Bit layout
 ///
@@ -149,7 +147,7 @@ impl fmt::Debug for Counter {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(nio_loom)))]
 mod tests {
     use super::*;
 
@@ -248,3 +246,82 @@ mod tests {
         assert_eq!(curr.is_notified(), true);
     }
 }
+
+/// Run with `RUSTFLAGS="--cfg nio_loom" cargo test -p nio --lib loom --release`
+#[cfg(all(test, nio_loom))]
+mod loom_tests {
+    use super::*;
+    use loom::{
+        sync::{Arc, Notify},
+        thread,
+    };
+
+    /// Workers sleep in `Driver::poll` until notified, like `Notify::wait`.
+    /// Loom reports a lost wakeup as a deadlock of the worker.
+    fn send_task(queue: &TaskQueue, notifier: &Notify) {
+        if !queue.increase_shared_and_mark_as_notified().is_notified() {
+            notifier.notify();
+        }
+    }
+
+    /// The loop of `EventLoop::run_with`, until it has received `count` tasks.
+    fn run_worker(queue: &TaskQueue, notifier: &Notify, count: u64) {
+        let mut received = 0;
+        loop {
+            let (_, counter) = queue.accept_notify_once_if_shared_queue_is_empty();
+            if counter.shared_queue_has_data() {
+                queue.move_shared_to_local(counter);
+                received += counter.shared();
+                // Run the tasks.
+                for _ in 0..counter.shared() {
+                    let _ = queue.decrease_local();
+                }
+                continue;
+            }
+            if received == count {
+                return;
+            }
+            notifier.wait();
+        }
+    }
+
+    #[test]
+    fn loom_no_lost_wakeup() {
+        loom::model(|| {
+            let queue = Arc::new(TaskQueue::new());
+            let notifier = Arc::new(Notify::new());
+
+            let senders: Vec<_> = (0..2)
+                .map(|_| {
+                    let queue = queue.clone();
+                    let notifier = notifier.clone();
+                    thread::spawn(move || send_task(&queue, &notifier))
+                })
+                .collect();
+
+            run_worker(&queue, &notifier, 2);
+            for sender in senders {
+                sender.join().unwrap();
+            }
+            assert_eq!(queue.load().total(), 0);
+        });
+    }
+
+    #[test]
+    fn loom_sender_while_worker_busy() {
+        loom::model(|| {
+            let queue = Arc::new(TaskQueue::new());
+            let notifier = Arc::new(Notify::new());
+            // A task sent earlier, the worker hasn't accepted its notification yet.
+            send_task(&queue, &notifier);
+
+            let sender = {
+                let queue = queue.clone();
+                let notifier = notifier.clone();
+                thread::spawn(move || send_task(&queue, &notifier))
+            };
+            run_worker(&queue, &notifier, 2);
+            sender.join().unwrap();
+        });
+    }
+}
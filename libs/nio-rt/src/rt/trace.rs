@@ -0,0 +1,106 @@
+//! Spans and events emitted with the `tracing` feature.
+//!
+//! Follows the conventions of tokio-console: every task has a `runtime.spawn`
+//! span, entered while the task is polled, and wakeups are `waker.wake` events.
+//!
+//! | target             | what                                          |
+//! |--------------------|-----------------------------------------------|
+//! | `nio::task`        | task spans; spawn, poll and completion        |
+//! | `nio::task::waker` | wakeups                                       |
+//! | `nio::worker`      | park and unpark of workers                    |
+//! | `nio::io`          | registration of I/O resources, driver errors, |
+//! |                    | readiness that wakes a task of another worker |
+//! | `nio::timer`       | timers fired                                  |
+
+use super::{
+    task::{Task, TaskMeta},
+    worker::WorkerId,
+};
+use nio_metrics::SpawnKind;
+use nio_task::{Status, TaskId};
+use std::cell::Cell;
+use tracing::Span;
+
+thread_local! {
+    /// The I/O resource whose tasks are being woken: token, type and worker.
+    static DISPATCHING: Cell<Option<(usize, &'static str, WorkerId)>> = const { Cell::new(None) };
+}
+
+pub(crate) fn task_span(
+    id: TaskId,
+    name: &'static str,
+    kind: SpawnKind,
+    worker: Option<WorkerId>,
+) -> Span {
+    let span = tracing::trace_span!(
+        target: "nio::task",
+        parent: None,
+        "runtime.spawn",
+        task.id = id.get().get(),
+        task.name = name,
+        kind = kind.as_str(),
+        worker = worker.map(WorkerId::get),
+    );
+    tracing::trace!(target: "nio::task", parent: &span, "spawn");
+    span
+}
+
+/// Attaches a `runtime.spawn` span to a newly spawned `task`.
+pub(crate) fn spawned(task: &Task, name: &'static str, kind: SpawnKind, worker: WorkerId) {
+    let span = task_span(task.id(), name, kind, Some(worker));
+    let _ = task.metadata().span.set(span);
+}
+
+/// `task` was woken, and is about to be scheduled.
+pub(crate) fn woken(task: &Task) {
+    tracing::trace!(
+        target: "nio::task::waker",
+        parent: task.metadata().span().and_then(Span::id),
+        op = "waker.wake",
+        task.id = task.id().get().get(),
+    );
+}
+
+pub(crate) fn polled(status: &Status<TaskMeta>) {
+    match status {
+        Status::Yielded(_) => tracing::trace!(target: "nio::task", "yield"),
+        Status::Pending => tracing::trace!(target: "nio::task", "pending"),
+        Status::Complete(meta) if meta.is_cancelled() => {
+            tracing::trace!(target: "nio::task", "abort")
+        }
+        Status::Complete(_) => tracing::trace!(target: "nio::task", "complete"),
+    }
+}
+
+/// Calls `wake`, which wakes the tasks waiting for the I/O resource `token`,
+/// registered with `worker`.
+pub(crate) fn io_dispatch(
+    token: usize,
+    resource: &'static str,
+    worker: WorkerId,
+    wake: impl FnOnce(),
+) {
+    let prev = DISPATCHING.replace(Some((token, resource, worker)));
+    wake();
+    DISPATCHING.set(prev);
+}
+
+/// `task`, pinned to `worker`, is woken from another thread. Reports the I/O
+/// resource that woke it, if its readiness fired on a different worker.
+pub(crate) fn woken_remote(task: &Task, worker: WorkerId) {
+    let Some((token, resource, io_worker)) = DISPATCHING.get() else {
+        return;
+    };
+    if io_worker != worker {
+        tracing::debug!(
+            target: "nio::io",
+            parent: task.metadata().span().and_then(Span::id),
+            token,
+            resource,
+            worker = io_worker.get(),
+            task.id = task.id().get().get(),
+            task.worker = worker.get(),
+            "readiness woke a task of another worker"
+        );
+    }
+}
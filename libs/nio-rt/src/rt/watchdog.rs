@@ -0,0 +1,170 @@
+//! Detects workers stuck in a single poll, see [`RuntimeBuilder::watchdog`].
+//!
+//! A blocking call or a long computation inside a task freezes its worker,
+//! and every task and socket of that worker. Each worker publishes a heartbeat
+//! around every poll, and a watchdog thread reports the workers whose heartbeat
+//! stopped in the middle of a poll.
+//!
+//! The stack of the stuck worker is not captured: `std` can only capture the
+//! backtrace of the current thread. [`Stall::thread`] identifies the worker
+//! thread for a debugger or a profiler.
+//!
+//! [`RuntimeBuilder::watchdog`]: crate::RuntimeBuilder::watchdog
+
+use super::{context::RuntimeContext, worker::WorkerId};
+use crate::TaskId;
+use std::{
+    io,
+    num::NonZero,
+    sync::{
+        Arc, OnceLock, Weak,
+        atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+    },
+    thread::{self, Thread},
+    time::{Duration, Instant},
+};
+
+/// A worker stuck in a single poll, reported once per poll.
+#[derive(Debug, Clone)]
+pub struct Stall {
+    pub worker: WorkerId,
+    /// The task being polled.
+    pub task: TaskId,
+    /// How long the poll has been running, at least the threshold.
+    pub duration: Duration,
+    /// The thread of the worker.
+    pub thread: Option<Thread>,
+}
+
+pub(crate) type OnStall = Box<dyn Fn(&Stall) + Send + Sync>;
+
+pub(crate) struct Watchdog {
+    threshold: Duration,
+    heartbeats: Box<[Heartbeat]>,
+    on_stall: OnStall,
+}
+
+#[derive(Default)]
+pub(crate) struct Heartbeat {
+    /// Completed polls.
+    polls: AtomicU64,
+    /// Id of the task being polled, `0` between polls.
+    task: AtomicUsize,
+    thread: OnceLock<Thread>,
+}
+
+impl Heartbeat {
+    #[inline]
+    pub(crate) fn enter(&self, task: TaskId) {
+        self.task.store(task.get().get(), Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn exit(&self) {
+        self.task.store(0, Relaxed);
+        self.polls.fetch_add(1, Relaxed);
+    }
+}
+
+impl Watchdog {
+    pub(crate) fn new(threshold: Duration, workers: u8, on_stall: Option<OnStall>) -> Watchdog {
+        Watchdog {
+            threshold,
+            heartbeats: (0..workers).map(|_| Heartbeat::default()).collect(),
+            on_stall: on_stall.unwrap_or_else(|| Box::new(log)),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn heartbeat(&self, worker: WorkerId) -> &Heartbeat {
+        &self.heartbeats[worker.get()]
+    }
+
+    /// Called by each worker, on its own thread.
+    pub(crate) fn register_thread(&self, worker: WorkerId) {
+        let _ = self.heartbeat(worker).thread.set(thread::current());
+    }
+}
+
+fn log(stall: &Stall) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        target: "nio::worker",
+        worker = stall.worker.get(),
+        task.id = stall.task.get().get(),
+        "worker stuck in a poll for {:?}, a task is blocking the thread",
+        stall.duration
+    );
+    #[cfg(not(feature = "tracing"))]
+    eprintln!(
+        "nio: worker {} stuck in a poll of task {} for {:?}, a task is blocking the thread",
+        stall.worker.get(),
+        stall.task,
+        stall.duration
+    );
+}
+
+/// Last heartbeat of a worker, as seen by the watchdog.
+#[derive(Default)]
+struct Seen {
+    polls: u64,
+    task: usize,
+    since: Option<Instant>,
+    reported: bool,
+}
+
+/// Starts the watchdog thread of `ctx`, which exits once the runtime is dropped.
+pub(crate) fn start(ctx: &Arc<RuntimeContext>) -> io::Result<()> {
+    let Some(watchdog) = &ctx.watchdog else {
+        return Ok(());
+    };
+    let interval = (watchdog.threshold / 4).max(Duration::from_millis(1));
+    let mut seen: Vec<Seen> = watchdog
+        .heartbeats
+        .iter()
+        .map(|_| Seen::default())
+        .collect();
+    let ctx = Arc::downgrade(ctx);
+
+    thread::Builder::new()
+        .name("nio-watchdog".into())
+        .spawn(move || {
+            while let Some(ctx) = Weak::upgrade(&ctx) {
+                if let Some(watchdog) = &ctx.watchdog {
+                    check(watchdog, &mut seen);
+                }
+                drop(ctx);
+                thread::sleep(interval);
+            }
+        })?;
+    Ok(())
+}
+
+fn check(watchdog: &Watchdog, seen: &mut [Seen]) {
+    let now = Instant::now();
+    for (id, (heartbeat, seen)) in watchdog.heartbeats.iter().zip(seen).enumerate() {
+        let task = heartbeat.task.load(Relaxed);
+        let polls = heartbeat.polls.load(Relaxed);
+
+        if task == 0 || task != seen.task || polls != seen.polls {
+            *seen = Seen {
+                polls,
+                task,
+                since: (task != 0).then_some(now),
+                reported: false,
+            };
+            continue;
+        }
+        let Some(since) = seen.since else { continue };
+        let duration = now - since;
+        if duration >= watchdog.threshold && !seen.reported {
+            seen.reported = true;
+            (watchdog.on_stall)(&Stall {
+                worker: WorkerId::new(id as u8),
+                task: TaskId::from_raw(NonZero::new(task).unwrap()),
+                duration,
+                thread: heartbeat.thread.get().cloned(),
+            });
+        }
+    }
+}
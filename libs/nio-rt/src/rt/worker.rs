@@ -21,6 +21,10 @@ impl WorkerId {
     pub fn get(self) -> usize {
         self.0 as usize
     }
+
+    pub(crate) fn new(id: u8) -> WorkerId {
+        WorkerId(id)
+    }
 }
 
 pub struct Workers {
@@ -0,0 +1,333 @@
+//! Deterministic simulation of a network of hosts, for reproducing races.
+//!
+//! A [`Sim`] runs every task on a single worker thread, picking the next
+//! ready task with a seeded random number generator rather than in FIFO
+//! order. Its clock is frozen and jumps to the next timer whenever the worker
+//! is idle (see [`time::pause`]), so hours of virtual time pass in
+//! milliseconds.
+//!
+//! Hosts communicate through the in-memory network of [`sim::net`], whose
+//! [`TcpStream`], [`TcpListener`] and [`UdpSocket`] have the same API as the
+//! types of [`nio::net`]. Latency and packet loss are drawn from the same
+//! seed, and links between hosts can be cut with [`Handle::partition`].
+//!
+//! Given the same seed, a simulation makes the same scheduling decisions and
+//! delivers the same packets at the same virtual times, so a failing seed
+//! replays exactly. When a simulation panics, its seed is printed. Run it
+//! again with `NIO_SIM_SEED=<seed>` to replay it.
+//!
+//! Determinism requires the code under test to only use the simulated
+//! network and the worker clock: [`std::time::Instant::now`], real sockets,
+//! [`spawn_blocking`](crate::spawn_blocking) and the file system are not
+//! simulated.
+//!
+//! ```
+//! use nio::sim::{self, net::{TcpListener, TcpStream}};
+//! use std::{net::Ipv4Addr, time::Duration};
+//!
+//! let mut sim = sim::Builder::new()
+//!     .seed(7)
+//!     .latency(Duration::from_millis(5), Duration::from_millis(50))
+//!     .build()
+//!     .unwrap();
+//!
+//! let handle = sim.handle();
+//! sim.block_on(async move {
+//!     let server = handle.host(Ipv4Addr::new(10, 0, 0, 1), async {
+//!         let mut listener = TcpListener::bind("10.0.0.1:80").await.unwrap();
+//!         let mut stream = listener.accept().await.unwrap().connect().await.unwrap();
+//!         stream.write(b"hello").await.unwrap();
+//!     });
+//!     let client = handle.host(Ipv4Addr::new(10, 0, 0, 2), async {
+//!         let mut stream = TcpStream::connect("10.0.0.1:80").await.unwrap();
+//!         let mut buf = [0; 5];
+//!         stream.read(&mut buf).await.unwrap();
+//!         assert_eq!(&buf, b"hello");
+//!     });
+//!     server.await.unwrap();
+//!     client.await.unwrap();
+//! });
+//! ```
+//!
+//! Requires the `sim` feature.
+//!
+//! [`sim::net`]: net
+//! [`TcpStream`]: net::TcpStream
+//! [`TcpListener`]: net::TcpListener
+//! [`UdpSocket`]: net::UdpSocket
+//! [`nio::net`]: crate::net
+//! [`time::pause`]: crate::time::pause
+
+pub mod net;
+
+use crate::{JoinHandle, LocalRuntime, RuntimeBuilder, rng::Rng};
+use net::Network;
+use std::{
+    cell::Cell,
+    collections::hash_map::RandomState,
+    env,
+    hash::BuildHasher,
+    io,
+    net::{IpAddr, Ipv4Addr},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
+
+/// Configures a [`Sim`].
+#[derive(Debug, Clone)]
+pub struct Builder {
+    seed: Option<u64>,
+    latency: (Duration, Duration),
+    packet_loss: f64,
+    connect_timeout: Duration,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder {
+            seed: None,
+            latency: (Duration::from_millis(1), Duration::from_millis(1)),
+            packet_loss: 0.0,
+            connect_timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the seed of every random decision of the simulation.
+    ///
+    /// Defaults to the `NIO_SIM_SEED` environment variable, or to a random
+    /// seed if it's not set.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Every packet takes between `min` and `max` to arrive (inclusive).
+    ///
+    /// TCP segments never overtake each other, UDP datagrams may.
+    ///
+    /// Default: `1ms`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn latency(mut self, min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "`min` latency is greater than `max`");
+        self.latency = (min, max);
+        self
+    }
+
+    /// Probability of dropping a UDP datagram. TCP is reliable, so its
+    /// segments are never dropped.
+    ///
+    /// Default: `0.0`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` is not between `0.0` and `1.0`.
+    pub fn packet_loss(mut self, probability: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&probability),
+            "`probability` must be between 0.0 and 1.0"
+        );
+        self.packet_loss = probability;
+        self
+    }
+
+    /// How long `TcpStream::connect` waits before failing with
+    /// [`io::ErrorKind::TimedOut`] when the hosts are partitioned.
+    ///
+    /// Default: `1s`
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    pub fn build(self) -> io::Result<Sim> {
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => seed_from_env()?.unwrap_or_else(|| RandomState::new().hash_one(0)),
+        };
+        let mut rt = RuntimeBuilder::new()
+            .worker_threads(1)
+            .start_paused(true)
+            .build()?;
+
+        rt.shuffle_tasks(Rng::new(seed));
+        // The branch order of `select!` and `join!` as well.
+        rt.rng.set_seed(seed ^ 0x6e69_6f5f_7365_6c00);
+        let network = Network::new(
+            // Keep the scheduler and the network independent.
+            Rng::new(seed ^ 0x6e69_6f5f_7369_6d00),
+            self.latency,
+            self.packet_loss,
+            self.connect_timeout,
+        );
+        Ok(Sim {
+            rt,
+            network: Rc::new(network),
+            seed,
+        })
+    }
+}
+
+fn seed_from_env() -> io::Result<Option<u64>> {
+    match env::var("NIO_SIM_SEED") {
+        Ok(seed) => seed.trim().parse().map(Some).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid `NIO_SIM_SEED`: {seed:?}"),
+            )
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+/// A simulated network of hosts, see the [module documentation](self).
+pub struct Sim {
+    rt: LocalRuntime,
+    network: Rc<Network>,
+    seed: u64,
+}
+
+impl Sim {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle {
+            network: self.network.clone(),
+        }
+    }
+
+    /// Runs the simulation until `fut` completes.
+    ///
+    /// If it panics, the seed is printed to stderr.
+    pub fn block_on<F: Future>(&mut self, fut: F) -> F::Output {
+        let _enter = net::enter(self.network.clone());
+        let _report = ReportSeed(self.seed);
+        self.rt.block_on(fut)
+    }
+}
+
+impl Drop for Sim {
+    fn drop(&mut self) {
+        // Wakers of the tasks left behind must be dropped by the worker.
+        self.network.clear();
+    }
+}
+
+struct ReportSeed(u64);
+
+impl Drop for ReportSeed {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            eprintln!(
+                "nio::sim: failed with seed {0}, replay with `NIO_SIM_SEED={0}`",
+                self.0
+            );
+        }
+    }
+}
+
+/// Controls the network of a [`Sim`].
+#[derive(Clone)]
+pub struct Handle {
+    network: Rc<Network>,
+}
+
+impl Handle {
+    /// Returns the handle of the running simulation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of [`Sim::block_on`].
+    pub fn current() -> Handle {
+        Handle {
+            network: net::current(),
+        }
+    }
+
+    /// Spawns a task running on the host with address `ip`.
+    ///
+    /// Sockets created by the task are bound to `ip`. Code that runs outside
+    /// of a host uses `127.0.0.1`. Tasks spawned by the task with
+    /// [`spawn_local`](crate::spawn_local) don't belong to the host, use
+    /// [`sim::spawn_local`](spawn_local) instead.
+    pub fn host<F>(&self, ip: impl Into<IpAddr>, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        crate::spawn_local(Host {
+            ip: ip.into(),
+            future,
+        })
+    }
+
+    /// Cuts the link between hosts `a` and `b`.
+    ///
+    /// TCP segments are held until the link is repaired, UDP datagrams are
+    /// dropped, and new connections time out.
+    pub fn partition(&self, a: impl Into<IpAddr>, b: impl Into<IpAddr>) {
+        self.network.partition(a.into(), b.into());
+    }
+
+    /// Repairs the link between hosts `a` and `b`.
+    pub fn repair(&self, a: impl Into<IpAddr>, b: impl Into<IpAddr>) {
+        self.network.repair(Some((a.into(), b.into())));
+    }
+
+    /// Repairs every link.
+    pub fn repair_all(&self) {
+        self.network.repair(None);
+    }
+}
+
+/// Spawns a task on the host of the current task.
+pub fn spawn_local<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    crate::spawn_local(Host {
+        ip: host_ip(),
+        future,
+    })
+}
+
+thread_local! {
+    static HOST: Cell<IpAddr> = const { Cell::new(IpAddr::V4(Ipv4Addr::LOCALHOST)) };
+}
+
+/// Address of the host of the current task.
+pub(crate) fn host_ip() -> IpAddr {
+    HOST.get()
+}
+
+/// Runs `future` as part of a host.
+struct Host<F> {
+    ip: IpAddr,
+    future: F,
+}
+
+impl<F: Future> Future for Host<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let prev = HOST.replace(this.ip);
+        let poll = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+        HOST.set(prev);
+        poll
+    }
+}
@@ -0,0 +1,753 @@
+//! In-memory TCP and UDP for [`Sim`](super::Sim).
+//!
+//! The types mirror the API of [`nio::net`](crate::net), so code under test
+//! can switch between them with a `cfg`:
+//!
+//! ```ignore
+//! #[cfg(not(test))]
+//! use nio::net::{TcpListener, TcpStream};
+//! #[cfg(test)]
+//! use nio::sim::net::{TcpListener, TcpStream};
+//! ```
+//!
+//! Writes never block: there is no flow control, data sent is buffered by
+//! the receiver until it's read.
+
+use super::{Rng, host_ip};
+use crate::timer::sleep::{Sleep, sleep};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+    future::{Future, poll_fn},
+    io::{Error, ErrorKind, IoSlice, Result},
+    net::{IpAddr, Shutdown, SocketAddr, ToSocketAddrs},
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+const EPHEMERAL_PORTS: std::ops::RangeInclusive<u16> = 49152..=65535;
+
+pub(super) struct Network {
+    rng: Rng,
+    latency: (Duration, Duration),
+    packet_loss: f64,
+    connect_timeout: Duration,
+    /// Pairs of hosts that can't reach each other, the smaller address first.
+    partitions: RefCell<BTreeSet<(IpAddr, IpAddr)>>,
+    listeners: RefCell<BTreeMap<SocketAddr, Rc<RefCell<Backlog>>>>,
+    sockets: RefCell<BTreeMap<SocketAddr, Rc<RefCell<Inbox>>>>,
+    /// Streams waiting for a partition to be repaired.
+    stalled: RefCell<Vec<Weak<RefCell<Pipe>>>>,
+    next_port: Cell<u16>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Network>>> = const { RefCell::new(None) };
+}
+
+pub(super) struct Enter(Option<Rc<Network>>);
+
+pub(super) fn enter(network: Rc<Network>) -> Enter {
+    Enter(CURRENT.replace(Some(network)))
+}
+
+impl Drop for Enter {
+    fn drop(&mut self) {
+        CURRENT.set(self.0.take());
+    }
+}
+
+pub(super) fn current() -> Rc<Network> {
+    CURRENT
+        .with_borrow(|network| network.clone())
+        .expect("must be called from a simulation, see `nio::sim::Sim::block_on`")
+}
+
+impl Network {
+    pub(super) fn new(
+        rng: Rng,
+        latency: (Duration, Duration),
+        packet_loss: f64,
+        connect_timeout: Duration,
+    ) -> Network {
+        Network {
+            rng,
+            latency,
+            packet_loss,
+            connect_timeout,
+            partitions: RefCell::default(),
+            listeners: RefCell::default(),
+            sockets: RefCell::default(),
+            stalled: RefCell::default(),
+            next_port: Cell::new(*EPHEMERAL_PORTS.start()),
+        }
+    }
+
+    pub(super) fn partition(&self, a: IpAddr, b: IpAddr) {
+        self.partitions.borrow_mut().insert(link(a, b));
+    }
+
+    /// Repairs the link, or every link if `None`.
+    pub(super) fn repair(&self, hosts: Option<(IpAddr, IpAddr)>) {
+        match hosts {
+            Some((a, b)) => self.partitions.borrow_mut().remove(&link(a, b)),
+            None => {
+                self.partitions.borrow_mut().clear();
+                true
+            }
+        };
+        // Readers check again whether they are still partitioned.
+        for pipe in self.stalled.take() {
+            if let Some(pipe) = pipe.upgrade() {
+                pipe.borrow_mut().wake();
+            }
+        }
+    }
+
+    pub(super) fn clear(&self) {
+        self.listeners.take();
+        self.sockets.take();
+        self.stalled.take();
+    }
+
+    fn is_partitioned(&self, a: IpAddr, b: IpAddr) -> bool {
+        a != b && self.partitions.borrow().contains(&link(a, b))
+    }
+
+    fn latency(&self) -> Duration {
+        self.rng.duration(self.latency)
+    }
+
+    fn ephemeral_port(&self, ip: IpAddr) -> Result<u16> {
+        for _ in EPHEMERAL_PORTS {
+            let port = self.next_port.get();
+            self.next_port.set(match port {
+                u16::MAX => *EPHEMERAL_PORTS.start(),
+                port => port + 1,
+            });
+            let addr = SocketAddr::new(ip, port);
+            if !self.listeners.borrow().contains_key(&addr)
+                && !self.sockets.borrow().contains_key(&addr)
+            {
+                return Ok(port);
+            }
+        }
+        Err(ErrorKind::AddrInUse.into())
+    }
+
+    /// Resolves the address to bind to on the current host.
+    fn local_addr(
+        &self,
+        addr: SocketAddr,
+        in_use: impl Fn(&SocketAddr) -> bool,
+    ) -> Result<SocketAddr> {
+        let host = host_ip();
+        let ip = if addr.ip().is_unspecified() {
+            host
+        } else if addr.ip() == host || addr.ip().is_loopback() {
+            addr.ip()
+        } else {
+            return Err(Error::new(
+                ErrorKind::AddrNotAvailable,
+                format!("{} is not an address of host {host}", addr.ip()),
+            ));
+        };
+        let addr = match addr.port() {
+            0 => SocketAddr::new(ip, self.ephemeral_port(ip)?),
+            _ => SocketAddr::new(ip, addr.port()),
+        };
+        if in_use(&addr) {
+            return Err(ErrorKind::AddrInUse.into());
+        }
+        Ok(addr)
+    }
+}
+
+fn link(a: IpAddr, b: IpAddr) -> (IpAddr, IpAddr) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+fn now() -> Instant {
+    crate::rt::context::LocalContext::with(|ctx| unsafe {
+        ctx.timers(|timers| timers.clock.current())
+    })
+}
+
+fn resolve<A: ToSocketAddrs>(addr: A) -> Result<SocketAddr> {
+    addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "could not resolve to any address"))
+}
+
+/// Waits until `deadline`, reusing the timer in `delay`.
+fn poll_until(delay: &mut Option<Sleep>, deadline: Instant, cx: &mut Context) -> Poll<()> {
+    let delay = delay.get_or_insert_with(|| Sleep::at(deadline));
+    if delay.deadline() != deadline {
+        delay.reset_at(deadline);
+    }
+    Pin::new(delay).poll(cx)
+}
+
+/// One direction of a TCP connection.
+struct Pipe {
+    from: IpAddr,
+    to: IpAddr,
+    /// Segments in flight with their arrival time. An empty segment is FIN.
+    segments: VecDeque<(Instant, Vec<u8>)>,
+    /// Arrival time of the last segment, segments never overtake each other.
+    last: Instant,
+    reader_closed: bool,
+    writer_closed: bool,
+    waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(from: IpAddr, to: IpAddr) -> Rc<RefCell<Pipe>> {
+        Rc::new(RefCell::new(Pipe {
+            from,
+            to,
+            segments: VecDeque::new(),
+            last: now(),
+            reader_closed: false,
+            writer_closed: false,
+            waker: None,
+        }))
+    }
+
+    fn push(&mut self, network: &Network, data: Vec<u8>) {
+        let at = std::cmp::max(now() + network.latency(), self.last);
+        self.last = at;
+        self.segments.push_back((at, data));
+        self.wake();
+    }
+
+    fn close_write(&mut self, network: &Network) {
+        if !self.writer_closed {
+            self.writer_closed = true;
+            self.push(network, Vec::new());
+        }
+    }
+
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A simulated TCP connection, see [`nio::net::TcpStream`].
+///
+/// [`nio::net::TcpStream`]: crate::net::TcpStream
+pub struct TcpStream {
+    network: Rc<Network>,
+    rx: Rc<RefCell<Pipe>>,
+    tx: Rc<RefCell<Pipe>>,
+    local_addr: SocketAddr,
+    peer_addr: SocketAddr,
+    delay: Option<Sleep>,
+}
+
+impl TcpStream {
+    /// Connects to a listener of the simulated network.
+    ///
+    /// The handshake takes a round trip. Fails with
+    /// [`ErrorKind::ConnectionRefused`] if nothing listens at `addr`, and with
+    /// [`ErrorKind::TimedOut`] if the hosts are partitioned.
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> Result<TcpStream> {
+        let network = current();
+        let peer_addr = resolve(addr)?;
+        let ip = host_ip();
+
+        if network.is_partitioned(ip, peer_addr.ip()) {
+            sleep(network.connect_timeout).await;
+            return Err(ErrorKind::TimedOut.into());
+        }
+        sleep(network.latency()).await;
+
+        let backlog = network.listeners.borrow().get(&peer_addr).cloned();
+        let Some(backlog) = backlog else {
+            sleep(network.latency()).await;
+            return Err(ErrorKind::ConnectionRefused.into());
+        };
+        let local_addr = SocketAddr::new(ip, network.ephemeral_port(ip)?);
+
+        let upstream = Pipe::new(ip, peer_addr.ip());
+        let downstream = Pipe::new(peer_addr.ip(), ip);
+        backlog.borrow_mut().push(TcpStream {
+            network: network.clone(),
+            rx: upstream.clone(),
+            tx: downstream.clone(),
+            local_addr: peer_addr,
+            peer_addr: local_addr,
+            delay: None,
+        });
+
+        sleep(network.latency()).await;
+        Ok(TcpStream {
+            network,
+            rx: downstream,
+            tx: upstream,
+            local_addr,
+            peer_addr,
+            delay: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        Ok(self.peer_addr)
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        if matches!(how, Shutdown::Read | Shutdown::Both) {
+            let mut rx = self.rx.borrow_mut();
+            rx.reader_closed = true;
+            rx.segments.clear();
+        }
+        if matches!(how, Shutdown::Write | Shutdown::Both) {
+            self.tx.borrow_mut().close_write(&self.network);
+        }
+        Ok(())
+    }
+
+    pub fn read<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_read(cx, buf))
+    }
+
+    pub fn write<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_write(cx, buf))
+    }
+
+    pub fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let mut rx = self.rx.borrow_mut();
+        if rx.reader_closed {
+            return Poll::Ready(Ok(0));
+        }
+        let Some(&(at, _)) = rx.segments.front() else {
+            rx.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        };
+        if self.network.is_partitioned(rx.from, rx.to) {
+            rx.waker = Some(cx.waker().clone());
+            self.network
+                .stalled
+                .borrow_mut()
+                .push(Rc::downgrade(&self.rx));
+            return Poll::Pending;
+        }
+        if at > now() && poll_until(&mut self.delay, at, cx).is_pending() {
+            return Poll::Pending;
+        }
+        let data = &mut rx.segments.front_mut().unwrap().1;
+        if data.is_empty() {
+            // FIN, every further read returns EOF.
+            return Poll::Ready(Ok(0));
+        }
+        let n = buf.len().min(data.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        data.drain(..n);
+        if data.is_empty() {
+            rx.segments.pop_front();
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    pub fn poll_write(&mut self, _: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        let mut tx = self.tx.borrow_mut();
+        if tx.writer_closed || tx.reader_closed {
+            return Poll::Ready(Err(ErrorKind::BrokenPipe.into()));
+        }
+        if !buf.is_empty() {
+            tx.push(&self.network, buf.to_vec());
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    pub fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        let buf: Vec<u8> = bufs.iter().flat_map(|buf| buf.iter().copied()).collect();
+        self.poll_write(cx, &buf)
+    }
+}
+
+impl Drop for TcpStream {
+    fn drop(&mut self) {
+        let _ = self.shutdown(Shutdown::Both);
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpStream")
+            .field("local_addr", &self.local_addr)
+            .field("peer_addr", &self.peer_addr)
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Backlog {
+    streams: VecDeque<TcpStream>,
+    waker: Option<Waker>,
+}
+
+impl Backlog {
+    fn push(&mut self, stream: TcpStream) {
+        self.streams.push_back(stream);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A simulated TCP listener, see [`nio::net::TcpListener`].
+///
+/// [`nio::net::TcpListener`]: crate::net::TcpListener
+pub struct TcpListener {
+    network: Rc<Network>,
+    backlog: Rc<RefCell<Backlog>>,
+    local_addr: SocketAddr,
+}
+
+impl TcpListener {
+    /// Listens on the current host. The address must be unspecified, the
+    /// address of the host, or a loopback address.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> impl Future<Output = Result<TcpListener>> + use<A> {
+        // The host is the one that polls the future.
+        let addr = resolve(addr);
+        async move {
+            let addr = addr?;
+            let network = current();
+            let local_addr =
+                network.local_addr(addr, |addr| network.listeners.borrow().contains_key(addr))?;
+            let backlog = Rc::default();
+            network
+                .listeners
+                .borrow_mut()
+                .insert(local_addr, Rc::clone(&backlog));
+            Ok(TcpListener {
+                network,
+                backlog,
+                local_addr,
+            })
+        }
+    }
+
+    pub fn accept(&mut self) -> impl Future<Output = Result<TcpConnection>> + '_ {
+        poll_fn(|cx| {
+            let mut backlog = self.backlog.borrow_mut();
+            match backlog.streams.pop_front() {
+                Some(stream) => Poll::Ready(Ok(TcpConnection { stream })),
+                None => {
+                    backlog.waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+}
+
+impl Drop for TcpListener {
+    fn drop(&mut self) {
+        self.network.listeners.borrow_mut().remove(&self.local_addr);
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpListener")
+            .field("local_addr", &self.local_addr)
+            .finish()
+    }
+}
+
+/// An accepted connection, see [`nio::net::TcpConnection`].
+///
+/// [`nio::net::TcpConnection`]: crate::net::TcpConnection
+#[derive(Debug)]
+pub struct TcpConnection {
+    stream: TcpStream,
+}
+
+impl TcpConnection {
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.stream.local_addr()
+    }
+
+    pub fn connect(self) -> impl Future<Output = Result<TcpStream>> {
+        std::future::ready(Ok(self.stream))
+    }
+}
+
+#[derive(Default)]
+struct Inbox {
+    /// Datagrams in flight, ordered by arrival time.
+    datagrams: VecDeque<(Instant, SocketAddr, Vec<u8>)>,
+    waker: Option<Waker>,
+}
+
+/// A simulated UDP socket, see [`nio::net::UdpSocket`].
+///
+/// [`nio::net::UdpSocket`]: crate::net::UdpSocket
+pub struct UdpSocket {
+    network: Rc<Network>,
+    inbox: Rc<RefCell<Inbox>>,
+    local_addr: SocketAddr,
+    peer_addr: Cell<Option<SocketAddr>>,
+    delay: Option<Sleep>,
+}
+
+impl UdpSocket {
+    /// Binds to the current host. The address must be unspecified, the
+    /// address of the host, or a loopback address.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> impl Future<Output = Result<UdpSocket>> + use<A> {
+        // The host is the one that polls the future.
+        let addr = resolve(addr);
+        async move {
+            let addr = addr?;
+            let network = current();
+            let local_addr =
+                network.local_addr(addr, |addr| network.sockets.borrow().contains_key(addr))?;
+            let inbox = Rc::default();
+            network
+                .sockets
+                .borrow_mut()
+                .insert(local_addr, Rc::clone(&inbox));
+            Ok(UdpSocket {
+                network,
+                inbox,
+                local_addr,
+                peer_addr: Cell::new(None),
+                delay: None,
+            })
+        }
+    }
+
+    /// Sets the default destination of [`UdpSocket::send`], and only
+    /// receives datagrams from it.
+    pub fn connect<A: ToSocketAddrs>(
+        &self,
+        addr: A,
+    ) -> impl Future<Output = Result<()>> + use<'_, A> {
+        let res = resolve(addr).map(|addr| self.peer_addr.set(Some(addr)));
+        std::future::ready(res)
+    }
+
+    pub fn send<'b>(&mut self, buf: &'b [u8]) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        let res = match self.peer_addr.get() {
+            Some(target) => Ok(self.send_datagram(buf, target)),
+            None => Err(ErrorKind::NotConnected.into()),
+        };
+        std::future::ready(res)
+    }
+
+    pub fn recv<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_recv_from(cx, buf).map_ok(|(n, _)| n))
+    }
+
+    /// Sends a datagram, which may be dropped or arrive out of order.
+    pub fn send_to<'b>(
+        &mut self,
+        buf: &'b [u8],
+        target: SocketAddr,
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        std::future::ready(Ok(self.send_datagram(buf, target)))
+    }
+
+    pub fn recv_from<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<(usize, SocketAddr)>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_recv_from(cx, buf))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.peer_addr
+            .get()
+            .ok_or_else(|| ErrorKind::NotConnected.into())
+    }
+
+    fn send_datagram(&self, buf: &[u8], target: SocketAddr) -> usize {
+        let network = &self.network;
+        if network.rng.chance(network.packet_loss)
+            || network.is_partitioned(self.local_addr.ip(), target.ip())
+        {
+            return buf.len();
+        }
+        let inbox = network.sockets.borrow().get(&target).cloned();
+        if let Some(inbox) = inbox {
+            let at = now() + network.latency();
+            let mut inbox = inbox.borrow_mut();
+            let index = inbox
+                .datagrams
+                .partition_point(|(arrival, ..)| *arrival <= at);
+            inbox
+                .datagrams
+                .insert(index, (at, self.local_addr, buf.to_vec()));
+            if let Some(waker) = inbox.waker.take() {
+                waker.wake();
+            }
+        }
+        buf.len()
+    }
+
+    fn poll_recv_from(
+        &mut self,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<Result<(usize, SocketAddr)>> {
+        loop {
+            let mut inbox = self.inbox.borrow_mut();
+            let Some(&(at, from, _)) = inbox.datagrams.front() else {
+                inbox.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            };
+            if at > now() && poll_until(&mut self.delay, at, cx).is_pending() {
+                // An earlier datagram may still be sent, and overtake this one.
+                inbox.waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            let (_, _, data) = inbox.datagrams.pop_front().unwrap();
+            if self.peer_addr.get().is_some_and(|peer| peer != from) {
+                continue;
+            }
+            // Like a real socket, the rest of a long datagram is discarded.
+            let n = buf.len().min(data.len());
+            buf[..n].copy_from_slice(&data[..n]);
+            return Poll::Ready(Ok((n, from)));
+        }
+    }
+}
+
+impl Drop for UdpSocket {
+    fn drop(&mut self) {
+        self.network.sockets.borrow_mut().remove(&self.local_addr);
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UdpSocket")
+            .field("local_addr", &self.local_addr)
+            .field("peer_addr", &self.peer_addr.get())
+            .finish()
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for TcpStream {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        TcpStream::poll_read(self.get_mut(), cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncRead for TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<Result<()>> {
+        let n = std::task::ready!(TcpStream::poll_read(
+            self.get_mut(),
+            cx,
+            buf.initialize_unfilled()
+        ))?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncWrite for TcpStream {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        TcpStream::poll_write(self.get_mut(), cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        TcpStream::poll_write_vectored(self.get_mut(), cx, bufs)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncWrite for TcpStream {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        TcpStream::poll_write(self.get_mut(), cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        TcpStream::poll_write_vectored(self.get_mut(), cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        true
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(self.shutdown(Shutdown::Write))
+    }
+}
@@ -0,0 +1,366 @@
+//! A scripted I/O object, for testing protocol code without sockets.
+//!
+//! [`Builder`] records the bytes the code under test is expected to read and
+//! write, in order, and builds a [`Mock`] that plays them back. The mock
+//! implements the same async I/O traits as [`TcpStream`], so it can stand in
+//! for a connection wherever the code is generic over those traits.
+//!
+//! - A read returns the bytes of the next scripted read. It waits while a
+//!   scripted write comes first, and returns `Ok(0)` once the script is over.
+//! - A write is checked against the next scripted writes, which may come
+//!   after scripted reads that haven't happened yet. Unexpected bytes panic.
+//! - [`Builder::wait`] holds both reads and writes for a duration of the
+//!   worker clock, combine it with [`time::pause`] to make tests deterministic.
+//!
+//! Dropping the mock before the script is over panics.
+//!
+//! ```
+//! use nio::test_util::io::Builder;
+//! use std::time::Duration;
+//!
+//! # #[nio::main(start_paused = true)]
+//! # async fn main() {
+//! let mut mock = Builder::new()
+//!     .write(b"PING\r\n")
+//!     .wait(Duration::from_secs(1))
+//!     .read(b"PONG\r\n")
+//!     .build();
+//!
+//! mock.write(b"PING\r\n").await.unwrap();
+//!
+//! let mut buf = [0; 6];
+//! mock.read(&mut buf).await.unwrap();
+//! assert_eq!(&buf, b"PONG\r\n");
+//! # }
+//! ```
+//!
+//! [`TcpStream`]: crate::net::TcpStream
+//! [`time::pause`]: crate::time::pause
+
+use crate::timer::sleep::{Sleep, sleep};
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::{Future, poll_fn},
+    io::{Error, IoSlice, Result},
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug)]
+enum Action {
+    Read(Vec<u8>),
+    Write(Vec<u8>),
+    Wait(Duration),
+    ReadError(Option<Error>),
+    WriteError(Option<Error>),
+}
+
+/// Records the script of a [`Mock`].
+#[derive(Debug, Default)]
+pub struct Builder {
+    actions: VecDeque<Action>,
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// The next read returns `buf`, possibly split across several reads.
+    pub fn read(mut self, buf: &[u8]) -> Self {
+        self.actions.push_back(Action::Read(buf.into()));
+        self
+    }
+
+    /// The next writes are expected to be `buf`.
+    pub fn write(mut self, buf: &[u8]) -> Self {
+        self.actions.push_back(Action::Write(buf.into()));
+        self
+    }
+
+    /// Reads and writes wait for `duration` before the rest of the script.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.actions.push_back(Action::Wait(duration));
+        self
+    }
+
+    /// The next read fails with `error`.
+    pub fn read_error(mut self, error: Error) -> Self {
+        self.actions.push_back(Action::ReadError(Some(error)));
+        self
+    }
+
+    /// The next write fails with `error`.
+    pub fn write_error(mut self, error: Error) -> Self {
+        self.actions.push_back(Action::WriteError(Some(error)));
+        self
+    }
+
+    /// Builds a mock that plays the script.
+    ///
+    /// The mock uses the timer of the current worker, for [`Builder::wait`].
+    pub fn build(self) -> Mock {
+        Mock {
+            actions: self.actions,
+            delay: None,
+            read_waker: None,
+            write_waker: None,
+        }
+    }
+}
+
+/// Plays back the script recorded by a [`Builder`].
+pub struct Mock {
+    actions: VecDeque<Action>,
+    /// Timer of the [`Action::Wait`] in front of the script.
+    delay: Option<Sleep>,
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+}
+
+impl Mock {
+    pub fn read<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_read(cx, buf))
+    }
+
+    pub fn write<'b>(
+        &mut self,
+        buf: &'b [u8],
+    ) -> impl Future<Output = Result<usize>> + use<'_, 'b> {
+        poll_fn(|cx| self.poll_write(cx, buf))
+    }
+
+    pub fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if self.poll_wait(cx).is_pending() {
+            self.read_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        match self.actions.front_mut() {
+            None => Poll::Ready(Ok(0)),
+            Some(Action::Read(data)) => {
+                let n = buf.len().min(data.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                data.drain(..n);
+                if data.is_empty() {
+                    self.pop();
+                }
+                Poll::Ready(Ok(n))
+            }
+            Some(Action::ReadError(error)) => {
+                let error = error.take().unwrap();
+                self.pop();
+                Poll::Ready(Err(error))
+            }
+            Some(Action::Write(_) | Action::WriteError(_)) => {
+                // Waits for the writer.
+                self.read_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Some(Action::Wait(_)) => unreachable!(),
+        }
+    }
+
+    pub fn poll_write(&mut self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        if self.poll_wait(cx).is_pending() {
+            self.write_waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        // Writes may go ahead of scripted reads.
+        let index = self
+            .actions
+            .iter()
+            .position(|action| !matches!(action, Action::Read(_) | Action::ReadError(_)));
+
+        match index.map(|index| (index, &mut self.actions[index])) {
+            Some((index, Action::Write(expected))) => {
+                let n = buf.len().min(expected.len());
+                assert_eq!(
+                    Bytes(&buf[..n]),
+                    Bytes(&expected[..n]),
+                    "unexpected bytes written"
+                );
+                expected.drain(..n);
+                if expected.is_empty() {
+                    self.remove(index);
+                }
+                Poll::Ready(Ok(n))
+            }
+            Some((index, Action::WriteError(error))) => {
+                let error = error.take().unwrap();
+                self.remove(index);
+                Poll::Ready(Err(error))
+            }
+            Some((_, Action::Wait(_))) => {
+                // Reads before the wait must happen first.
+                self.write_waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            _ => panic!("unexpected write: {:?}", Bytes(buf)),
+        }
+    }
+
+    pub fn poll_write_vectored(
+        &mut self,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        let buf = bufs
+            .iter()
+            .find(|buf| !buf.is_empty())
+            .map_or(&[][..], |buf| &**buf);
+        self.poll_write(cx, buf)
+    }
+
+    /// Waits for the [`Action::Wait`] in front of the script.
+    fn poll_wait(&mut self, cx: &mut Context) -> Poll<()> {
+        while let Some(Action::Wait(duration)) = self.actions.front() {
+            let delay = self.delay.get_or_insert_with(|| sleep(*duration));
+            if Pin::new(delay).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            self.delay = None;
+            self.pop();
+        }
+        Poll::Ready(())
+    }
+
+    fn pop(&mut self) {
+        self.remove(0);
+    }
+
+    /// Wakes the reader and the writer, the next action may be theirs.
+    fn remove(&mut self, index: usize) {
+        self.actions.remove(index);
+        for waker in [self.read_waker.take(), self.write_waker.take()]
+            .into_iter()
+            .flatten()
+        {
+            waker.wake();
+        }
+    }
+}
+
+impl Drop for Mock {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        let remaining = self
+            .actions
+            .iter()
+            .filter(|action| !matches!(action, Action::Wait(_)))
+            .count();
+        assert!(
+            remaining == 0,
+            "mock dropped before the end of the script: {:?}",
+            self.actions
+        );
+    }
+}
+
+impl fmt::Debug for Mock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mock")
+            .field("actions", &self.actions)
+            .finish()
+    }
+}
+
+/// Prints bytes as an escaped string.
+#[derive(PartialEq)]
+struct Bytes<'a>(&'a [u8]);
+
+impl fmt::Debug for Bytes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "b\"{}\"", self.0.escape_ascii())
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncRead for Mock {
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        Mock::poll_read(self.get_mut(), cx, buf)
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncRead for Mock {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut tokio::io::ReadBuf,
+    ) -> Poll<Result<()>> {
+        let n = std::task::ready!(Mock::poll_read(
+            self.get_mut(),
+            cx,
+            buf.initialize_unfilled()
+        ))?;
+        buf.advance(n);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl futures_io::AsyncWrite for Mock {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        Mock::poll_write(self.get_mut(), cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        Mock::poll_write_vectored(self.get_mut(), cx, bufs)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-io")]
+impl tokio::io::AsyncWrite for Mock {
+    #[inline]
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        Mock::poll_write(self.get_mut(), cx, buf)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<Result<usize>> {
+        Mock::poll_write_vectored(self.get_mut(), cx, bufs)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
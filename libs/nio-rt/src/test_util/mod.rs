@@ -0,0 +1,5 @@
+//! Utilities for testing code built on Nio.
+//!
+//! Requires the `test-util` feature.
+
+pub mod io;
@@ -0,0 +1,89 @@
+//! Utilities for tracking time.
+//!
+//! Every worker has its own clock, which is read once per iteration of the
+//! event loop. For tests, the clock of the current worker can be frozen with
+//! [`pause`] and moved forward manually with [`advance`]. While the clock is
+//! frozen and the worker has nothing else to do, it jumps to the deadline of
+//! the next timer, so sleeping tasks complete instantly:
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! #[nio::main(start_paused = true)]
+//! async fn main() {
+//!     let start = std::time::Instant::now();
+//!     nio::time::sleep(Duration::from_secs(60 * 60)).await;
+//!     assert!(start.elapsed() < Duration::from_secs(1));
+//! }
+//! ```
+
+use crate::rt::context::LocalContext;
+use std::{future::poll_fn, task::Poll, time::Duration};
+
+pub use crate::timer::{
+    delay_queue::{self, DelayQueue},
+    error,
+    interval::{Interval, MissedTickBehavior, interval, interval_at},
+    sleep::{Sleep, sleep},
+    timeout::{StreamTimeoutExt, Timeout, TimeoutExt, TimeoutStream, timeout, timeout_at},
+};
+
+/// Freezes the clock of the current worker.
+///
+/// Timers don't fire until the clock is moved forward with [`advance`], or
+/// automatically, when the worker is idle.
+///
+/// The clock is not shared between workers. Use a single worker, which is the
+/// default for `#[nio::test]`, or [`RuntimeBuilder::start_paused`] to freeze
+/// all of them.
+///
+/// [`RuntimeBuilder::start_paused`]: crate::RuntimeBuilder::start_paused
+///
+/// # Panics
+///
+/// Panics if the clock is already frozen, or if called outside of a worker.
+pub fn pause() {
+    LocalContext::with(|ctx| unsafe { ctx.timers(|timers| timers.pause()) })
+}
+
+/// Resumes the clock of the current worker.
+///
+/// If the clock was advanced ahead of the real time, it continues from the
+/// frozen time, so advanced time is not lost. Otherwise it catches up with the
+/// real time, including the time spent frozen. It never goes backwards.
+///
+/// # Panics
+///
+/// Panics if the clock is not frozen, or if called outside of a worker.
+pub fn resume() {
+    LocalContext::with(|ctx| unsafe { ctx.timers(|timers| timers.clock.resume()) })
+}
+
+/// Moves the frozen clock of the current worker forward by `duration`.
+///
+/// Timers that have expired are fired, and the current task yields, so that
+/// tasks woken by them run before this future completes.
+///
+/// # Panics
+///
+/// Panics if the clock is not frozen, or if called outside of a worker.
+pub async fn advance(duration: Duration) {
+    let expired = LocalContext::with(|ctx| unsafe {
+        ctx.timers(|timers| {
+            timers.clock.advance(duration);
+            timers.fetch(timers.clock.current())
+        })
+    });
+    expired.notify_all();
+
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
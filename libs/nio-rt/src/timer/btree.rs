@@ -0,0 +1,98 @@
+//! Timers ordered by deadline in a [`BTreeMap`].
+//!
+//! Enabled by the `timer-btree` feature, as a reference for the timing wheel.
+
+use super::{RcTimer, Timer};
+use std::{
+    collections::BTreeMap,
+    mem::{self, ManuallyDrop},
+    time::{Duration, Instant},
+};
+
+type Entries = BTreeMap<RcTimer, ()>;
+
+pub struct Store {
+    entries: Entries,
+}
+
+pub struct Expired {
+    entries: Entries,
+}
+
+impl Store {
+    /// Deadlines are exact, the resolution is ignored.
+    pub fn new(_: Instant, _: Duration) -> Store {
+        Store {
+            entries: Entries::new(),
+        }
+    }
+
+    pub fn insert(&mut self, timer: RcTimer) {
+        self.entries.insert(timer, ());
+    }
+
+    pub fn remove(&mut self, timer: &RcTimer) {
+        if let Some((entry, _)) = self.entries.remove_entry(timer) {
+            drop(entry);
+        }
+    }
+
+    pub fn reset(&mut self, timer: &RcTimer, deadline: Instant) {
+        if let Some((entry, _)) = self.entries.remove_entry(timer) {
+            entry.set_deadline(deadline);
+            self.entries.insert(entry, ());
+        }
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        Some(self.entries.first_key_value()?.0.deadline())
+    }
+
+    pub fn fetch(&mut self, upto: Instant) -> Expired {
+        // Timers with the same deadline are ordered by address.
+        let timer = &Timer::new(upto + Duration::from_nanos(1));
+        let entry = ManuallyDrop::new(RcTimer::from_inner(timer.into()));
+
+        let right = self.entries.split_off(&entry);
+        let left = mem::replace(&mut self.entries, right);
+        Expired { entries: left }
+    }
+
+    pub fn round_up(&self, time: Instant) -> Instant {
+        time
+    }
+
+    pub fn for_each(&self, mut f: impl FnMut(&Timer)) {
+        for entry in self.entries.keys() {
+            f(entry.as_ref());
+        }
+    }
+}
+
+impl Expired {
+    pub fn notify_all(self) -> usize {
+        let count = self.entries.len();
+        for (entry, _) in self.entries {
+            entry.as_ref().waker.wake();
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_includes_deadline() {
+        let now = Instant::now();
+        let mut store = Store::new(now, Duration::ZERO);
+        // Timers with the same deadline are ordered by address, and this one is
+        // on the stack of the caller, above the entry built by `fetch`. The
+        // paused clock advances exactly to the deadline of the next timer.
+        let timer = Timer::new(now);
+        store.insert(RcTimer::from_inner((&timer).into()));
+        assert_eq!(store.fetch(now).entries.len(), 1);
+        assert!(store.next_deadline().is_none());
+    }
+}
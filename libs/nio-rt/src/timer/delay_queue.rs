@@ -0,0 +1,322 @@
+//! A queue of values, each yielded once its own deadline has elapsed.
+
+use crate::{rt::context::LocalContext, timer::sleep::Sleep};
+use futures_core::Stream;
+use std::{
+    collections::BTreeSet,
+    fmt,
+    future::{Future, poll_fn},
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// Identifies a value inserted into a [`DelayQueue`].
+///
+/// A key is valid until its value expires or is removed, after that it may be
+/// reused for a new value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: usize,
+}
+
+/// A value yielded by [`DelayQueue::poll_expired`].
+#[derive(Debug)]
+pub struct Expired<T> {
+    data: T,
+    deadline: Instant,
+    key: Key,
+}
+
+impl<T> Expired<T> {
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        &self.data
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.data
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data
+    }
+
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub fn key(&self) -> Key {
+        self.key
+    }
+}
+
+enum Slot<T> {
+    Occupied {
+        data: T,
+        deadline: Instant,
+    },
+    /// Index of the next vacant slot.
+    Vacant(Option<usize>),
+}
+
+/// Holds values until their deadline elapses.
+///
+/// Meant for many short lived expirations, like idle connections or cache
+/// entries. Rather than one [`Sleep`] per value, the queue keeps a single
+/// timer on the worker for the earliest deadline, so inserting, resetting and
+/// removing values doesn't touch the timer unless the earliest deadline
+/// changes, and only one waker is registered per queue.
+///
+/// Like other timers, the queue is bound to the worker it was created on.
+///
+/// ```
+/// use nio::time::DelayQueue;
+/// use std::time::Duration;
+///
+/// # #[nio::main]
+/// # async fn main() {
+/// let mut queue = DelayQueue::new();
+/// let key = queue.insert("a", Duration::from_millis(20));
+/// queue.insert("b", Duration::from_millis(10));
+/// queue.reset(&key, Duration::from_millis(5));
+///
+/// assert_eq!(queue.next_expired().await.unwrap().into_inner(), "a");
+/// assert_eq!(queue.next_expired().await.unwrap().into_inner(), "b");
+/// assert!(queue.next_expired().await.is_none());
+/// # }
+/// ```
+pub struct DelayQueue<T> {
+    slots: Vec<Slot<T>>,
+    /// Head of the vacant slots.
+    vacant: Option<usize>,
+    len: usize,
+    expirations: BTreeSet<(Instant, usize)>,
+    /// Fires at the earliest deadline, created on first use.
+    delay: Option<Sleep>,
+}
+
+impl<T> DelayQueue<T> {
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> DelayQueue<T> {
+        DelayQueue {
+            slots: Vec::with_capacity(capacity),
+            vacant: None,
+            len: 0,
+            expirations: BTreeSet::new(),
+            delay: None,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts `value`, which expires after `timeout`.
+    pub fn insert(&mut self, value: T, timeout: Duration) -> Key {
+        self.insert_at(value, current() + timeout)
+    }
+
+    /// Inserts `value`, which expires at `deadline`.
+    pub fn insert_at(&mut self, value: T, deadline: Instant) -> Key {
+        let slot = Slot::Occupied {
+            data: value,
+            deadline,
+        };
+        let index = match self.vacant {
+            Some(index) => {
+                match mem::replace(&mut self.slots[index], slot) {
+                    Slot::Vacant(next) => self.vacant = next,
+                    Slot::Occupied { .. } => unreachable!(),
+                }
+                index
+            }
+            None => {
+                self.slots.push(slot);
+                self.slots.len() - 1
+            }
+        };
+        self.len += 1;
+        self.expirations.insert((deadline, index));
+        self.update_delay();
+        Key { index }
+    }
+
+    /// Returns the deadline of the value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not in the queue.
+    pub fn deadline(&self, key: &Key) -> Instant {
+        match self.slots.get(key.index) {
+            Some(Slot::Occupied { deadline, .. }) => *deadline,
+            _ => invalid_key(),
+        }
+    }
+
+    /// Returns `true` if `key` is in the queue.
+    pub fn contains(&self, key: &Key) -> bool {
+        matches!(self.slots.get(key.index), Some(Slot::Occupied { .. }))
+    }
+
+    /// Removes the value before it expires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not in the queue.
+    pub fn remove(&mut self, key: &Key) -> T {
+        self.try_remove(key).unwrap_or_else(|| invalid_key())
+    }
+
+    /// Removes the value before it expires, or returns `None` if `key` is not
+    /// in the queue.
+    pub fn try_remove(&mut self, key: &Key) -> Option<T> {
+        let expired = self.take(key.index)?;
+        self.update_delay();
+        Some(expired.data)
+    }
+
+    /// Changes the value to expire after `timeout`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not in the queue.
+    pub fn reset(&mut self, key: &Key, timeout: Duration) {
+        self.reset_at(key, current() + timeout);
+    }
+
+    /// Changes the value to expire at `deadline`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `key` is not in the queue.
+    pub fn reset_at(&mut self, key: &Key, deadline: Instant) {
+        let Some(Slot::Occupied { deadline: old, .. }) = self.slots.get_mut(key.index) else {
+            invalid_key()
+        };
+        let old = mem::replace(old, deadline);
+        self.expirations.remove(&(old, key.index));
+        self.expirations.insert((deadline, key.index));
+        self.update_delay();
+    }
+
+    /// Removes every value.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.vacant = None;
+        self.len = 0;
+        self.expirations.clear();
+        self.delay = None;
+    }
+
+    /// Returns the next expired value.
+    ///
+    /// Returns `Poll::Ready(None)` if the queue is empty. New values can be
+    /// inserted afterwards, and the queue polled again.
+    pub fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        loop {
+            let Some(&(deadline, index)) = self.expirations.first() else {
+                self.delay = None;
+                return Poll::Ready(None);
+            };
+            if deadline <= current() {
+                let expired = self.take(index).expect("expired value is missing");
+                return Poll::Ready(Some(expired));
+            }
+            let delay = self.delay.get_or_insert_with(|| Sleep::at(deadline));
+            if delay.deadline() != deadline {
+                delay.reset_at(deadline);
+            }
+            if Pin::new(delay).poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+
+    /// Completes with the next expired value, or `None` if the queue is empty.
+    pub fn next_expired(&mut self) -> impl Future<Output = Option<Expired<T>>> + use<'_, T> {
+        poll_fn(|cx| self.poll_expired(cx))
+    }
+
+    /// Removes the slot, without touching the timer.
+    fn take(&mut self, index: usize) -> Option<Expired<T>> {
+        match self.slots.get(index)? {
+            Slot::Occupied { .. } => {}
+            Slot::Vacant(_) => return None,
+        }
+        let Slot::Occupied { data, deadline } =
+            mem::replace(&mut self.slots[index], Slot::Vacant(self.vacant))
+        else {
+            unreachable!()
+        };
+        self.vacant = Some(index);
+        self.len -= 1;
+        self.expirations.remove(&(deadline, index));
+        Some(Expired {
+            data,
+            deadline,
+            key: Key { index },
+        })
+    }
+
+    /// Moves the timer to the earliest deadline, so the registered waker is
+    /// woken in time.
+    fn update_delay(&mut self) {
+        if let Some(delay) = &mut self.delay {
+            match self.expirations.first() {
+                Some(&(deadline, _)) if delay.deadline() != deadline => delay.reset_at(deadline),
+                Some(_) => {}
+                None => self.delay = None,
+            }
+        }
+    }
+}
+
+fn current() -> Instant {
+    LocalContext::with(|ctx| unsafe { ctx.timers(|timers| timers.clock.current()) })
+}
+
+#[cold]
+fn invalid_key() -> ! {
+    panic!("invalid key")
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        DelayQueue::new()
+    }
+}
+
+// Values are never pinned.
+impl<T> Unpin for DelayQueue<T> {}
+
+impl<T> Stream for DelayQueue<T> {
+    type Item = Expired<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        self.get_mut().poll_expired(cx)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for &(deadline, index) in &self.expirations {
+            if let Slot::Occupied { data, .. } = &self.slots[index] {
+                map.entry(&deadline, data);
+            }
+        }
+        map.finish()
+    }
+}
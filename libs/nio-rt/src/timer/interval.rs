@@ -5,22 +5,72 @@ use crate::{
 use std::{
     future::poll_fn,
     ops::{Deref, DerefMut},
+    pin::Pin,
     task::{Context, Poll},
     time::{Duration, Instant},
 };
 
+/// A tick is considered missed if it is polled later than this.
+const MISSED_THRESHOLD: Duration = Duration::from_millis(5);
+
+/// Defines what [`Interval`] does when a tick is missed, because the task
+/// didn't poll it in time.
+///
+/// With a period of `10ms`, first tick at `t`, and ticks at `t+10` and `t+20`
+/// missed until `t+25`:
+///
+/// | Behavior | Next ticks             |
+/// | -------- | ---------------------- |
+/// | `Burst`  | `25`, `25`, `30`, `40` |
+/// | `Delay`  | `25`, `35`, `45`, `55` |
+/// | `Skip`   | `25`, `30`, `40`, `50` |
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissedTickBehavior {
+    /// Ticks as fast as possible until caught up with the schedule.
+    #[default]
+    Burst,
+    /// Restarts the schedule from the time of the missed tick.
+    Delay,
+    /// Skips the missed ticks and keeps the original schedule.
+    Skip,
+}
+
+impl MissedTickBehavior {
+    fn next_timeout(self, timeout: Instant, now: Instant, period: Duration) -> Instant {
+        match self {
+            MissedTickBehavior::Burst => timeout + period,
+            MissedTickBehavior::Delay => now + period,
+            MissedTickBehavior::Skip => {
+                let behind = (now - timeout).as_nanos() % period.as_nanos();
+                now + period - Duration::from_nanos(behind as u64)
+            }
+        }
+    }
+}
+
+/// Ticks on a fixed schedule, every `period`.
+///
+/// Created with [`interval`] or [`Interval::at`].
 #[derive(Debug)]
 pub struct Interval {
     delay: Sleep,
     period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
 }
 
 impl Interval {
+    /// Creates an interval whose first tick completes at `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
     #[inline]
-    pub fn at(deadline: Instant, period: Duration) -> Interval {
+    pub fn at(start: Instant, period: Duration) -> Interval {
+        assert!(period > Duration::ZERO, "`period` must be non-zero");
         Interval {
-            delay: Sleep::at(deadline),
+            delay: Sleep::at(start),
             period,
+            missed_tick_behavior: MissedTickBehavior::default(),
         }
     }
 
@@ -28,37 +78,100 @@ impl Interval {
         self.period
     }
 
+    /// Changes the period, starting from the tick after the next one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
     pub fn set_period(&mut self, period: Duration) {
+        assert!(period > Duration::ZERO, "`period` must be non-zero");
         self.period = period;
     }
 
-    pub fn tick<'a>(&'a mut self) -> impl Future + use<'a> {
-        poll_fn(|cx| self.poll(cx))
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.missed_tick_behavior
     }
 
+    pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+        self.missed_tick_behavior = behavior;
+    }
+
+    /// Completes when the next tick is due, with the time it was scheduled at.
+    pub fn tick<'a>(&'a mut self) -> impl Future<Output = Instant> + use<'a> {
+        poll_fn(|cx| self.poll_tick(cx))
+    }
+
+    /// Polls for the next tick, without the time it was scheduled at.
+    #[deprecated(since = "0.2.0", note = "use `Interval::poll_tick`")]
     pub fn poll(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.poll_tick(cx).map(|_| ())
+    }
+
+    pub fn poll_tick(&mut self, cx: &mut Context<'_>) -> Poll<Instant> {
         self.delay.timer.as_ref().waker.register(cx);
 
-        if self.delay.is_elapsed() {
-            let timer = self.delay.timer.clone();
+        if !self.delay.is_elapsed() {
+            return Poll::Pending;
+        }
+        let timeout = self.delay.deadline();
+        let now = current();
+
+        let next = if now > timeout + MISSED_THRESHOLD {
+            self.missed_tick_behavior
+                .next_timeout(timeout, now, self.period)
+        } else {
+            timeout + self.period
+        };
+        self.delay.reset_at(next);
+        Poll::Ready(timeout)
+    }
 
-            LocalContext::with(|ctx| unsafe {
-                ctx.timers(|timers| {
-                    timer.set_deadline(timers.clock.current() + self.period);
-                    timers.insert_entry(timer)
-                })
-            });
+    /// Resets the schedule, the next tick completes after one `period`.
+    pub fn reset(&mut self) {
+        self.delay.reset_at(current() + self.period);
+    }
 
-            return Poll::Ready(());
-        }
-        Poll::Pending
+    /// Resets the schedule, the next tick completes immediately.
+    pub fn reset_immediately(&mut self) {
+        self.delay.reset_at(current());
     }
+
+    /// Resets the schedule, the next tick completes at `deadline`.
+    pub fn reset_at(&mut self, deadline: Instant) {
+        self.delay.reset_at(deadline);
+    }
+}
+
+fn current() -> Instant {
+    LocalContext::with(|ctx| unsafe { ctx.timers(|timers| timers.clock.current()) })
 }
 
+/// Creates an interval whose first tick completes immediately.
+///
+/// # Panics
+///
+/// Panics if `period` is zero.
 pub fn interval(period: Duration) -> Interval {
+    assert!(period > Duration::ZERO, "`period` must be non-zero");
     Interval {
         delay: sleep(Duration::ZERO),
         period,
+        missed_tick_behavior: MissedTickBehavior::default(),
+    }
+}
+
+/// Creates an interval whose first tick completes at `start`.
+///
+/// See [`Interval::at`].
+pub fn interval_at(start: Instant, period: Duration) -> Interval {
+    Interval::at(start, period)
+}
+
+impl futures_core::Stream for Interval {
+    type Item = Instant;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+        self.get_mut().poll_tick(cx).map(Some)
     }
 }
 
@@ -1,26 +1,41 @@
+pub mod delay_queue;
+pub mod error;
 pub mod interval;
 pub mod sleep;
 pub mod timeout;
 
+#[cfg(feature = "timer-btree")]
+mod btree;
+#[cfg(not(feature = "timer-btree"))]
+mod wheel;
+
 use crate::local_waker::LocalWaker;
 use sleep::Sleep;
 
+#[cfg(feature = "timer-btree")]
+use btree::{Expired, Store};
+#[cfg(not(feature = "timer-btree"))]
+use wheel::{Expired, Store};
+
 use std::{
     cell::Cell,
-    cmp,
-    collections::BTreeMap,
-    fmt,
+    cmp, fmt,
     marker::PhantomData,
-    mem::{self, ManuallyDrop},
     ptr::NonNull,
     time::{Duration, Instant},
 };
 
-type Entries = BTreeMap<RcTimer, ()>;
-pub struct Clock(Cell<Instant>);
+pub const DEFAULT_RESOLUTION: Duration = Duration::from_millis(1);
+
+pub struct Clock {
+    now: Cell<Instant>,
+    /// Time spent paused (and advanced), added to [`Instant::now`].
+    offset: Cell<Duration>,
+    paused: Cell<bool>,
+}
 
 pub struct Timers {
-    entries: Entries,
+    store: Store,
     pub clock: Clock,
 }
 
@@ -117,6 +132,8 @@ struct Timer {
     rc: Cell<u8>,
     deadline: Cell<Instant>,
     waker: LocalWaker,
+    #[cfg(not(feature = "timer-btree"))]
+    links: wheel::Links,
 }
 
 impl Timer {
@@ -125,90 +142,122 @@ impl Timer {
             rc: Cell::new(2),
             deadline: Cell::new(deadline),
             waker: LocalWaker::new(),
+            #[cfg(not(feature = "timer-btree"))]
+            links: wheel::Links::new(),
         }
     }
 }
 
 impl Timers {
-    pub fn new() -> Timers {
+    /// `resolution` is the granularity of the timing wheel, timers never
+    /// fire before their deadline, but up to `resolution` after it.
+    pub fn new(resolution: Duration) -> Timers {
+        let clock = Clock::new();
         Timers {
-            entries: Entries::new(),
-            clock: Clock::new(),
+            store: Store::new(clock.current(), resolution),
+            clock,
         }
     }
 
+    /// Freezes the clock at the start of the next tick, so that durations
+    /// that are a multiple of the resolution expire exactly.
+    pub fn pause(&mut self) {
+        self.clock.pause();
+        let now = self.store.round_up(self.clock.current());
+        self.clock.now.set(now);
+    }
+
     fn remove(&mut self, timer: &RcTimer) {
-        if let Some((entry, _)) = self.entries.remove_entry(timer) {
-            drop(entry);
-        }
+        self.store.remove(timer);
     }
 
+    /// Inserts the timer again if it has already fired.
     fn reset_at(&mut self, timer: &RcTimer, deadline: Instant) {
-        if let Some((entry, _)) = self.entries.remove_entry(timer) {
-            entry.set_deadline(deadline);
-            self.entries.insert(entry, ());
+        if timer.as_ref().rc.get() == 1 {
+            timer.set_deadline(deadline);
+            return self.store.insert(timer.clone());
         }
+        self.store.reset(timer, deadline);
     }
 
     fn sleep_at(&mut self, deadline: Instant) -> Sleep {
         let (timer, other) = RcTimer::create(deadline);
-        self.entries.insert(other, ());
+        self.store.insert(other);
         Sleep { timer }
     }
 
-    fn insert_entry(&mut self, timer: RcTimer) {
-        self.entries.insert(timer, ());
-    }
-
     pub fn next_timeout(&self, since: Instant) -> Option<Duration> {
-        if self.entries.is_empty() {
-            return None;
-        }
-        self.entries
-            .first_key_value()?
-            .0
-            .deadline()
-            .checked_duration_since(since)
+        let deadline = self.store.next_deadline()?;
+        Some(deadline.saturating_duration_since(since))
     }
 
+    /// Removes timers whose deadline is at or before `upto`.
     pub fn fetch(&mut self, upto: Instant) -> Elapsed {
-        let timer = &Timer::new(upto);
-        let entry = ManuallyDrop::new(RcTimer::from_inner(timer.into()));
-
-        let right = self.entries.split_off(&entry);
-        let left = mem::replace(&mut self.entries, right);
-        Elapsed { entries: left }
+        Elapsed {
+            entries: self.store.fetch(upto),
+        }
     }
 }
 
 pub struct Elapsed {
-    entries: Entries,
+    entries: Expired,
 }
 
 impl Elapsed {
     /// [`crate::LocalContext::add_task_to_local_queue`]
-    pub fn notify_all(self) {
-        for (entry, _) in self.entries {
-            entry.as_ref().waker.wake();
-        }
+    ///
+    /// Returns the number of timers fired.
+    pub fn notify_all(self) -> usize {
+        self.entries.notify_all()
     }
 }
 
 impl Clock {
     fn new() -> Self {
-        Self(Cell::new(Instant::now()))
+        Self {
+            now: Cell::new(Instant::now()),
+            offset: Cell::new(Duration::ZERO),
+            paused: Cell::new(false),
+        }
     }
 
     #[inline]
     pub fn current(&self) -> Instant {
-        self.0.get()
+        self.now.get()
     }
 
     pub fn now(&self) -> Instant {
-        let time = Instant::now();
-        self.0.set(time);
+        if self.paused.get() {
+            return self.now.get();
+        }
+        let time = Instant::now() + self.offset.get();
+        self.now.set(time);
         time
     }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    pub fn pause(&self) {
+        assert!(!self.paused.get(), "time is already frozen");
+        self.now();
+        self.paused.set(true);
+    }
+
+    /// Keeps the lead of the paused time over the real time, if any; it never goes backwards.
+    pub fn resume(&self) {
+        assert!(self.paused.get(), "time is not frozen");
+        let offset = self.now.get().saturating_duration_since(Instant::now());
+        self.offset.set(offset);
+        self.paused.set(false);
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        assert!(self.paused.get(), "time is not frozen");
+        self.now.set(self.now.get() + duration);
+    }
 }
 
 impl fmt::Debug for Timer {
@@ -224,9 +273,9 @@ impl fmt::Debug for Timer {
 impl fmt::Debug for Timers {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut map = f.debug_list();
-        for entry in self.entries.keys() {
-            map.entry(entry.as_ref());
-        }
+        self.store.for_each(|timer| {
+            map.entry(timer);
+        });
         map.finish()
     }
 }
@@ -25,12 +25,17 @@ impl Sleep {
         self.timer.as_ref().rc.get() == 1
     }
 
+    /// Changes the deadline of the sleep.
+    ///
+    /// An elapsed sleep is armed again, and completes at the new deadline.
+    /// Before `0.2`, resetting an elapsed sleep had no effect.
     pub fn reset_at(&mut self, deadline: Instant) {
         LocalContext::with(|ctx| unsafe {
             ctx.timers(|timers| timers.reset_at(&self.timer, deadline))
         })
     }
 
+    /// Changes the deadline of the sleep to `duration` from now, see [`Sleep::reset_at`].
     pub fn reset(&mut self, duration: Duration) {
         LocalContext::with(|ctx| unsafe {
             ctx.timers(|timers| timers.reset_at(&self.timer, timers.clock.current() + duration))
@@ -53,6 +58,8 @@ impl Future for Sleep {
             return Poll::Ready(());
         }
         self.timer.as_ref().waker.register(cx);
+        #[cfg(feature = "task-dump")]
+        crate::rt::dump::awaiting("sleep", "nio::time::Sleep");
         Poll::Pending
     }
 }
@@ -1,4 +1,8 @@
-use crate::timer::sleep::{Sleep, sleep};
+use crate::timer::{
+    error::Elapsed,
+    sleep::{Sleep, sleep},
+};
+use futures_core::Stream;
 use std::{
     fmt,
     ops::{Deref, DerefMut},
@@ -7,6 +11,24 @@ use std::{
     time::{Duration, Instant},
 };
 
+/// Requires a future to complete before a deadline.
+///
+/// Resolves to `Err(Elapsed)` if the deadline elapses first, the inner future
+/// is then dropped along with the `Timeout`.
+///
+/// Before `0.2`, `Timeout` resolved to `Option<T>` (see the changelog). Use
+/// [`Result::ok`] to get the previous output, or `?` in functions returning
+/// [`std::io::Result`]:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// # #[nio::main]
+/// # async fn main() {
+/// let output: Option<u8> = nio::timeout(Duration::from_secs(1), async { 42 }).await.ok();
+/// assert_eq!(output, Some(42));
+/// # }
+/// ```
 pub struct Timeout<Fut> {
     delay: Sleep,
     fut: Fut,
@@ -38,6 +60,7 @@ impl<T> Timeout<T> {
     }
 }
 
+/// Requires `future` to complete within `duration`.
 pub fn timeout<F>(duration: Duration, future: F) -> Timeout<F::IntoFuture>
 where
     F: IntoFuture,
@@ -48,16 +71,27 @@ where
     }
 }
 
+/// Requires `future` to complete before `deadline`.
+pub fn timeout_at<F>(deadline: Instant, future: F) -> Timeout<F::IntoFuture>
+where
+    F: IntoFuture,
+{
+    Timeout {
+        delay: Sleep::at(deadline),
+        fut: future.into_future(),
+    }
+}
+
 impl<Fut: Future> Future for Timeout<Fut> {
-    type Output = Option<Fut::Output>;
+    type Output = Result<Fut::Output, Elapsed>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = unsafe { self.get_unchecked_mut() };
         match unsafe { Pin::new_unchecked(&mut this.fut).poll(cx) } {
-            Poll::Ready(val) => Poll::Ready(Some(val)),
+            Poll::Ready(val) => Poll::Ready(Ok(val)),
             Poll::Pending => {
                 if this.delay.is_elapsed() {
-                    return Poll::Ready(None);
+                    return Poll::Ready(Err(Elapsed(())));
                 }
                 this.delay.timer.as_ref().waker.register(cx);
                 Poll::Pending
@@ -87,3 +121,106 @@ impl<T> fmt::Debug for Timeout<T> {
         f.debug_tuple("Timeout").field(&self.delay).finish()
     }
 }
+
+/// Extension methods to add a timeout to any future.
+pub trait TimeoutExt: IntoFuture + Sized {
+    /// See [`timeout`].
+    fn timeout(self, duration: Duration) -> Timeout<Self::IntoFuture> {
+        timeout(duration, self)
+    }
+
+    /// See [`timeout_at`].
+    fn timeout_at(self, deadline: Instant) -> Timeout<Self::IntoFuture> {
+        timeout_at(deadline, self)
+    }
+}
+
+impl<F: IntoFuture> TimeoutExt for F {}
+
+/// Requires every item of a stream to arrive within a duration.
+///
+/// Created with [`StreamTimeoutExt::timeout`]. When the duration elapses,
+/// `Err(Elapsed)` is yielded once and the stream keeps waiting for the next
+/// item, which restarts the timer.
+pub struct TimeoutStream<S> {
+    stream: S,
+    delay: Sleep,
+    duration: Duration,
+    /// `false` after `Elapsed` was yielded, until the next item.
+    armed: bool,
+}
+
+impl<S> TimeoutStream<S> {
+    #[inline]
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Stream> Stream for TimeoutStream<S> {
+    type Item = Result<S::Item, Elapsed>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        match unsafe { Pin::new_unchecked(&mut this.stream).poll_next(cx) } {
+            Poll::Ready(item) => {
+                if item.is_some() {
+                    this.delay.reset(this.duration);
+                    this.armed = true;
+                }
+                Poll::Ready(item.map(Ok))
+            }
+            Poll::Pending => {
+                if !this.armed {
+                    return Poll::Pending;
+                }
+                if this.delay.is_elapsed() {
+                    this.armed = false;
+                    return Poll::Ready(Some(Err(Elapsed(()))));
+                }
+                this.delay.timer.as_ref().waker.register(cx);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.stream.size_hint();
+        // Each item may be preceded by an `Elapsed` error.
+        (lower, upper.and_then(|n| n.checked_mul(2)))
+    }
+}
+
+impl<S> fmt::Debug for TimeoutStream<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeoutStream")
+            .field("delay", &self.delay)
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+/// Extension methods to add a timeout to any stream.
+pub trait StreamTimeoutExt: Stream + Sized {
+    /// Requires every item to arrive within `duration` of the previous one,
+    /// or of this call for the first item.
+    fn timeout(self, duration: Duration) -> TimeoutStream<Self> {
+        TimeoutStream {
+            stream: self,
+            delay: sleep(duration),
+            duration,
+            armed: true,
+        }
+    }
+}
+
+impl<S: Stream> StreamTimeoutExt for S {}
@@ -0,0 +1,465 @@
+//! Hierarchical timing wheel.
+//!
+//! Time is measured in ticks of the configured resolution. The wheel has
+//! [`LEVELS`] levels of [`SLOTS`] slots, a slot of level `n` spans `64^n`
+//! ticks. Every slot is an intrusive doubly linked list of timers, so
+//! inserting and cancelling a timer is O(1). When the wheel reaches a slot of
+//! a higher level, its timers are moved ("cascaded") to lower levels.
+
+use super::{RcTimer, Timer};
+use std::{
+    cell::Cell,
+    cmp,
+    mem::{self, ManuallyDrop},
+    ptr::NonNull,
+    time::{Duration, Instant},
+};
+
+const LEVELS: usize = 6;
+const SLOT_BITS: usize = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+/// Timers further away are moved to lower levels in multiple steps.
+const MAX_TICKS: u64 = (1 << (LEVELS * SLOT_BITS)) - 1;
+
+/// [`Links::slot`] of a timer that is not in the wheel.
+const UNLINKED: u16 = u16::MAX;
+/// [`Links::slot`] of a timer whose deadline was reached when it was inserted.
+const EXPIRED: u16 = u16::MAX - 1;
+/// [`Links::slot`] of a timer whose deadline is within the next tick.
+const NEAR: u16 = u16::MAX - 2;
+
+/// Position of a [`Timer`] in the wheel.
+pub struct Links {
+    prev: Cell<Option<NonNull<Timer>>>,
+    next: Cell<Option<NonNull<Timer>>>,
+    /// `level * SLOTS + slot`, [`EXPIRED`], [`NEAR`] or [`UNLINKED`].
+    slot: Cell<u16>,
+}
+
+impl Links {
+    pub fn new() -> Links {
+        Links {
+            prev: Cell::new(None),
+            next: Cell::new(None),
+            slot: Cell::new(UNLINKED),
+        }
+    }
+}
+
+#[derive(Default)]
+struct List {
+    head: Option<NonNull<Timer>>,
+}
+
+impl List {
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    fn push(&mut self, ptr: NonNull<Timer>) {
+        let links = unsafe { &ptr.as_ref().links };
+        links.prev.set(None);
+        links.next.set(self.head);
+        if let Some(head) = self.head {
+            unsafe { head.as_ref() }.links.prev.set(Some(ptr));
+        }
+        self.head = Some(ptr);
+    }
+
+    fn unlink(&mut self, ptr: NonNull<Timer>) {
+        let links = unsafe { &ptr.as_ref().links };
+        let (prev, next) = (links.prev.take(), links.next.take());
+        match prev {
+            Some(prev) => unsafe { prev.as_ref() }.links.next.set(next),
+            None => self.head = next,
+        }
+        if let Some(next) = next {
+            unsafe { next.as_ref() }.links.prev.set(prev);
+        }
+        links.slot.set(UNLINKED);
+    }
+
+    fn pop(&mut self) -> Option<NonNull<Timer>> {
+        let head = self.head?;
+        self.unlink(head);
+        Some(head)
+    }
+
+    fn for_each(&self, mut f: impl FnMut(&Timer)) {
+        let mut cursor = self.head;
+        while let Some(ptr) = cursor {
+            let timer = unsafe { ptr.as_ref() };
+            f(timer);
+            cursor = timer.links.next.get();
+        }
+    }
+}
+
+struct Level {
+    /// Bit `n` is set if slot `n` is not empty.
+    occupied: u64,
+    slots: [List; SLOTS],
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level {
+            occupied: 0,
+            slots: std::array::from_fn(|_| List::default()),
+        }
+    }
+}
+
+/// Every linked timer holds one reference of its [`RcTimer`].
+pub struct Store {
+    start: Instant,
+    /// In nanoseconds.
+    resolution: u64,
+    /// Ticks since `start`, that have been processed.
+    elapsed: u64,
+    levels: [Level; LEVELS],
+    expired: List,
+    /// Timers due before the end of the next tick, such as `sleep(0)`, are
+    /// checked against the exact time, so they don't wait for the tick.
+    near: List,
+}
+
+pub struct Expired {
+    list: List,
+}
+
+impl Store {
+    pub fn new(start: Instant, resolution: Duration) -> Store {
+        Store {
+            start,
+            resolution: u64::try_from(resolution.as_nanos()).unwrap().max(1),
+            elapsed: 0,
+            levels: Default::default(),
+            expired: List::default(),
+            near: List::default(),
+        }
+    }
+
+    pub fn insert(&mut self, timer: RcTimer) {
+        let ptr = ManuallyDrop::new(timer).ptr;
+        self.link(ptr);
+    }
+
+    pub fn remove(&mut self, timer: &RcTimer) {
+        if self.unlink(timer.ptr) {
+            drop(RcTimer::from_inner(timer.ptr));
+        }
+    }
+
+    pub fn reset(&mut self, timer: &RcTimer, deadline: Instant) {
+        if self.unlink(timer.ptr) {
+            timer.set_deadline(deadline);
+            self.link(timer.ptr);
+        }
+    }
+
+    /// May return the start of a slot of a higher level, which is earlier
+    /// than the deadlines of its timers, to cascade them.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if !self.expired.is_empty() {
+            return self.instant(self.elapsed);
+        }
+        let mut next = self
+            .next_expiration()
+            .and_then(|(tick, ..)| self.instant(tick));
+        self.near.for_each(|timer| {
+            let deadline = timer.deadline.get();
+            if next.is_none_or(|next| deadline < next) {
+                next = Some(deadline);
+            }
+        });
+        next
+    }
+
+    pub fn fetch(&mut self, upto: Instant) -> Expired {
+        let target = self.nanos(upto) / self.resolution;
+
+        let mut expired = mem::take(&mut self.expired);
+        while let Some((tick, level, slot)) = self.next_expiration() {
+            if tick > target {
+                break;
+            }
+            self.elapsed = tick;
+            self.levels[level].occupied &= !(1 << slot);
+            let mut list = mem::take(&mut self.levels[level].slots[slot]);
+
+            while let Some(ptr) = list.pop() {
+                let when = self.ticks(unsafe { ptr.as_ref() }.deadline.get());
+                if when <= tick {
+                    expired.push(ptr);
+                } else {
+                    self.link(ptr);
+                }
+            }
+        }
+        self.elapsed = cmp::max(self.elapsed, target);
+
+        let mut cursor = self.near.head;
+        while let Some(ptr) = cursor {
+            let timer = unsafe { ptr.as_ref() };
+            cursor = timer.links.next.get();
+            if timer.deadline.get() <= upto {
+                self.near.unlink(ptr);
+                expired.push(ptr);
+            }
+        }
+        Expired { list: expired }
+    }
+
+    pub fn round_up(&self, time: Instant) -> Instant {
+        self.instant(self.ticks(time)).unwrap_or(time)
+    }
+
+    pub fn for_each(&self, mut f: impl FnMut(&Timer)) {
+        self.expired.for_each(&mut f);
+        self.near.for_each(&mut f);
+        for level in &self.levels {
+            for list in &level.slots {
+                list.for_each(&mut f);
+            }
+        }
+    }
+
+    /// Rounds `deadline` up, timers never fire early.
+    fn ticks(&self, deadline: Instant) -> u64 {
+        self.nanos(deadline).div_ceil(self.resolution)
+    }
+
+    /// Saturates after ~584 years, avoiding the slow 128-bit division.
+    fn nanos(&self, time: Instant) -> u64 {
+        let nanos = time.saturating_duration_since(self.start).as_nanos();
+        u64::try_from(nanos).unwrap_or(u64::MAX)
+    }
+
+    fn instant(&self, tick: u64) -> Option<Instant> {
+        let nanos = tick.checked_mul(self.resolution)?;
+        self.start.checked_add(Duration::from_nanos(nanos))
+    }
+
+    fn link(&mut self, ptr: NonNull<Timer>) {
+        let links = &unsafe { ptr.as_ref() }.links;
+        let when = self.ticks(unsafe { ptr.as_ref() }.deadline.get());
+        if when <= self.elapsed {
+            self.expired.push(ptr);
+            links.slot.set(EXPIRED);
+            return;
+        }
+        if when == self.elapsed + 1 {
+            self.near.push(ptr);
+            links.slot.set(NEAR);
+            return;
+        }
+        let when = cmp::min(when, self.elapsed.saturating_add(MAX_TICKS));
+        let level = level_for(self.elapsed, when);
+        let slot = (when >> (level * SLOT_BITS)) as usize % SLOTS;
+
+        self.levels[level].slots[slot].push(ptr);
+        self.levels[level].occupied |= 1 << slot;
+        links.slot.set((level * SLOTS + slot) as u16);
+    }
+
+    /// Returns `false` if the timer is not in the wheel.
+    fn unlink(&mut self, ptr: NonNull<Timer>) -> bool {
+        match unsafe { ptr.as_ref() }.links.slot.get() {
+            UNLINKED => false,
+            EXPIRED => {
+                self.expired.unlink(ptr);
+                true
+            }
+            NEAR => {
+                self.near.unlink(ptr);
+                true
+            }
+            index => {
+                let (level, slot) = (index as usize / SLOTS, index as usize % SLOTS);
+                let list = &mut self.levels[level].slots[slot];
+                list.unlink(ptr);
+                if list.is_empty() {
+                    self.levels[level].occupied &= !(1 << slot);
+                }
+                true
+            }
+        }
+    }
+
+    /// Returns the earliest `(tick, level, slot)` of an occupied slot.
+    fn next_expiration(&self) -> Option<(u64, usize, usize)> {
+        let mut next: Option<(u64, usize, usize)> = None;
+        for (level, Level { occupied, .. }) in self.levels.iter().enumerate() {
+            if *occupied == 0 {
+                continue;
+            }
+            let slot_range = 1u64 << (level * SLOT_BITS);
+            let level_range = slot_range << SLOT_BITS;
+
+            let now_slot = (self.elapsed / slot_range) as usize % SLOTS;
+            let slot = (occupied.rotate_right(now_slot as u32).trailing_zeros() as usize
+                + now_slot)
+                % SLOTS;
+
+            let level_start = self.elapsed & !(level_range - 1);
+            let mut tick = level_start + slot as u64 * slot_range;
+            if tick < self.elapsed {
+                // The slot belongs to the next rotation of the level.
+                tick += level_range;
+            }
+            if next.is_none_or(|(next, ..)| tick < next) {
+                next = Some((tick, level, slot));
+            }
+        }
+        next
+    }
+}
+
+/// Returns the level of a timer firing at `when`, the highest group of
+/// [`SLOT_BITS`] bits that differs from `elapsed`.
+fn level_for(elapsed: u64, when: u64) -> usize {
+    let masked = (elapsed ^ when) | (SLOTS as u64 - 1);
+    let significant = 63 - masked.leading_zeros() as usize;
+    cmp::min(significant / SLOT_BITS, LEVELS - 1)
+}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        for list in [&mut self.expired, &mut self.near] {
+            drop(Expired {
+                list: mem::take(list),
+            });
+        }
+        for level in &mut self.levels {
+            for list in &mut level.slots {
+                drop(Expired {
+                    list: mem::take(list),
+                });
+            }
+        }
+    }
+}
+
+impl Expired {
+    pub fn notify_all(mut self) -> usize {
+        let mut count = 0;
+        while let Some(ptr) = self.list.pop() {
+            let entry = RcTimer::from_inner(ptr);
+            entry.as_ref().waker.wake();
+            count += 1;
+        }
+        count
+    }
+}
+
+impl Drop for Expired {
+    fn drop(&mut self) {
+        while let Some(ptr) = self.list.pop() {
+            drop(RcTimer::from_inner(ptr));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RES: Duration = Duration::from_millis(1);
+
+    fn is_elapsed(timer: &RcTimer) -> bool {
+        timer.as_ref().rc.get() == 1
+    }
+
+    #[test]
+    fn fire_in_order() {
+        let start = Instant::now();
+        let mut store = Store::new(start, RES);
+
+        // Spread over every level.
+        let timers: Vec<_> = (0..200u32)
+            .map(|i| {
+                let deadline = start + Duration::from_micros(7u64.pow(i % 12) + i as u64);
+                let (timer, other) = RcTimer::create(deadline);
+                store.insert(other);
+                timer
+            })
+            .collect();
+
+        let mut now = start;
+        while timers.iter().any(|timer| !is_elapsed(timer)) {
+            let next = store.next_deadline().unwrap();
+            assert!(next >= now);
+            now = next;
+            store.fetch(now).notify_all();
+
+            for timer in &timers {
+                let deadline = timer.deadline();
+                if is_elapsed(timer) {
+                    assert!(deadline <= now);
+                } else {
+                    assert!(deadline + RES > now);
+                }
+            }
+        }
+        assert!(store.next_deadline().is_none());
+    }
+
+    #[test]
+    fn cancel_and_reset() {
+        let start = Instant::now();
+        let mut store = Store::new(start, RES);
+
+        let (a, other) = RcTimer::create(start + Duration::from_secs(10));
+        store.insert(other);
+        let (b, other) = RcTimer::create(start + Duration::from_secs(20));
+        store.insert(other);
+
+        store.remove(&a);
+        assert!(is_elapsed(&a));
+        // Not in the wheel anymore.
+        store.remove(&a);
+
+        store.reset(&b, start + Duration::from_millis(5));
+        assert_eq!(
+            store.next_deadline(),
+            Some(start + Duration::from_millis(5))
+        );
+
+        store.fetch(start + Duration::from_millis(4)).notify_all();
+        assert!(!is_elapsed(&b));
+        store.fetch(start + Duration::from_millis(5)).notify_all();
+        assert!(is_elapsed(&b));
+        assert!(store.next_deadline().is_none());
+    }
+
+    #[test]
+    fn near_deadline() {
+        let start = Instant::now();
+        let mut store = Store::new(start, RES);
+
+        let (timer, other) = RcTimer::create(start + Duration::from_micros(10));
+        store.insert(other);
+        assert_eq!(
+            store.next_deadline(),
+            Some(start + Duration::from_micros(10))
+        );
+
+        store.fetch(start + Duration::from_micros(5)).notify_all();
+        assert!(!is_elapsed(&timer));
+        // Fires without waiting for the end of the tick.
+        store.fetch(start + Duration::from_micros(10)).notify_all();
+        assert!(is_elapsed(&timer));
+    }
+
+    #[test]
+    fn drop_store() {
+        let start = Instant::now();
+        let mut store = Store::new(start, RES);
+        let (timer, other) = RcTimer::create(start + Duration::from_secs(1));
+        store.insert(other);
+        drop(store);
+        assert!(is_elapsed(&timer));
+    }
+}
@@ -86,8 +86,8 @@ async fn set() {
 #[test]
 async fn test_timeout() {
     let timer = timeout(Duration::from_secs(1), sleep(Duration::from_secs(2)));
-    assert!(timer.await.is_none());
+    assert!(timer.await.is_err());
 
     let timer = timeout(Duration::from_secs(2), sleep(Duration::from_secs(1)));
-    assert!(timer.await.is_some());
+    assert!(timer.await.is_ok());
 }
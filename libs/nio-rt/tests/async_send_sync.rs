@@ -238,6 +238,8 @@ async_assert_fn!(nio::Timeout::at(Instant, BoxFutureSync<()>): !Send & !Sync & U
 async_assert_fn!(nio::Timeout::at(Instant, BoxFutureSend<()>): !Send & !Sync & Unpin);
 async_assert_fn!(nio::Timeout::at(Instant, BoxFuture<()>): !Send & !Sync & Unpin);
 async_assert_fn!(nio::Interval::tick(_): !Send & !Sync & Unpin);
+assert_value!(nio::time::DelayQueue<u8>: !Send & !Sync & Unpin);
+async_assert_fn!(nio::time::DelayQueue<u8>::next_expired(_): !Send & !Sync & Unpin);
 
 assert_value!(nio::LocalContext: !Send & !Sync & Unpin);
 
@@ -0,0 +1,60 @@
+#![cfg(not(miri))]
+
+use futures::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use nio::{fs::File, test};
+use std::io::SeekFrom;
+
+const HELLO: &[u8] = b"hello world...";
+
+fn tempfile(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("nio-{name}-{}", std::process::id()))
+}
+
+#[test]
+async fn basic_write_read() {
+    let path = tempfile("write-read");
+
+    let mut file = File::create(&path).await.unwrap();
+    file.write_all(HELLO).await.unwrap();
+    file.flush().await.unwrap();
+
+    let mut file = File::open(&path).await.unwrap();
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, HELLO);
+
+    nio::fs::remove_file(path).await.unwrap();
+}
+
+#[test]
+async fn seek_and_set_len() {
+    let path = tempfile("seek");
+
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await
+        .unwrap();
+
+    file.write_all(HELLO).await.unwrap();
+    assert_eq!(file.seek(SeekFrom::Start(6)).await.unwrap(), 6);
+
+    let mut buf = [0; 5];
+    file.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"world");
+
+    file.set_len(5).await.unwrap();
+    file.sync_all().await.unwrap();
+    assert_eq!(file.metadata().await.unwrap().len(), 5);
+
+    let clone = file.try_clone().await.unwrap();
+    assert_eq!(clone.metadata().await.unwrap().len(), 5);
+    drop(clone);
+
+    let std = file.into_std().await;
+    assert_eq!(std.metadata().unwrap().len(), 5);
+
+    nio::fs::remove_file(path).await.unwrap();
+}
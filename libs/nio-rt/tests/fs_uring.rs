@@ -0,0 +1,60 @@
+#![cfg(all(feature = "io-uring", target_os = "linux", not(miri)))]
+
+use futures::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use nio::{fs, fs::File, test};
+use std::io::SeekFrom;
+
+fn tempfile(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("nio-uring-{name}-{}", std::process::id()))
+}
+
+fn contents(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+async fn read_write() {
+    let path = tempfile("read-write");
+    // Larger than a single read of `fs::read`.
+    let data = contents(100 * 1024);
+
+    fs::write(&path, &data).await.unwrap();
+    assert_eq!(fs::read(&path).await.unwrap(), data);
+
+    fs::write(&path, b"short").await.unwrap();
+    assert_eq!(fs::read(&path).await.unwrap(), b"short");
+
+    fs::remove_file(&path).await.unwrap();
+    let err = fs::read(&path).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+async fn file_position() {
+    let path = tempfile("position");
+    let data = contents(64 * 1024);
+
+    let mut file = File::options()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .await
+        .unwrap();
+
+    file.write_all(&data).await.unwrap();
+    file.flush().await.unwrap();
+    assert_eq!(file.stream_position().await.unwrap(), data.len() as u64);
+
+    file.seek(SeekFrom::Start(1000)).await.unwrap();
+    let mut buf = vec![0; 2000];
+    file.read_exact(&mut buf).await.unwrap();
+    assert_eq!(buf, data[1000..3000]);
+
+    file.seek(SeekFrom::Start(0)).await.unwrap();
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, data);
+
+    fs::remove_file(path).await.unwrap();
+}
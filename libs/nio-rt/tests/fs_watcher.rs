@@ -0,0 +1,154 @@
+#![cfg(all(any(target_os = "linux", target_os = "android"), not(miri)))]
+
+use futures::StreamExt;
+use nio::{
+    fs::{Event, Watcher},
+    test,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+fn tempdir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nio-watch-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir(&path).unwrap();
+    path
+}
+
+async fn next(watcher: &mut Watcher) -> Event {
+    nio::timeout(Duration::from_secs(5), watcher.next_event())
+        .await
+        .expect("timed out")
+        .unwrap()
+}
+
+#[test]
+async fn create_modify_remove() {
+    let dir = tempdir("basic");
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch(&dir).unwrap();
+
+    fs::write(dir.join("a"), "hello").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("a")));
+
+    nio::sleep(Duration::from_millis(50)).await;
+    fs::write(dir.join("a"), "world").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Modify(dir.join("a")));
+
+    fs::remove_file(dir.join("a")).unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Remove(dir.join("a")));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+async fn rename() {
+    let dir = tempdir("rename");
+    let outside = tempdir("rename-outside");
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch(&dir).unwrap();
+
+    fs::write(outside.join("a"), "").unwrap();
+    fs::rename(outside.join("a"), dir.join("a")).unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("a")));
+
+    fs::rename(dir.join("a"), dir.join("b")).unwrap();
+    let event = next(&mut watcher).await;
+    assert_eq!(
+        event,
+        Event::Rename {
+            from: dir.join("a"),
+            to: dir.join("b")
+        }
+    );
+
+    fs::rename(dir.join("b"), outside.join("b")).unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Remove(dir.join("b")));
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
+
+#[test]
+async fn recursive() {
+    let dir = tempdir("recursive");
+    fs::create_dir(dir.join("x")).unwrap();
+
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch_recursive(&dir).unwrap();
+
+    fs::write(dir.join("x/a"), "").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("x/a")));
+
+    fs::create_dir(dir.join("y")).unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("y")));
+    fs::write(dir.join("y/b"), "").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("y/b")));
+
+    // Paths of watched subdirectories follow renames.
+    fs::rename(dir.join("y"), dir.join("z")).unwrap();
+    let event = next(&mut watcher).await;
+    assert_eq!(event.path(), dir.join("z"));
+    fs::write(dir.join("z/c"), "").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("z/c")));
+
+    watcher.unwatch(&dir).unwrap();
+    assert!(watcher.unwatch(&dir).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+async fn rename_recursive() {
+    let dir = tempdir("rename-recursive");
+    let outside = tempdir("rename-recursive-outside");
+    fs::create_dir_all(dir.join("x/y")).unwrap();
+
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch_recursive(&dir).unwrap();
+
+    fs::rename(dir.join("x"), dir.join("w")).unwrap();
+    assert_eq!(
+        next(&mut watcher).await,
+        Event::Rename {
+            from: dir.join("x"),
+            to: dir.join("w")
+        }
+    );
+    // Nested watches follow the rename.
+    fs::write(dir.join("w/y/a"), "").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("w/y/a")));
+
+    // Moved out after the grace period, its entries are no longer watched.
+    fs::rename(dir.join("w"), outside.join("w")).unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Remove(dir.join("w")));
+    fs::write(outside.join("w/y/b"), "").unwrap();
+    fs::write(dir.join("c"), "").unwrap();
+    assert_eq!(next(&mut watcher).await, Event::Create(dir.join("c")));
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
+
+#[test]
+async fn debounce() {
+    let dir = tempdir("debounce");
+    let mut watcher = Watcher::new().unwrap();
+    watcher.watch(&dir).unwrap();
+    watcher.set_debounce(Duration::from_millis(100));
+
+    // Coalesced into a single event.
+    fs::write(dir.join("a"), "1").unwrap();
+    fs::write(dir.join("a"), "2").unwrap();
+    fs::write(dir.join("a"), "3").unwrap();
+    // Never reported.
+    fs::write(dir.join("tmp"), "").unwrap();
+    fs::remove_file(dir.join("tmp")).unwrap();
+    fs::write(dir.join("b"), "").unwrap();
+
+    let events: Vec<_> = (&mut watcher).take(2).map(Result::unwrap).collect().await;
+    assert_eq!(
+        events,
+        [Event::Create(dir.join("a")), Event::Create(dir.join("b"))]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
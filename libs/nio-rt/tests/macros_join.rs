@@ -0,0 +1,62 @@
+use nio::test;
+use nio_future::yield_now;
+use std::{cell::RefCell, time::Duration};
+
+#[test]
+async fn join_returns_all_outputs() {
+    let (a, b, c) = nio::join!(async { 1 }, async { "two" }, std::future::ready(3.0));
+    assert_eq!((a, b, c), (1, "two", 3.0));
+    assert_eq!(nio::join!(), ());
+}
+
+#[test(start_paused = true)]
+async fn join_polls_concurrently() {
+    let start = nio::sleep(Duration::ZERO).deadline();
+    nio::join!(
+        nio::sleep(Duration::from_secs(1)),
+        nio::sleep(Duration::from_secs(1)),
+    );
+    let end = nio::sleep(Duration::ZERO).deadline();
+    assert_eq!(end - start, Duration::from_secs(1));
+}
+
+#[test]
+async fn join_interleaves_futures() {
+    let log = RefCell::new(Vec::new());
+    let task = |id| {
+        let log = &log;
+        async move {
+            for _ in 0..2 {
+                log.borrow_mut().push(id);
+                yield_now().await;
+            }
+        }
+    };
+    nio::join!(task(0), task(1));
+    let log = log.into_inner();
+    assert_eq!(log.len(), 4);
+    assert_ne!(log, [0, 0, 1, 1]);
+    assert_ne!(log, [1, 1, 0, 0]);
+}
+
+#[test]
+async fn try_join_ok() {
+    let result: Result<_, ()> = nio::try_join!(async { Ok(1) }, async { Ok("two") });
+    assert_eq!(result, Ok((1, "two")));
+    assert_eq!(nio::try_join!() as Result<(), ()>, Ok(()));
+}
+
+#[test]
+async fn try_join_returns_first_error() {
+    let mut finished = false;
+    let result = nio::try_join!(
+        async {
+            nio::sleep(Duration::from_secs(60)).await;
+            finished = true;
+            Ok(1)
+        },
+        async { Err::<(), _>("boom") },
+    );
+    assert_eq!(result, Err("boom"));
+    assert!(!finished);
+}
@@ -0,0 +1,151 @@
+use futures::channel::oneshot;
+use nio::test;
+use std::{
+    future::{pending, ready},
+    time::Duration,
+};
+
+#[test]
+async fn first_ready_branch_wins() {
+    let (tx, rx) = oneshot::channel();
+    nio::spawn_local(async move {
+        nio::sleep(Duration::from_millis(10)).await;
+        tx.send(1).unwrap();
+    });
+    let value = nio::select! {
+        v = rx => v.unwrap(),
+        _ = pending::<()>() => unreachable!(),
+    };
+    assert_eq!(value, 1);
+}
+
+#[test]
+async fn biased_polls_in_order() {
+    for _ in 0..100 {
+        let branch = nio::select! {
+            biased;
+            _ = ready(()) => 0,
+            _ = ready(()) => 1,
+        };
+        assert_eq!(branch, 0);
+    }
+}
+
+#[test]
+async fn unbiased_start_is_random() {
+    let mut seen = [false; 2];
+    for _ in 0..100 {
+        let branch = nio::select! {
+            _ = ready(()) => 0,
+            _ = ready(()) => 1,
+        };
+        seen[branch] = true;
+    }
+    assert_eq!(seen, [true, true]);
+}
+
+#[test]
+async fn precondition_disables_branch() {
+    let enabled = false;
+    let value = nio::select! {
+        v = ready(1), if enabled => v,
+        v = ready(2) => v,
+    };
+    assert_eq!(value, 2);
+}
+
+#[test]
+async fn pattern_mismatch_disables_branch() {
+    let (tx, rx) = oneshot::channel();
+    nio::spawn_local(async move {
+        nio::sleep(Duration::from_millis(10)).await;
+        tx.send(3).unwrap();
+    });
+    let value = nio::select! {
+        biased;
+        Some(v) = ready(None::<i32>) => v,
+        Ok(v) = rx => v,
+    };
+    assert_eq!(value, 3);
+}
+
+#[test]
+async fn else_when_all_disabled() {
+    let value = nio::select! {
+        Some(v) = ready(None) => v,
+        v = ready(1), if false => v,
+        else => 0,
+    };
+    assert_eq!(value, 0);
+}
+
+#[test]
+#[should_panic = "all branches are disabled and there is no else branch"]
+async fn panics_without_else() {
+    nio::select! {
+        Some(v) = ready(None::<()>) => v,
+    }
+}
+
+#[test]
+async fn loop_with_block_handlers() {
+    let mut count = 0;
+    let mut interval = nio::interval(Duration::from_millis(1));
+    let timeout = nio::sleep(Duration::from_millis(500));
+    let mut timeout = std::pin::pin!(timeout);
+    loop {
+        nio::select! {
+            _ = interval.tick() => {
+                count += 1;
+                if count == 3 {
+                    break;
+                }
+            }
+            _ = &mut timeout => panic!("timed out"),
+        }
+    }
+    assert_eq!(count, 3);
+}
+
+#[test]
+async fn futures_are_dropped_before_handler() {
+    let mut buf = Vec::new();
+    nio::select! {
+        _ = async { buf.push(1) } => buf.push(2),
+    }
+    assert_eq!(buf, [1, 2]);
+}
+
+#[test]
+async fn mut_binding_by_value() {
+    let value = nio::select! {
+        Some(mut x) = ready(Some(vec![1])) => {
+            x.push(2);
+            x
+        }
+    };
+    assert_eq!(value, [1, 2]);
+}
+
+#[test]
+async fn non_copy_payload() {
+    let value = nio::select! {
+        biased;
+        Ok(name) = ready(Err::<String, _>(())) => name,
+        (Some(name), ref tag) = ready((Some(String::from("nio")), String::from("rt"))) => {
+            format!("{name}-{tag}")
+        }
+    };
+    assert_eq!(value, "nio-rt");
+}
+
+#[test]
+async fn constant_in_pattern() {
+    const ZERO: i32 = 0;
+    let value = nio::select! {
+        biased;
+        (ZERO, x) = ready((1, 1)) => x,
+        (ZERO, x) | (x, ZERO) = ready((2, 0)) => x,
+    };
+    assert_eq!(value, 2);
+}
@@ -9,6 +9,61 @@ async fn test_macro_can_be_used_via_use() {
     nio::spawn(async {}).await.unwrap();
 }
 
+#[test(flavor = "multi", worker_threads = 2)]
+async fn test_multi_flavor() {
+    let name = std::thread::current().name().map(str::to_owned);
+    assert_eq!(name.as_deref(), Some("Worker: 0"));
+}
+
+#[test(flavor = "local")]
+async fn test_local_flavor() {
+    // Runs on the test thread, not on a worker thread.
+    let name = std::thread::current().name().map(str::to_owned);
+    assert!(!name.unwrap_or_default().starts_with("Worker: "));
+}
+
+#[derive(Debug)]
+struct Metrics;
+
+impl nio::metrics::Measurement for Metrics {}
+
+#[test(
+    worker_threads = 3,
+    event_interval = 31,
+    max_blocking_threads = 1,
+    start_paused = true,
+    measurement = Metrics
+)]
+async fn test_builder_options() {
+    let start = std::time::Instant::now();
+    nio::sleep(std::time::Duration::from_secs(60)).await;
+    assert!(start.elapsed() < std::time::Duration::from_secs(60));
+
+    let threads: Vec<_> = (0..3)
+        .map(|worker| nio::spawn_pinned_at(worker, || async { std::thread::current().id() }))
+        .collect();
+    let mut ids = std::collections::HashSet::new();
+    for thread in threads {
+        ids.insert(thread.await.unwrap());
+    }
+    assert_eq!(ids.len(), 3);
+
+    let metrics = nio::RuntimeContext::current().metrics();
+    assert_eq!(metrics.blocking_max_threads(), 1);
+    #[cfg(feature = "metrics")]
+    assert!(metrics.measurement_as::<Metrics>().is_some());
+}
+
+// Other `RuntimeBuilder` methods are passed through.
+#[test(
+    min_tasks_per_worker = 4,
+    thread_timeout(None),
+    timer_resolution = std::time::Duration::from_millis(2)
+)]
+async fn test_builder_methods() {
+    nio::sleep(std::time::Duration::from_millis(1)).await;
+}
+
 // #[nio::test]
 // async fn test_macro_is_resilient_to_shadowing() {
 //     nio::spawn(async {}).await.unwrap();
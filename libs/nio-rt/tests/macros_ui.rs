@@ -0,0 +1,9 @@
+#![cfg(not(miri))]
+
+/// Errors of `#[nio::main]` and `#[nio::test]` arguments, regenerate the
+/// expected output with `TRYBUILD=overwrite`.
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
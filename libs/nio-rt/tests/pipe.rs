@@ -0,0 +1,177 @@
+#![cfg(all(unix, not(miri)))]
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use nio::{pipe, test};
+use std::{
+    fs::File,
+    io::{ErrorKind, Read, Write},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn mkfifo(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("nio-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert!(
+        Command::new("mkfifo")
+            .arg(&path)
+            .status()
+            .unwrap()
+            .success()
+    );
+    path
+}
+
+#[test]
+async fn anonymous() {
+    let (mut tx, mut rx) = pipe::pipe().unwrap();
+
+    tx.write_all(b"hello world").await.unwrap();
+    drop(tx);
+
+    let mut out = String::new();
+    rx.read_to_string(&mut out).await.unwrap();
+    assert_eq!(out, "hello world");
+}
+
+#[test]
+async fn fifo() {
+    let path = mkfifo("fifo");
+
+    let mut rx = pipe::OpenOptions::new().open_receiver(&path).unwrap();
+    let mut tx = pipe::OpenOptions::new().open_sender(&path).unwrap();
+
+    let task = nio::spawn_local(async move {
+        let mut buf = vec![0; 1024 * 1024];
+        rx.read_exact(&mut buf).await.unwrap();
+        buf
+    });
+
+    let data = vec![7; 1024 * 1024];
+    tx.write_all(&data).await.unwrap();
+    assert_eq!(task.await.unwrap(), data);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+async fn fifo_without_reader() {
+    let path = mkfifo("no-reader");
+
+    let err = pipe::OpenOptions::new().open_sender(&path).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(6)); // ENXIO
+
+    #[cfg(target_os = "linux")]
+    pipe::OpenOptions::new()
+        .read_write(true)
+        .open_sender(&path)
+        .unwrap();
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+async fn not_a_fifo() {
+    let path = std::env::temp_dir().join(format!("nio-file-{}", std::process::id()));
+    std::fs::write(&path, b"").unwrap();
+
+    let err = pipe::OpenOptions::new().open_receiver(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    // Regular files can't be registered with the reactor.
+    pipe::OpenOptions::new()
+        .unchecked(true)
+        .open_receiver(&path)
+        .unwrap_err();
+
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Runs `test` in a child process, which copies its stdin to its stdout.
+fn echo_child(test: &str, stdin: Stdio, stdout: Stdio) -> std::process::Child {
+    Command::new(std::env::current_exe().unwrap())
+        .args([test, "--exact", "--nocapture", "--test-threads=1"])
+        .env("NIO_ECHO_CHILD", "1")
+        .stdin(stdin)
+        .stdout(stdout)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap()
+}
+
+async fn echo(is_async: bool) {
+    let mut stdin = nio::io::stdin();
+    let mut stdout = nio::io::stdout();
+    assert_eq!(stdin.is_async(), is_async);
+    assert_eq!(stdout.is_async(), is_async);
+
+    let mut data = Vec::new();
+    stdin.read_to_end(&mut data).await.unwrap();
+    stdout.write_all(&data).await.unwrap();
+    stdout.flush().await.unwrap();
+}
+
+/// The test harness also writes to stdout, around the echoed data.
+fn assert_echoed(output: &Output, out: &[u8], data: &[u8]) {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        (0..=out.len().saturating_sub(data.len())).any(|i| out[i..].starts_with(data)),
+        "data was not echoed"
+    );
+}
+
+fn payload() -> Vec<u8> {
+    (0..1024 * 1024).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+async fn stdio_pipe() {
+    if std::env::var_os("NIO_ECHO_CHILD").is_some() {
+        return echo(true).await;
+    }
+    let mut child = echo_child("stdio_pipe", Stdio::piped(), Stdio::piped());
+
+    // Larger than the pipe buffer, so both ends have to wait for readiness.
+    let data = payload();
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = std::thread::spawn({
+        let data = data.clone();
+        move || stdin.write_all(&data).unwrap()
+    });
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+
+    assert_echoed(&output, &output.stdout, &data);
+}
+
+#[test]
+async fn stdio_file() {
+    if std::env::var_os("NIO_ECHO_CHILD").is_some() {
+        return echo(false).await;
+    }
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("nio-stdin-{}", std::process::id()));
+    let output = dir.join(format!("nio-stdout-{}", std::process::id()));
+
+    let data = payload();
+    std::fs::write(&input, &data).unwrap();
+
+    // Regular files can't be registered with the reactor.
+    let child = echo_child(
+        "stdio_file",
+        File::open(&input).unwrap().into(),
+        File::create(&output).unwrap().into(),
+    );
+    let status = child.wait_with_output().unwrap();
+
+    let mut out = Vec::new();
+    File::open(&output).unwrap().read_to_end(&mut out).unwrap();
+    assert_echoed(&status, &out, &data);
+
+    std::fs::remove_file(input).unwrap();
+    std::fs::remove_file(output).unwrap();
+}
@@ -0,0 +1,82 @@
+#![cfg(all(unix, not(miri)))]
+
+use futures::{AsyncReadExt, AsyncWriteExt};
+use nio::{process::Command, test};
+use std::{process::Stdio, time::Duration};
+
+#[test]
+async fn output() {
+    let output = Command::new("echo").arg("hello").output().await.unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello\n");
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+async fn status() {
+    let status = Command::new("sh").args(["-c", "exit 3"]).status().await;
+    assert_eq!(status.unwrap().code(), Some(3));
+}
+
+#[test]
+async fn piped_stdin_stdout() {
+    let mut child = Command::new("cat")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"foo bar baz").await.unwrap();
+    drop(stdin);
+
+    let mut out = String::new();
+    let mut stdout = child.stdout.take().unwrap();
+    stdout.read_to_string(&mut out).await.unwrap();
+    assert_eq!(out, "foo bar baz");
+
+    assert!(child.wait().await.unwrap().success());
+}
+
+#[test]
+async fn kill() {
+    let mut child = Command::new("sleep").arg("60").spawn().unwrap();
+    assert!(child.try_wait().unwrap().is_none());
+
+    child.kill().await.unwrap();
+
+    let status = child.wait().await.unwrap();
+    assert!(!status.success());
+}
+
+#[test]
+async fn kill_on_drop() {
+    let mut child = Command::new("sleep")
+        .arg("60")
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .unwrap();
+
+    let mut out = child.stdout.take().unwrap();
+    drop(child);
+
+    let mut buf = String::new();
+    let read = nio::timeout(Duration::from_secs(1), out.read_to_string(&mut buf)).await;
+    assert_eq!(read.unwrap().unwrap(), 0);
+}
+
+#[test]
+async fn wait_many() {
+    let mut children = Vec::new();
+    for i in 0..8 {
+        let child = Command::new("sh")
+            .args(["-c", &format!("exit {i}")])
+            .spawn()
+            .unwrap();
+        children.push(child);
+    }
+    for (i, mut child) in children.into_iter().enumerate().rev() {
+        assert_eq!(child.wait().await.unwrap().code(), Some(i as i32));
+    }
+}
@@ -0,0 +1,166 @@
+#![cfg(not(miri))]
+
+use nio::{RuntimeBuilder, RuntimeConfig};
+use std::{io::ErrorKind, time::Duration};
+
+const VARS: [&str; 5] = [
+    "NIO_WORKER_THREADS",
+    "NIO_EVENT_INTERVAL",
+    "NIO_MIN_TASKS_PER_WORKER",
+    "NIO_MAX_BLOCKING_THREADS",
+    "NIO_THREAD_TIMEOUT",
+];
+
+// Every access to the environment is in this test, as other threads may read it.
+#[test]
+fn from_env() {
+    let set = |vars: &[(&str, &str)]| {
+        for key in VARS {
+            unsafe { std::env::remove_var(key) };
+        }
+        for (key, value) in vars {
+            unsafe { std::env::set_var(key, value) };
+        }
+    };
+
+    set(&[]);
+    assert_eq!(RuntimeConfig::from_env().unwrap(), RuntimeConfig::default());
+
+    set(&[
+        ("NIO_WORKER_THREADS", "2"),
+        ("NIO_EVENT_INTERVAL", "31"),
+        ("NIO_MIN_TASKS_PER_WORKER", "4"),
+        ("NIO_MAX_BLOCKING_THREADS", "16"),
+        ("NIO_THREAD_TIMEOUT", "500ms"),
+    ]);
+    assert_eq!(
+        RuntimeConfig::from_env().unwrap(),
+        RuntimeConfig {
+            worker_threads: Some(2),
+            event_interval: Some(31),
+            min_tasks_per_worker: Some(4),
+            max_blocking_threads: Some(16),
+            thread_timeout: Some(Some(Duration::from_millis(500))),
+        }
+    );
+    let rt = RuntimeBuilder::from_env().unwrap().rt().unwrap();
+    assert_eq!(rt.context().metrics().num_workers(), 2);
+    assert_eq!(rt.context().metrics().blocking_max_threads(), 16);
+
+    set(&[("NIO_THREAD_TIMEOUT", "none")]);
+    assert_eq!(
+        RuntimeConfig::from_env().unwrap().thread_timeout,
+        Some(None)
+    );
+
+    set(&[("NIO_WORKER_THREADS", "300")]);
+    let err = RuntimeConfig::from_env().unwrap_err();
+    assert_eq!(err.key(), "NIO_WORKER_THREADS");
+    assert!(err.to_string().contains("\"300\""), "{err}");
+
+    set(&[("NIO_THREAD_TIMEOUT", "soon")]);
+    assert_eq!(
+        RuntimeConfig::from_env().unwrap_err().key(),
+        "NIO_THREAD_TIMEOUT"
+    );
+
+    set(&[("NIO_WORKER_THREADS", "0")]);
+    let err = RuntimeBuilder::from_env().err().unwrap();
+    assert_eq!(err.key(), "worker_threads");
+    assert_eq!(
+        err.to_string(),
+        "invalid `worker_threads`: must be at least 1"
+    );
+
+    set(&[("NIO_MAX_BLOCKING_THREADS", "0")]);
+    let err = RuntimeBuilder::from_env().err().unwrap();
+    assert_eq!(err.key(), "max_blocking_threads");
+
+    set(&[]);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn from_toml() {
+    let config: RuntimeConfig = toml::from_str(
+        r#"
+        worker_threads = 2
+        max_blocking_threads = 8
+        thread_timeout = "2s"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        RuntimeConfig {
+            worker_threads: Some(2),
+            max_blocking_threads: Some(8),
+            thread_timeout: Some(Some(Duration::from_secs(2))),
+            ..RuntimeConfig::default()
+        }
+    );
+    let rt = RuntimeBuilder::new().config(&config).rt().unwrap();
+    assert_eq!(rt.context().metrics().num_workers(), 2);
+
+    let config: RuntimeConfig = toml::from_str("thread_timeout = 10").unwrap();
+    assert_eq!(config.thread_timeout, Some(Some(Duration::from_secs(10))));
+    let config: RuntimeConfig = toml::from_str("thread_timeout = \"none\"").unwrap();
+    assert_eq!(config.thread_timeout, Some(None));
+
+    assert!(toml::from_str::<RuntimeConfig>("thread_timeout = \"10h\"").is_err());
+    assert!(toml::from_str::<RuntimeConfig>("worker_thread = 2").is_err());
+
+    let config: RuntimeConfig = toml::from_str("max_blocking_threads = 0").unwrap();
+    let err = RuntimeBuilder::new().config(&config).rt().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid `max_blocking_threads`: must be at least 1"
+    );
+}
+
+#[test]
+fn invalid_settings_are_errors() {
+    let err = |builder: RuntimeBuilder| {
+        let err = builder.validate().unwrap_err();
+        assert_eq!(builder.rt().err().unwrap().kind(), ErrorKind::InvalidInput);
+        err.key()
+    };
+    assert_eq!(
+        err(RuntimeBuilder::new().worker_threads(0)),
+        "worker_threads"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().event_interval(0)),
+        "event_interval"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().min_tasks_per_worker(0)),
+        "min_tasks_per_worker"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().max_blocking_threads(0)),
+        "max_blocking_threads"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().threadpool_load_factor(0)),
+        "threadpool_load_factor"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().timer_resolution(Duration::ZERO)),
+        "timer_resolution"
+    );
+    assert_eq!(
+        err(RuntimeBuilder::new().watchdog(Duration::ZERO)),
+        "watchdog"
+    );
+
+    let err = RuntimeBuilder::new()
+        .worker_threads(0)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid `worker_threads`: must be at least 1"
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(all(feature = "task-dump", not(miri)))]
+
+use nio::{
+    RuntimeBuilder, RuntimeContext,
+    dump::TaskState,
+    metrics::SpawnKind,
+    net::{TcpListener, TcpStream},
+};
+use nio_future::yield_now;
+use std::time::Duration;
+
+#[test]
+fn dump_live_tasks() {
+    let rt = RuntimeBuilder::new().worker_threads(2).rt().unwrap();
+
+    rt.block_on(|| async {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        let _server = listener.accept().await.unwrap();
+
+        let reader = nio::spawn_local(async move {
+            let mut buf = [0; 8];
+            client.read(&mut buf).await
+        });
+        let sleeper = nio::spawn_pinned_at(1, || nio::sleep(Duration::from_secs(60)));
+        let line = line!() - 1;
+        let ready = nio::spawn(async {});
+        ready.await.unwrap();
+        // Let the tasks run until they are pending.
+        for _ in 0..10 {
+            yield_now().await;
+        }
+        nio::sleep(Duration::from_millis(20)).await;
+
+        let dump = RuntimeContext::current().dump().await;
+        assert_eq!(dump.tasks.len(), 2, "{dump}");
+
+        let reader_dump = dump.tasks.iter().find(|t| t.id == reader.id()).unwrap();
+        assert_eq!(reader_dump.kind, SpawnKind::Local);
+        assert_eq!(reader_dump.worker.get(), 0);
+        assert_eq!(reader_dump.state, TaskState::Sleep);
+        assert_eq!(reader_dump.polls, 1);
+        let (op, resource) = reader_dump.awaiting.unwrap();
+        assert_eq!(op, "read");
+        assert!(resource.contains("TcpStream"), "{resource}");
+        assert!(reader_dump.name.contains("dump_live_tasks"));
+
+        let sleeper_dump = dump.tasks.iter().find(|t| t.id == sleeper.id()).unwrap();
+        assert_eq!(sleeper_dump.kind, SpawnKind::Pinned);
+        assert_eq!(sleeper_dump.worker.get(), 1);
+        assert_eq!(sleeper_dump.awaiting, Some(("sleep", "nio::time::Sleep")));
+        assert_eq!(sleeper_dump.location.file(), file!());
+        assert_eq!(sleeper_dump.location.line(), line);
+
+        let text = dump.to_string();
+        assert!(text.starts_with("2 live tasks\n"), "{text}");
+        assert!(text.contains("awaiting read on mio::net::tcp::stream::TcpStream"));
+
+        reader.abort();
+        sleeper.abort();
+        assert!(reader.await.unwrap_err().is_cancelled());
+        assert!(sleeper.await.unwrap_err().is_cancelled());
+        assert!(RuntimeContext::current().dump().await.tasks.is_empty());
+    });
+}
+
+#[test]
+fn dump_completes_while_a_worker_is_blocked() {
+    let rt = RuntimeBuilder::new().worker_threads(2).rt().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+
+    rt.block_on(|| async move {
+        let blocked = nio::spawn_pinned_at(1, move || async move { rx.recv().unwrap() });
+        nio::sleep(Duration::from_millis(20)).await;
+
+        let dump = RuntimeContext::current().dump().await;
+        let task = dump.tasks.iter().find(|t| t.id == blocked.id()).unwrap();
+        assert_eq!(task.state, TaskState::Running);
+        assert_eq!(task.worker.get(), 1);
+
+        tx.send(()).unwrap();
+        blocked.await.unwrap();
+    });
+}
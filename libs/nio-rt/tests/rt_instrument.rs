@@ -0,0 +1,115 @@
+#![cfg(all(feature = "metrics", not(miri)))]
+
+use nio::{
+    RuntimeBuilder, TaskId, WorkerId,
+    metrics::{Instrument, SpawnKind},
+};
+use nio_future::yield_now;
+use std::sync::{Arc, Mutex};
+
+mod support {
+    pub mod wakeups;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    Spawn(u8, SpawnKind),
+    PollStart(u8),
+    PollEnd(u8),
+    Wake(Option<u8>, u8),
+    Terminate(u8),
+}
+
+#[derive(Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<(TaskId, Event)>>>);
+
+impl Recorder {
+    fn events(&self, task: TaskId) -> Vec<Event> {
+        let events = self.0.lock().unwrap();
+        events
+            .iter()
+            .filter(|(id, _)| *id == task)
+            .map(|(_, event)| *event)
+            .collect()
+    }
+
+    fn record(&self, task: TaskId, event: Event) {
+        self.0.lock().unwrap().push((task, event));
+    }
+}
+
+impl Instrument for Recorder {
+    fn on_task_spawn(&self, task: TaskId, worker: WorkerId, kind: SpawnKind) {
+        self.record(task, Event::Spawn(worker.get() as u8, kind));
+    }
+
+    fn on_task_poll_start(&self, task: TaskId, worker: WorkerId) {
+        self.record(task, Event::PollStart(worker.get() as u8));
+    }
+
+    fn on_task_poll_end(&self, task: TaskId, worker: WorkerId) {
+        self.record(task, Event::PollEnd(worker.get() as u8));
+    }
+
+    fn on_task_wake(&self, task: TaskId, from: Option<WorkerId>, to: WorkerId) {
+        let from = from.map(|worker| worker.get() as u8);
+        self.record(task, Event::Wake(from, to.get() as u8));
+    }
+
+    fn on_task_terminate(&self, task: TaskId, worker: WorkerId) {
+        self.record(task, Event::Terminate(worker.get() as u8));
+    }
+}
+
+#[test]
+fn task_lifecycle() {
+    let recorder = Recorder::default();
+    let rt = RuntimeBuilder::new()
+        .worker_threads(2)
+        .instrument(recorder.clone())
+        .rt()
+        .unwrap();
+
+    let task = rt.block_on(|| async {
+        let task = nio::spawn_pinned_at(1, || async { yield_now().await });
+        let id = task.id();
+        task.await.unwrap();
+        id
+    });
+
+    use Event::*;
+    assert_eq!(
+        recorder.events(task),
+        [
+            Spawn(1, SpawnKind::Pinned),
+            PollStart(1),
+            PollEnd(1),
+            Wake(Some(1), 1),
+            PollStart(1),
+            PollEnd(1),
+            Terminate(1),
+        ]
+    );
+}
+
+#[test]
+fn cross_worker_wakeups() {
+    let recorder = Recorder::default();
+    let rt = RuntimeBuilder::new()
+        .worker_threads(2)
+        .instrument(recorder.clone())
+        .rt()
+        .unwrap();
+
+    let (waiter, blocking_waiter) = rt.block_on(support::wakeups::cross_worker);
+
+    let wakes = |task| {
+        let events = recorder.events(task);
+        events
+            .into_iter()
+            .filter(|event| matches!(event, Event::Wake(..)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(wakes(waiter), [Event::Wake(Some(0), 1)]);
+    assert_eq!(wakes(blocking_waiter), [Event::Wake(None, 1)]);
+}
@@ -0,0 +1,293 @@
+#![cfg(all(feature = "metrics", not(miri)))]
+
+use nio::{
+    RuntimeBuilder,
+    metrics::{HistogramConfig, SimpleMeasurement, SpawnKind, export},
+    net::{TcpListener, TcpStream},
+};
+use nio_future::yield_now;
+use std::time::Duration;
+
+mod support {
+    pub mod wakeups;
+}
+
+#[test]
+fn worker_counters() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(1)
+        .measurement(SimpleMeasurement::new())
+        .rt()
+        .unwrap();
+
+    rt.block_on(|| async {
+        nio::spawn(async {}).await.unwrap();
+        nio::spawn_pinned(|| async {}).await.unwrap();
+        nio::spawn_local(async {
+            yield_now().await;
+        })
+        .await
+        .unwrap();
+        nio::spawn_blocking(|| {}).await.unwrap();
+        nio::sleep(Duration::from_millis(10)).await;
+
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = nio::spawn_local(TcpStream::connect(addr));
+        listener.accept().await.unwrap();
+        client.await.unwrap().unwrap();
+    });
+
+    let metrics = rt.context().metrics();
+    let measurement = metrics.measurement_as::<SimpleMeasurement>().unwrap();
+    assert_eq!(measurement.workers().len(), 1);
+    assert_eq!(measurement.blocking_spawned(), 1);
+
+    let worker = measurement.worker(0);
+    assert_eq!(worker.spawned(SpawnKind::Spawn), 1);
+    assert_eq!(worker.spawned(SpawnKind::Pinned), 1);
+    assert_eq!(worker.spawned(SpawnKind::Local), 2);
+    assert_eq!(worker.spawned(SpawnKind::Blocking), 0);
+
+    assert!(worker.polled() >= 6);
+    assert!(worker.yielded() >= 1);
+    assert!(worker.completed() >= 5);
+    assert!(worker.timers_fired() >= 1);
+    assert!(worker.io_events() >= 1);
+    assert!(worker.tasks_transferred() >= 1);
+    assert!(worker.parked() >= 1);
+    assert!(worker.idle_time() >= Duration::from_millis(5));
+    assert!(worker.busy_time() > Duration::ZERO);
+}
+
+#[test]
+fn local_runtime_counters() {
+    let mut rt = RuntimeBuilder::new()
+        .worker_threads(1)
+        .measurement(SimpleMeasurement::new())
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        for _ in 0..10 {
+            nio::spawn_local(async {});
+        }
+        yield_now().await;
+    });
+
+    let metrics = rt.runtime_context().metrics();
+    let worker = metrics
+        .measurement_as::<SimpleMeasurement>()
+        .unwrap()
+        .worker(0);
+    assert_eq!(worker.spawned(SpawnKind::Local), 10);
+    // The spawned tasks, and the main future twice.
+    assert_eq!(worker.polled(), 12);
+    assert_eq!(worker.completed(), 11);
+    assert_eq!(worker.yielded(), 1);
+}
+
+#[test]
+fn histograms() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(2)
+        .measurement(SimpleMeasurement::new().histograms(HistogramConfig::default()))
+        .rt()
+        .unwrap();
+
+    rt.block_on(|| async {
+        nio::spawn_pinned_at(1, || async {
+            // A slow future, blocking its worker.
+            std::thread::sleep(Duration::from_millis(20));
+        })
+        .await
+        .unwrap();
+    });
+
+    let metrics = rt.context().metrics();
+    let poll_time = metrics.poll_time_histogram(1).unwrap();
+    assert_eq!(poll_time.count(), 1);
+    assert!(poll_time.sum() >= Duration::from_millis(20));
+    assert!(poll_time.quantile(1.0).unwrap() >= Duration::from_millis(20));
+
+    // The main future, woken by worker 1.
+    let scheduling_delay = metrics.scheduling_delay_histogram(0).unwrap();
+    assert!(scheduling_delay.count() >= 2);
+    let buckets: u64 = scheduling_delay.buckets().map(|bucket| bucket.count).sum();
+    assert_eq!(buckets, scheduling_delay.count());
+}
+
+#[test]
+fn histograms_disabled_by_default() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(1)
+        .measurement(SimpleMeasurement::new())
+        .rt()
+        .unwrap();
+    rt.block_on(|| async {});
+
+    let metrics = rt.context().metrics();
+    assert!(metrics.poll_time_histogram(0).is_none());
+    assert!(metrics.scheduling_delay_histogram(0).is_none());
+}
+
+#[test]
+fn prometheus_export() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(2)
+        .measurement(SimpleMeasurement::new().histograms(HistogramConfig::default()))
+        .rt()
+        .unwrap();
+    rt.block_on(|| async {
+        nio::spawn_local(async {}).await.unwrap();
+    });
+
+    let text = export::prometheus(&rt.context().metrics());
+    for line in [
+        "nio_workers 2",
+        "nio_worker_queue_depth{worker=\"1\",queue=\"shared\"} 0",
+        "nio_tasks_spawned_total{worker=\"0\",kind=\"local\"} 1",
+        "# TYPE nio_task_poll_duration_seconds histogram",
+    ] {
+        assert!(
+            text.lines().any(|l| l == line),
+            "missing `{line}` in:\n{text}"
+        );
+    }
+    assert!(text.ends_with("# EOF\n"));
+
+    let mut out = Vec::new();
+    export::write_to(&rt.context().metrics(), &mut out).unwrap();
+    assert!(out.starts_with(b"# HELP nio_workers"));
+}
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    stream.write(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let mut buf = [0; 4096];
+    loop {
+        match stream.read(&mut buf).await.unwrap() {
+            0 => return String::from_utf8(response).unwrap(),
+            n => response.extend_from_slice(&buf[..n]),
+        }
+    }
+}
+
+#[nio::test]
+async fn serve_metrics_over_http() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    nio::spawn_local(nio::metrics::serve(listener));
+
+    let response = get(addr, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{response}");
+    assert!(response.contains(&format!("Content-Type: {}\r\n", export::CONTENT_TYPE)));
+    assert!(response.contains("\r\n\r\n# HELP nio_workers"));
+    assert!(response.ends_with("# EOF\n"));
+
+    let response = get(addr, "/").await;
+    assert!(
+        response.starts_with("HTTP/1.1 404 Not Found\r\n"),
+        "{response}"
+    );
+}
+
+#[nio::test]
+async fn serve_closes_idle_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    nio::spawn_local(nio::metrics::serve(listener));
+
+    // Never sends a request.
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    nio::sleep(Duration::from_millis(50)).await;
+
+    nio::time::pause();
+    nio::time::advance(Duration::from_secs(5)).await;
+    nio::time::resume();
+
+    let mut buf = [0; 64];
+    let n = nio::timeout(Duration::from_secs(1), stream.read(&mut buf))
+        .await
+        .expect("connection was not closed")
+        .unwrap();
+    assert_eq!(n, 0);
+}
+
+#[test]
+fn blocking_pool() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(1)
+        .max_blocking_threads(2)
+        .thread_timeout(Some(Duration::from_millis(50)))
+        .rt()
+        .unwrap();
+
+    rt.block_on(|| async {
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let busy = nio::spawn_blocking(move || rx.recv().unwrap());
+        let queued: Vec<_> = (0..4)
+            .map(|_| nio::spawn_blocking(|| std::thread::sleep(Duration::from_millis(5))))
+            .collect();
+
+        let metrics = nio::RuntimeContext::current().metrics();
+        assert_eq!(metrics.blocking_max_threads(), 2);
+        assert!(metrics.blocking_threads() <= 2);
+        tx.send(()).unwrap();
+        busy.await.unwrap();
+        for task in queued {
+            task.await.unwrap();
+        }
+    });
+
+    let metrics = rt.context().metrics();
+    assert_eq!(metrics.blocking_tasks_executed(), 5);
+    assert_eq!(metrics.blocking_queue_depth(), 0);
+    assert!(metrics.blocking_threads_spawned() >= 1);
+    assert!(metrics.blocking_max_queue_latency() >= Duration::from_millis(5));
+
+    // Idle threads exit after `thread_timeout`.
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while metrics.blocking_threads() > 0 && std::time::Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(metrics.blocking_threads(), 0);
+    assert_eq!(metrics.blocking_idle_threads(), 0);
+    assert_eq!(
+        metrics.blocking_threads_exited(),
+        metrics.blocking_threads_spawned()
+    );
+
+    let text = export::prometheus(&metrics);
+    assert!(
+        text.lines()
+            .any(|l| l == "nio_blocking_tasks_executed_total 5")
+    );
+}
+
+#[test]
+fn remote_wakeups() {
+    let rt = RuntimeBuilder::new()
+        .worker_threads(2)
+        .measurement(SimpleMeasurement::new())
+        .rt()
+        .unwrap();
+
+    rt.block_on(support::wakeups::cross_worker);
+
+    let metrics = rt.context().metrics();
+    let measurement = metrics.measurement_as::<SimpleMeasurement>().unwrap();
+    let worker = measurement.worker(1);
+    assert_eq!(worker.woken_remote(Some(0)), 1);
+    assert_eq!(worker.woken_remote(None), 1);
+    assert_eq!(worker.woken_remote_total(), 2);
+
+    let text = export::prometheus(&metrics);
+    assert!(
+        text.lines()
+            .any(|l| l == "nio_task_remote_wakeups_total{worker=\"1\",from=\"0\"} 1"),
+        "{text}"
+    );
+}
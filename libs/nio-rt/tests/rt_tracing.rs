@@ -0,0 +1,218 @@
+#![cfg(all(feature = "tracing", not(miri)))]
+
+use nio::{
+    RuntimeBuilder,
+    net::{TcpListener, TcpStream},
+};
+use nio_future::yield_now;
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    future::{Future, poll_fn},
+    pin::pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tracing::{
+    Event, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+
+/// Records spans and events as strings, like `nio::task: poll [runtime.spawn kind=local]`.
+#[derive(Default, Clone)]
+struct Recorder(Arc<Inner>);
+
+#[derive(Default)]
+struct Inner {
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, String>>,
+    stack: Mutex<Vec<u64>>,
+    lines: Mutex<Vec<String>>,
+}
+
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            self.0 += &format!(" {}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"))
+    }
+}
+
+impl Recorder {
+    fn lines(&self) -> Vec<String> {
+        self.0.lines.lock().unwrap().clone()
+    }
+
+    fn has(&self, pattern: &[&str]) -> bool {
+        self.lines()
+            .iter()
+            .any(|line| pattern.iter().all(|p| line.contains(p)))
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target().starts_with("nio")
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let name = format!("{}{}", span.metadata().name(), fields.0);
+        self.0.spans.lock().unwrap().insert(id, name);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let parent = event
+            .parent()
+            .map(Id::into_u64)
+            .or_else(|| self.0.stack.lock().unwrap().last().copied());
+        let span = parent
+            .and_then(|id| self.0.spans.lock().unwrap().get(&id).cloned())
+            .unwrap_or_default();
+        let line = format!("{}: {} [{span}]", event.metadata().target(), fields.0);
+        self.0.lines.lock().unwrap().push(line);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.0.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.stack.lock().unwrap().pop();
+    }
+}
+
+#[test]
+fn task_events() {
+    let recorder = Recorder::default();
+    let mut rt = RuntimeBuilder::new().worker_threads(1).build().unwrap();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        rt.block_on(async {
+            nio::spawn_local(async {
+                yield_now().await;
+            })
+            .await
+            .unwrap();
+
+            let never = nio::spawn_local(std::future::pending::<()>());
+            yield_now().await;
+            never.abort();
+            assert!(never.await.unwrap_err().is_cancelled());
+
+            nio::spawn_local(nio::sleep(Duration::from_millis(1)))
+                .await
+                .unwrap();
+            nio::spawn_blocking(|| {}).await.unwrap();
+        })
+    });
+
+    for pattern in [
+        &["nio::task: spawn [runtime.spawn", "kind=local", "worker=0"][..],
+        &["nio::task: poll [runtime.spawn", "task.name=rt_tracing::"],
+        &["nio::task: yield [runtime.spawn"],
+        &["nio::task::waker:", "op=waker.wake", "task.id="],
+        &["nio::task: abort [runtime.spawn"],
+        &["nio::task: complete [runtime.spawn"],
+        &["nio::task: spawn [runtime.spawn", "kind=blocking"],
+        &["nio::timer: timers fired", "fired=1"],
+        &["nio::worker: park", "worker=0", "timeout="],
+        &["nio::worker: unpark"],
+    ] {
+        assert!(
+            recorder.has(pattern),
+            "missing {pattern:?} in:\n{:#?}",
+            recorder.lines()
+        );
+    }
+}
+
+#[test]
+fn io_registration() {
+    let recorder = Recorder::default();
+    let mut rt = RuntimeBuilder::new().worker_threads(1).build().unwrap();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        rt.block_on(async {
+            let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let client = nio::spawn_local(TcpStream::connect(addr));
+            drop(listener.accept().await.unwrap());
+            drop(client.await.unwrap().unwrap());
+        })
+    });
+
+    assert!(recorder.has(&["nio::io: register", "TcpListener", "token="]));
+    assert!(recorder.has(&["nio::io: register", "TcpStream"]));
+    assert!(recorder.has(&["nio::io: deregister", "token="]));
+}
+
+#[test]
+fn io_woken_remote() {
+    let recorder = Recorder::default();
+    let mut rt = RuntimeBuilder::new().worker_threads(2).build().unwrap();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        rt.block_on(async {
+            let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let client = nio::spawn_local(TcpStream::connect(addr));
+            let mut server = listener.accept().await.unwrap().connect().await.unwrap();
+            let mut client = client.await.unwrap().unwrap();
+
+            let (tx, rx) = mpsc::channel();
+            let waiter = nio::spawn_pinned_at(1, move || {
+                let mut tx = Some(tx);
+                poll_fn(move |cx| match tx.take() {
+                    Some(tx) => {
+                        tx.send(cx.waker().clone()).unwrap();
+                        Poll::Pending
+                    }
+                    None => Poll::Ready(()),
+                })
+            });
+            let waker = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+            // A task of worker 1 waits for a socket of worker 0.
+            let mut buf = [0; 4];
+            let read = pin!(server.read(&mut buf));
+            assert!(read.poll(&mut Context::from_waker(&waker)).is_pending());
+            client.write(b"ping").await.unwrap();
+            waiter.await.unwrap();
+        })
+    });
+
+    assert!(
+        recorder.has(&[
+            "nio::io: readiness woke a task of another worker",
+            "resource=mio::net::tcp::stream::TcpStream",
+            "worker=0",
+            "task.worker=1",
+        ]),
+        "{:#?}",
+        recorder.lines()
+    );
+}
@@ -0,0 +1,59 @@
+#![cfg(not(miri))]
+
+use nio::{RuntimeBuilder, watchdog::Stall};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+fn runtime(stalls: &Arc<Mutex<Vec<Stall>>>) -> nio::Runtime {
+    let stalls = stalls.clone();
+    RuntimeBuilder::new()
+        .worker_threads(2)
+        .watchdog(Duration::from_millis(40))
+        .on_stall(move |stall| stalls.lock().unwrap().push(stall.clone()))
+        .rt()
+        .unwrap()
+}
+
+#[test]
+fn reports_a_blocked_worker() {
+    let stalls = Arc::default();
+    let rt = runtime(&stalls);
+
+    let task_id = rt.block_on(|| async {
+        let task = nio::spawn_pinned_at(1, || async {
+            std::thread::sleep(Duration::from_millis(200));
+        });
+        let id = task.id();
+        task.await.unwrap();
+        id
+    });
+
+    let stalls = stalls.lock().unwrap();
+    assert_eq!(stalls.len(), 1, "{stalls:#?}");
+    let stall = &stalls[0];
+    assert_eq!(stall.worker.get(), 1);
+    assert_eq!(stall.task, task_id);
+    assert!(stall.duration >= Duration::from_millis(40));
+    let thread = stall.thread.as_ref().unwrap();
+    assert_eq!(thread.name(), Some("Worker: 1"));
+}
+
+#[test]
+fn idle_and_busy_workers_are_not_reported() {
+    let stalls = Arc::default();
+    let rt = runtime(&stalls);
+
+    rt.block_on(|| async {
+        // Idle, parked in the driver.
+        nio::sleep(Duration::from_millis(100)).await;
+        // Busy with many short polls.
+        let start = std::time::Instant::now();
+        while start.elapsed() < Duration::from_millis(100) {
+            nio_future::yield_now().await;
+        }
+    });
+
+    assert!(stalls.lock().unwrap().is_empty());
+}
@@ -0,0 +1,284 @@
+use nio::{
+    sim::{
+        self, Handle,
+        net::{TcpListener, TcpStream, UdpSocket},
+    },
+    time,
+};
+use std::{
+    cell::RefCell,
+    io::ErrorKind,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+const MS: Duration = Duration::from_millis(1);
+const SERVER: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+
+fn now() -> Instant {
+    time::sleep(Duration::ZERO).deadline()
+}
+
+fn addr(ip: IpAddr, port: u16) -> SocketAddr {
+    SocketAddr::new(ip, port)
+}
+
+async fn echo_server(port: u16) {
+    let mut listener = TcpListener::bind(addr(SERVER, port)).await.unwrap();
+    loop {
+        let mut stream = listener.accept().await.unwrap().connect().await.unwrap();
+        sim::spawn_local(async move {
+            let mut buf = [0; 64];
+            loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                stream.write(&buf[..n]).await.unwrap();
+            }
+        });
+    }
+}
+
+#[test]
+fn tcp_echo() {
+    let mut sim = sim::Builder::new()
+        .seed(1)
+        .latency(10 * MS, 10 * MS)
+        .build()
+        .unwrap();
+
+    let handle = sim.handle();
+    sim.block_on(async move {
+        handle.host(SERVER, echo_server(80));
+        let client = handle.host(CLIENT, async {
+            let start = now();
+            let mut stream = TcpStream::connect(addr(SERVER, 80)).await.unwrap();
+            assert_eq!(now() - start, 20 * MS);
+            assert_eq!(stream.peer_addr().unwrap(), addr(SERVER, 80));
+            assert_eq!(stream.local_addr().unwrap().ip(), CLIENT);
+
+            let start = now();
+            stream.write(b"hello").await.unwrap();
+            let mut buf = [0; 5];
+            assert_eq!(stream.read(&mut buf).await.unwrap(), 5);
+            assert_eq!(&buf, b"hello");
+            assert_eq!(now() - start, 20 * MS);
+        });
+        client.await.unwrap();
+    });
+}
+
+#[test]
+fn eof_after_shutdown() {
+    let mut sim = sim::Builder::new().seed(2).build().unwrap();
+    let handle = sim.handle();
+    sim.block_on(async move {
+        let server = handle.host(SERVER, async {
+            let mut listener = TcpListener::bind("0.0.0.0:80").await.unwrap();
+            assert_eq!(listener.local_addr().unwrap(), addr(SERVER, 80));
+            let mut stream = listener.accept().await.unwrap().connect().await.unwrap();
+            let mut buf = [0; 8];
+            assert_eq!(stream.read(&mut buf).await.unwrap(), 3);
+            assert_eq!(stream.read(&mut buf).await.unwrap(), 0);
+        });
+        handle.host(CLIENT, async {
+            let mut stream = TcpStream::connect(addr(SERVER, 80)).await.unwrap();
+            stream.write(b"bye").await.unwrap();
+        });
+        server.await.unwrap();
+    });
+}
+
+#[test]
+fn connection_refused() {
+    let mut sim = sim::Builder::new().seed(3).build().unwrap();
+    let handle = sim.handle();
+    sim.block_on(async move {
+        let client = handle.host(CLIENT, TcpStream::connect(addr(SERVER, 80)));
+        let err = client.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+    });
+}
+
+#[test]
+fn bind_foreign_address() {
+    let mut sim = sim::Builder::new().seed(4).build().unwrap();
+    let handle = sim.handle();
+    sim.block_on(async move {
+        let client = handle.host(CLIENT, TcpListener::bind(addr(SERVER, 80)));
+        let err = client.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::AddrNotAvailable);
+    });
+}
+
+#[test]
+fn partition() {
+    let mut sim = sim::Builder::new()
+        .seed(5)
+        .connect_timeout(Duration::from_secs(3))
+        .build()
+        .unwrap();
+
+    let handle = sim.handle();
+    sim.block_on(async move {
+        handle.host(SERVER, echo_server(80));
+        handle.partition(SERVER, CLIENT);
+
+        let client = handle.host(CLIENT, async {
+            let start = now();
+            let err = TcpStream::connect(addr(SERVER, 80)).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::TimedOut);
+            assert_eq!(now() - start, Duration::from_secs(3));
+
+            Handle::current().repair(SERVER, CLIENT);
+            let mut stream = TcpStream::connect(addr(SERVER, 80)).await.unwrap();
+
+            // Segments are held back until the link is repaired.
+            Handle::current().partition(CLIENT, SERVER);
+            stream.write(b"ping").await.unwrap();
+            let mut buf = [0; 4];
+            let read = time::timeout(Duration::from_secs(60), stream.read(&mut buf)).await;
+            assert!(read.is_err());
+
+            Handle::current().repair_all();
+            assert_eq!(stream.read(&mut buf).await.unwrap(), 4);
+            assert_eq!(&buf, b"ping");
+        });
+        client.await.unwrap();
+    });
+}
+
+#[test]
+fn udp() {
+    let mut sim = sim::Builder::new()
+        .seed(6)
+        .latency(5 * MS, 5 * MS)
+        .build()
+        .unwrap();
+
+    let handle = sim.handle();
+    sim.block_on(async move {
+        // Datagrams sent before the socket is bound are lost.
+        let bind = handle.host(SERVER, UdpSocket::bind(addr(SERVER, 53)));
+        let mut socket = bind.await.unwrap().unwrap();
+        let server = handle.host(SERVER, async move {
+            let mut buf = [0; 3];
+            // Truncated, like a real datagram socket.
+            let (n, from) = socket.recv_from(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"que");
+            socket.send_to(b"answer", from).await.unwrap();
+        });
+        let client = handle.host(CLIENT, async {
+            let mut socket = UdpSocket::bind("0.0.0.0:0").await.unwrap();
+            socket.connect(addr(SERVER, 53)).await.unwrap();
+            let start = now();
+            socket.send(b"query").await.unwrap();
+            let mut buf = [0; 16];
+            let n = socket.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"answer");
+            assert_eq!(now() - start, 10 * MS);
+        });
+        server.await.unwrap();
+        client.await.unwrap();
+    });
+}
+
+/// Sends 100 datagrams and returns how many arrived.
+fn udp_received(packet_loss: f64, partitioned: bool) -> usize {
+    let mut sim = sim::Builder::new()
+        .seed(7)
+        .packet_loss(packet_loss)
+        .build()
+        .unwrap();
+
+    let handle = sim.handle();
+    if partitioned {
+        handle.partition(SERVER, CLIENT);
+    }
+    sim.block_on(async move {
+        let bind = handle.host(SERVER, UdpSocket::bind(addr(SERVER, 9000)));
+        let mut socket = bind.await.unwrap().unwrap();
+        let server = handle.host(SERVER, async move {
+            let mut received = 0;
+            let mut buf = [0; 8];
+            while time::timeout(Duration::from_secs(1), socket.recv_from(&mut buf))
+                .await
+                .is_ok()
+            {
+                received += 1;
+            }
+            received
+        });
+        handle.host(CLIENT, async {
+            let mut socket = UdpSocket::bind(addr(CLIENT, 9000)).await.unwrap();
+            for i in 0..100u8 {
+                socket.send_to(&[i], addr(SERVER, 9000)).await.unwrap();
+            }
+        });
+        server.await.unwrap()
+    })
+}
+
+#[test]
+fn packet_loss() {
+    assert_eq!(udp_received(0.0, false), 100);
+    assert_eq!(udp_received(1.0, false), 0);
+    assert_eq!(udp_received(0.0, true), 0);
+
+    let received = udp_received(0.5, false);
+    assert!(received > 0 && received < 100, "{received}");
+}
+
+/// Runs racing clients, and returns the order in which the server saw them.
+fn race(seed: u64) -> Vec<(u8, Duration)> {
+    let mut sim = sim::Builder::new()
+        .seed(seed)
+        .latency(MS, 20 * MS)
+        .build()
+        .unwrap();
+    assert_eq!(sim.seed(), seed);
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let handle = sim.handle();
+    let server_log = log.clone();
+    sim.block_on(async move {
+        let start = now();
+        handle.host(SERVER, async move {
+            let mut listener = TcpListener::bind(addr(SERVER, 80)).await.unwrap();
+            loop {
+                let mut stream = listener.accept().await.unwrap().connect().await.unwrap();
+                let log = server_log.clone();
+                sim::spawn_local(async move {
+                    let mut buf = [0; 1];
+                    stream.read(&mut buf).await.unwrap();
+                    log.borrow_mut().push((buf[0], now() - start));
+                });
+            }
+        });
+        let clients: Vec<_> = (0..10)
+            .map(|id| {
+                let ip = Ipv4Addr::new(10, 0, 1, id);
+                handle.host(ip, async move {
+                    let mut stream = TcpStream::connect(addr(SERVER, 80)).await.unwrap();
+                    stream.write(&[id]).await.unwrap();
+                })
+            })
+            .collect();
+        for client in clients {
+            client.await.unwrap();
+        }
+        time::sleep(Duration::from_secs(1)).await;
+    });
+    log.take()
+}
+
+#[test]
+fn replay() {
+    let run = race(42);
+    assert_eq!(run.len(), 10);
+    assert_eq!(race(42), run);
+    assert!((0..5).any(|seed| race(seed) != run));
+}
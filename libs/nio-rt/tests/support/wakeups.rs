@@ -0,0 +1,38 @@
+use nio::{
+    TaskId,
+    net::{TcpListener, TcpStream},
+};
+use std::time::Duration;
+
+/// Wakes tasks pinned to worker `1` from the other worker, from the blocking
+/// pool and by I/O, returns the ids of the first two.
+pub async fn cross_worker() -> (TaskId, TaskId) {
+    let sleeper = nio::spawn_pinned_at(0, || nio::sleep(Duration::from_millis(20)));
+    // Woken by worker 0, when `sleeper` completes.
+    let waiter = nio::spawn_pinned_at(1, || async { sleeper.await.unwrap() });
+    // Woken by a thread of the blocking pool.
+    let blocking_waiter = nio::spawn_pinned_at(1, || async {
+        nio::spawn_blocking(|| std::thread::sleep(Duration::from_millis(20)))
+            .await
+            .unwrap()
+    });
+    // I/O readiness wakes tasks of the worker that owns the socket.
+    let echo = nio::spawn_pinned_at(1, || async {
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = nio::spawn_local(async move {
+            let mut client = TcpStream::connect(addr).await.unwrap();
+            let mut buf = [0; 4];
+            client.read(&mut buf).await.unwrap()
+        });
+        let mut server = listener.accept().await.unwrap().connect().await.unwrap();
+        server.write(b"ping").await.unwrap();
+        client.await.unwrap()
+    });
+
+    let ids = (waiter.id(), blocking_waiter.id());
+    waiter.await.unwrap();
+    blocking_waiter.await.unwrap();
+    assert_eq!(echo.await.unwrap(), 4);
+    ids
+}
@@ -0,0 +1,145 @@
+use futures::{AsyncReadExt, AsyncWriteExt};
+use nio::{spawn_local, test, test_util::io::Builder, time};
+use std::{
+    io::{self, ErrorKind},
+    time::{Duration, Instant},
+};
+
+/// A line based protocol, generic over the stream like real code would be.
+async fn ping<S>(stream: &mut S) -> io::Result<String>
+where
+    S: futures::AsyncRead + futures::AsyncWrite + Unpin,
+{
+    stream.write_all(b"PING\r\n").await?;
+    let mut buf = [0; 64];
+    let mut len = 0;
+    while !buf[..len].ends_with(b"\r\n") {
+        let n = stream.read(&mut buf[len..]).await?;
+        if n == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        len += n;
+    }
+    Ok(String::from_utf8_lossy(&buf[..len - 2]).into_owned())
+}
+
+#[test]
+async fn read_write() {
+    let mut mock = Builder::new()
+        .write(b"PING\r\n")
+        .read(b"PO")
+        .read(b"NG\r\n")
+        .build();
+    assert_eq!(ping(&mut mock).await.unwrap(), "PONG");
+}
+
+#[test]
+async fn split_reads_and_writes() {
+    let mut mock = Builder::new().write(b"hello world").read(b"abc").build();
+    mock.write_all(b"hello").await.unwrap();
+    mock.write_all(b" world").await.unwrap();
+
+    let mut buf = [0; 1];
+    for byte in b"abc" {
+        assert_eq!(mock.read(&mut buf).await.unwrap(), 1);
+        assert_eq!(buf[0], *byte);
+    }
+    assert_eq!(mock.read(&mut buf).await.unwrap(), 0);
+}
+
+#[test]
+async fn write_ahead_of_reads() {
+    let mut mock = Builder::new().read(b"greeting").write(b"reply").build();
+    mock.write_all(b"reply").await.unwrap();
+
+    let mut buf = Vec::new();
+    mock.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, b"greeting");
+}
+
+#[test]
+async fn read_waits_for_write() {
+    let mock = Builder::new().write(b"req").read(b"res").build();
+    let (mut reader, mut writer) = mock.split();
+
+    let read = spawn_local(async move {
+        let mut buf = [0; 3];
+        reader.read_exact(&mut buf).await.unwrap();
+        buf
+    });
+    nio::sleep(Duration::from_millis(10)).await;
+    assert!(!read.is_finished());
+
+    writer.write_all(b"req").await.unwrap();
+    assert_eq!(&read.await.unwrap(), b"res");
+}
+
+#[test]
+async fn errors() {
+    let mut mock = Builder::new()
+        .read_error(io::Error::new(ErrorKind::ConnectionReset, "reset"))
+        .write_error(ErrorKind::BrokenPipe.into())
+        .build();
+
+    let err = mock.read(&mut [0; 8]).await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+    assert_eq!(err.to_string(), "reset");
+
+    let err = mock.write(b"data").await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+}
+
+#[test(start_paused = true)]
+async fn wait() {
+    let start = Instant::now();
+    let mut mock = Builder::new()
+        .write(b"PING\r\n")
+        .wait(Duration::from_secs(30))
+        .read(b"PONG\r\n")
+        .build();
+
+    let reply = time::timeout(Duration::from_secs(10), ping(&mut mock)).await;
+    assert!(reply.is_err());
+
+    // The script continues, after 30 seconds of the paused clock.
+    let mut buf = [0; 6];
+    mock.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"PONG\r\n");
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[cfg(feature = "tokio-io")]
+#[test]
+async fn tokio_io() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut mock = Builder::new().write(b"ping").read(b"pong").build();
+    AsyncWriteExt::write_all(&mut mock, b"ping").await.unwrap();
+    let mut buf = String::new();
+    AsyncReadExt::read_to_string(&mut mock, &mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, "pong");
+}
+
+#[test]
+#[should_panic = "unexpected bytes written"]
+async fn unexpected_bytes() {
+    let mut mock = Builder::new().write(b"PING").build();
+    let _ = mock.write(b"PONG").await;
+}
+
+#[test]
+#[should_panic = "unexpected write"]
+async fn unexpected_write() {
+    let mut mock = Builder::new().read(b"data").build();
+    let _ = mock.write(b"data").await;
+}
+
+#[test]
+#[should_panic = "mock dropped before the end of the script"]
+async fn unfinished_script() {
+    let mut mock = Builder::new().read(b"hello").write(b"bye").build();
+    let mut buf = [0; 5];
+    mock.read(&mut buf).await.unwrap();
+}
@@ -19,7 +19,7 @@ async fn sleep_short() {
 async fn timeout_expired() {
     let now = Instant::now();
     let timer = Timeout::at(now + DELAY, pending::<()>());
-    assert!(timer.await.is_none());
+    assert!(timer.await.is_err());
 
     let elapsed = now.elapsed();
     assert!(elapsed >= DELAY, "{elapsed:?}");
@@ -28,7 +28,7 @@ async fn timeout_expired() {
 async fn timeout_short() {
     let now = Instant::now();
     let timer = Timeout::at(now + Duration::from_secs(60), Sleep::at(now + DELAY));
-    assert!(timer.await.is_some());
+    assert!(timer.await.is_ok());
 
     let elapsed = now.elapsed();
     assert!(elapsed >= DELAY, "{elapsed:?}");
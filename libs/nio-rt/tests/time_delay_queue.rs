@@ -0,0 +1,129 @@
+use futures::StreamExt;
+use nio::{
+    test,
+    time::{self, DelayQueue},
+};
+use std::{
+    future::poll_fn,
+    task::Poll,
+    time::{Duration, Instant},
+};
+
+const MS: Duration = Duration::from_millis(1);
+
+fn now() -> Instant {
+    time::sleep(Duration::ZERO).deadline()
+}
+
+/// Polls the queue once, so that its timer is registered.
+async fn poll_pending(queue: &mut DelayQueue<&str>) {
+    poll_fn(|cx| {
+        assert!(queue.poll_expired(cx).is_pending());
+        Poll::Ready(())
+    })
+    .await
+}
+
+#[test(start_paused = true)]
+async fn expire_in_order() {
+    let start = now();
+    let mut queue = DelayQueue::new();
+    queue.insert("c", 30 * MS);
+    queue.insert("a", 10 * MS);
+    queue.insert("b", 20 * MS);
+    assert_eq!(queue.len(), 3);
+
+    for (value, after) in [("a", 10 * MS), ("b", 20 * MS), ("c", 30 * MS)] {
+        let expired = queue.next_expired().await.unwrap();
+        assert_eq!(*expired.get_ref(), value);
+        assert_eq!(expired.deadline(), start + after);
+        assert_eq!(now(), start + after);
+    }
+    assert!(queue.is_empty());
+    assert!(queue.next_expired().await.is_none());
+}
+
+#[test(start_paused = true)]
+async fn remove() {
+    let mut queue = DelayQueue::new();
+    let a = queue.insert("a", 10 * MS);
+    let b = queue.insert("b", 20 * MS);
+
+    assert_eq!(queue.remove(&a), "a");
+    assert!(!queue.contains(&a));
+    assert_eq!(queue.try_remove(&a), None);
+
+    let expired = queue.next_expired().await.unwrap();
+    assert_eq!(expired.key(), b);
+    assert_eq!(expired.into_inner(), "b");
+}
+
+#[test(start_paused = true)]
+async fn reset() {
+    let start = now();
+    let mut queue = DelayQueue::new();
+    let a = queue.insert("a", 10 * MS);
+    queue.insert("b", 20 * MS);
+
+    queue.reset(&a, 30 * MS);
+    assert_eq!(queue.deadline(&a), start + 30 * MS);
+
+    assert_eq!(queue.next_expired().await.unwrap().into_inner(), "b");
+    assert_eq!(queue.next_expired().await.unwrap().into_inner(), "a");
+    assert_eq!(now(), start + 30 * MS);
+}
+
+#[test(start_paused = true)]
+async fn earlier_deadline_while_waiting() {
+    let start = now();
+    let mut queue = DelayQueue::new();
+    let a = queue.insert("a", 50 * MS);
+    poll_pending(&mut queue).await;
+
+    queue.insert("b", 10 * MS);
+    assert_eq!(queue.next_expired().await.unwrap().into_inner(), "b");
+    assert_eq!(now(), start + 10 * MS);
+
+    poll_pending(&mut queue).await;
+    queue.reset(&a, 20 * MS);
+    assert_eq!(queue.next_expired().await.unwrap().into_inner(), "a");
+    assert_eq!(now(), start + 30 * MS);
+}
+
+#[test(start_paused = true)]
+async fn insert_after_empty() {
+    let mut queue = DelayQueue::new();
+    assert!(queue.next_expired().await.is_none());
+
+    queue.insert("a", 10 * MS);
+    assert_eq!(queue.next_expired().await.unwrap().into_inner(), "a");
+}
+
+#[test(start_paused = true)]
+async fn stream() {
+    let mut queue = DelayQueue::new();
+    for (i, value) in ["a", "b", "c"].into_iter().enumerate() {
+        queue.insert(value, (3 - i as u32) * MS);
+    }
+    let values: Vec<_> = queue.map(|expired| expired.into_inner()).collect().await;
+    assert_eq!(values, ["c", "b", "a"]);
+}
+
+#[test(start_paused = true)]
+async fn clear() {
+    let mut queue = DelayQueue::new();
+    queue.insert("a", 10 * MS);
+    queue.insert("b", 10 * MS);
+    queue.clear();
+    assert!(queue.is_empty());
+    assert!(queue.next_expired().await.is_none());
+}
+
+#[test]
+#[should_panic = "invalid key"]
+async fn remove_expired_key() {
+    let mut queue = DelayQueue::new();
+    let key = queue.insert("a", Duration::ZERO);
+    queue.next_expired().await.unwrap();
+    queue.remove(&key);
+}
@@ -0,0 +1,127 @@
+use futures::StreamExt;
+use nio::{
+    test,
+    time::{self, MissedTickBehavior, interval, interval_at},
+};
+use std::time::{Duration, Instant};
+
+const MS: Duration = Duration::from_millis(1);
+
+/// Ticks with the time they completed at.
+async fn ticks(interval: &mut time::Interval, n: usize) -> Vec<(Duration, Duration)> {
+    let origin = time::sleep(Duration::ZERO).deadline();
+    let mut ticks = Vec::new();
+    for _ in 0..n {
+        let scheduled = interval.tick().await;
+        let now = time::sleep(Duration::ZERO).deadline();
+        ticks.push((scheduled.saturating_duration_since(origin), now - origin));
+    }
+    ticks
+}
+
+async fn missed_ticks(behavior: MissedTickBehavior) -> Vec<Duration> {
+    let mut interval = interval(10 * MS);
+    interval.set_missed_tick_behavior(behavior);
+    let start = interval.tick().await;
+
+    time::advance(25 * MS).await;
+
+    let mut completed = Vec::new();
+    for _ in 0..4 {
+        interval.tick().await;
+        completed.push(time::sleep(Duration::ZERO).deadline() - start);
+    }
+    completed
+}
+
+#[test(start_paused = true)]
+async fn first_tick_is_immediate() {
+    let mut interval = interval(10 * MS);
+    assert_eq!(
+        ticks(&mut interval, 3).await,
+        [
+            (Duration::ZERO, Duration::ZERO),
+            (10 * MS, 10 * MS),
+            (20 * MS, 20 * MS)
+        ]
+    );
+}
+
+#[test(start_paused = true)]
+async fn fixed_schedule() {
+    let start = Instant::now() + 100 * MS;
+    let mut interval = interval_at(start, 10 * MS);
+    assert_eq!(interval.tick().await, start);
+    assert_eq!(interval.tick().await, start + 10 * MS);
+
+    // Work between ticks doesn't shift the schedule.
+    time::advance(3 * MS).await;
+    assert_eq!(interval.tick().await, start + 20 * MS);
+    assert_eq!(interval.tick().await, start + 30 * MS);
+}
+
+#[test(start_paused = true)]
+async fn burst() {
+    assert_eq!(
+        missed_ticks(MissedTickBehavior::Burst).await,
+        [25 * MS, 25 * MS, 30 * MS, 40 * MS]
+    );
+}
+
+#[test(start_paused = true)]
+async fn delay() {
+    assert_eq!(
+        missed_ticks(MissedTickBehavior::Delay).await,
+        [25 * MS, 35 * MS, 45 * MS, 55 * MS]
+    );
+}
+
+#[test(start_paused = true)]
+async fn skip() {
+    assert_eq!(
+        missed_ticks(MissedTickBehavior::Skip).await,
+        [25 * MS, 30 * MS, 40 * MS, 50 * MS]
+    );
+}
+
+#[test(start_paused = true)]
+async fn reset() {
+    let mut interval = interval(10 * MS);
+    let start = interval.tick().await;
+
+    time::advance(4 * MS).await;
+    interval.reset();
+    assert_eq!(interval.tick().await, start + 14 * MS);
+
+    interval.reset_immediately();
+    assert_eq!(interval.tick().await, start + 14 * MS);
+    assert_eq!(interval.tick().await, start + 24 * MS);
+
+    interval.reset_at(start + 100 * MS);
+    assert_eq!(interval.tick().await, start + 100 * MS);
+    assert_eq!(interval.tick().await, start + 110 * MS);
+}
+
+#[test(start_paused = true)]
+async fn stream() {
+    let interval = interval(10 * MS);
+    let ticks: Vec<_> = interval.take(3).collect().await;
+    assert_eq!(ticks[1] - ticks[0], 10 * MS);
+    assert_eq!(ticks[2] - ticks[1], 10 * MS);
+}
+
+#[test(start_paused = true)]
+#[allow(deprecated)]
+async fn deprecated_poll() {
+    let mut interval = interval(10 * MS);
+    std::future::poll_fn(|cx| interval.poll(cx)).await;
+    let start = time::sleep(Duration::ZERO).deadline();
+    std::future::poll_fn(|cx| interval.poll(cx)).await;
+    assert_eq!(time::sleep(Duration::ZERO).deadline() - start, 10 * MS);
+}
+
+#[test]
+#[should_panic = "`period` must be non-zero"]
+async fn zero_period() {
+    interval(Duration::ZERO);
+}
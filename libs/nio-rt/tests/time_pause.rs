@@ -0,0 +1,90 @@
+use nio::{
+    spawn_local, test,
+    time::{self, Interval, sleep, timeout},
+};
+use std::{
+    cell::Cell,
+    future::pending,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+#[test(start_paused = true)]
+async fn auto_advance() {
+    let start = Instant::now();
+    for _ in 0..24 {
+        sleep(HOUR).await;
+    }
+    assert!(start.elapsed() < HOUR);
+
+    assert!(timeout(HOUR, pending::<()>()).await.is_err());
+    assert!(start.elapsed() < HOUR);
+}
+
+#[test(start_paused = true)]
+async fn advance() {
+    let fired = Rc::new(Cell::new(false));
+    let task = spawn_local({
+        let fired = fired.clone();
+        async move {
+            sleep(Duration::from_secs(10)).await;
+            fired.set(true);
+        }
+    });
+    // Let the task register its timer.
+    time::advance(Duration::ZERO).await;
+
+    time::advance(Duration::from_secs(9)).await;
+    assert!(!fired.get());
+
+    time::advance(Duration::from_secs(1)).await;
+    assert!(fired.get());
+    task.await.unwrap();
+}
+
+#[test]
+async fn pause_resume() {
+    time::pause();
+    let mut sleep = sleep(HOUR);
+    let deadline = sleep.deadline();
+
+    time::advance(HOUR).await;
+    assert!(sleep.is_elapsed());
+    (&mut sleep).await;
+
+    time::resume();
+    // The clock doesn't go back to the real time.
+    let next = time::sleep(Duration::ZERO);
+    assert!(next.deadline() >= deadline);
+    next.await;
+}
+
+#[test(start_paused = true)]
+async fn interval() {
+    let mut interval = Interval::at(Instant::now(), HOUR);
+    let start = Instant::now();
+    for _ in 0..10 {
+        interval.tick().await;
+    }
+    assert!(start.elapsed() < HOUR);
+}
+
+#[test]
+#[should_panic = "time is not frozen"]
+async fn advance_without_pause() {
+    time::advance(HOUR).await;
+}
+
+#[test(start_paused = true)]
+async fn blocking_task_holds_auto_advance() {
+    let task = nio::spawn_blocking(|| std::thread::sleep(Duration::from_millis(50)));
+    assert!(timeout(Duration::from_millis(1), task).await.is_ok());
+
+    let path = std::env::temp_dir().join(format!("nio-pause-{}", std::process::id()));
+    nio::fs::write(&path, b"hello").await.unwrap();
+    let read = timeout(Duration::from_millis(1), nio::fs::read(&path)).await;
+    assert_eq!(read.unwrap().unwrap(), b"hello");
+    nio::fs::remove_file(&path).await.unwrap();
+}
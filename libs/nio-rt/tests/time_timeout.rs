@@ -0,0 +1,80 @@
+use futures::{StreamExt, stream};
+use nio::{
+    test,
+    time::{self, StreamTimeoutExt, TimeoutExt, error::Elapsed, sleep, timeout, timeout_at},
+};
+use std::{
+    future::pending,
+    io,
+    time::{Duration, Instant},
+};
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test(start_paused = true)]
+async fn completes() {
+    assert_eq!(timeout(10 * MS, async { 1 }).await, Ok(1));
+    assert_eq!(timeout(10 * MS, sleep(5 * MS)).await, Ok(()));
+    assert_eq!(timeout(10 * MS, async { None::<u8> }).await, Ok(None));
+}
+
+#[test(start_paused = true)]
+async fn elapsed() {
+    let err: Elapsed = timeout(10 * MS, pending::<()>()).await.unwrap_err();
+    assert_eq!(err.to_string(), "deadline has elapsed");
+}
+
+#[test(start_paused = true)]
+async fn into_io_error() {
+    async fn read() -> io::Result<()> {
+        timeout(10 * MS, pending::<io::Result<()>>()).await??;
+        Ok(())
+    }
+    let err = read().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(err.to_string(), "deadline has elapsed");
+}
+
+#[test(start_paused = true)]
+async fn at_deadline() {
+    let deadline = Instant::now() + 10 * MS;
+    assert!(timeout_at(deadline, sleep(20 * MS)).await.is_err());
+    assert!(timeout_at(deadline + 20 * MS, sleep(5 * MS)).await.is_ok());
+}
+
+#[test(start_paused = true)]
+async fn extension() {
+    assert_eq!(async { 1 }.timeout(10 * MS).await, Ok(1));
+    assert!(pending::<()>().timeout(10 * MS).await.is_err());
+
+    let deadline = Instant::now() + 10 * MS;
+    assert!(sleep(20 * MS).timeout_at(deadline).await.is_err());
+}
+
+#[test(start_paused = true)]
+async fn stream() {
+    let delays = [MS, 20 * MS, MS];
+    let items = stream::iter(delays).then(|delay| async move {
+        sleep(delay).await;
+        delay
+    });
+    let items: Vec<_> = items.timeout(10 * MS).collect().await;
+    assert_eq!(items.len(), 4);
+    assert_eq!(items[0], Ok(MS));
+    assert!(items[1].is_err());
+    assert_eq!(items[2], Ok(20 * MS));
+    assert_eq!(items[3], Ok(MS));
+}
+
+#[test(start_paused = true)]
+async fn reset_elapsed_sleep() {
+    let mut delay = sleep(MS);
+    (&mut delay).await;
+    assert!(delay.is_elapsed());
+
+    let start = time::sleep(Duration::ZERO).deadline();
+    delay.reset(10 * MS);
+    assert!(!delay.is_elapsed());
+    (&mut delay).await;
+    assert_eq!(delay.deadline() - start, 10 * MS);
+}
@@ -0,0 +1,4 @@
+#[nio::test(start_paused = true, start_paused = false)]
+async fn duplicate_key() {}
+
+fn main() {}
@@ -0,0 +1,16 @@
+#[nio::test(worker_threads = 0)]
+async fn zero_workers() {}
+
+#[nio::test(max_blocking_threads = 0)]
+async fn zero_blocking_threads() {}
+
+#[nio::test(start_paused = "yes")]
+async fn not_a_bool() {}
+
+#[nio::test(flavor = "current_thread")]
+async fn unknown_flavor() {}
+
+#[nio::test(event_interval)]
+async fn missing_value() {}
+
+fn main() {}
@@ -0,0 +1,5 @@
+#[nio::main]
+fn main() {}
+
+#[nio::test]
+fn not_async() {}
@@ -0,0 +1,4 @@
+#[nio::test(workers = 2)]
+async fn unknown_key() {}
+
+fn main() {}
@@ -16,6 +16,12 @@ impl TaskId {
     pub fn get(&self) -> NonZero<usize> {
         self.0
     }
+
+    /// Rebuilds an id from the value returned by [`TaskId::get`].
+    #[inline]
+    pub fn from_raw(id: NonZero<usize>) -> TaskId {
+        TaskId(id)
+    }
 }
 
 pub fn id() -> impl Future<Output = TaskId> {
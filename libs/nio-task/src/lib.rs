@@ -6,6 +6,7 @@ mod blocking;
 mod error;
 mod id;
 mod join;
+mod loom;
 mod raw;
 mod state;
 mod task;
@@ -81,6 +82,11 @@ impl<M> Metadata<M> {
         unsafe { &*self.raw.metadata().cast::<M>() }
     }
 
+    /// Whether the task was aborted before it completed.
+    pub fn is_cancelled(&self) -> bool {
+        self.raw.header().state.load().has(CANCELLED)
+    }
+
     pub fn get_mut(&mut self) -> &mut M {
         unsafe { &mut *self.raw.metadata().cast::<M>() }
     }
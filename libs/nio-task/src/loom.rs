@@ -0,0 +1,13 @@
+//! Atomics of the task state, swapped for [`loom`]'s when built with
+//! `RUSTFLAGS="--cfg nio_loom"` to model check them (see `tests/loom.rs`).
+//!
+//! The cfg is prefixed, a plain `--cfg loom` also changes how dependencies
+//! such as `concurrent-queue` are built.
+//!
+//! [`loom`]: https://docs.rs/loom
+
+#[cfg(nio_loom)]
+pub use ::loom::sync::atomic;
+
+#[cfg(not(nio_loom))]
+pub use std::sync::atomic;
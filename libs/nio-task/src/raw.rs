@@ -160,7 +160,10 @@ impl Header {
             }
             // SAFETY: `JOIN_WAKER` unset successfully, we can set the new waker.
             // We have the exclusive access to the waker field.
-            unsafe { self.set_join_waker(waker.clone()) };
+            //
+            // The task may complete before the new waker is set, it then sees
+            // no `JOIN_WAKER` and doesn't wake us. So the output must be read now.
+            return unsafe { self.set_join_waker(waker.clone()) };
         }
         is_complete
     }
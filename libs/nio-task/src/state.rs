@@ -4,13 +4,11 @@
 //! NOTIFIED -> RUNNING -> ( SLEEP? -> NOTIFIED -> RUNNING )* -> COMPLETE?
 //! ```
 
-use std::{
-    fmt,
-    sync::atomic::{
-        AtomicUsize,
-        Ordering::{self, AcqRel, Acquire},
-    },
+use crate::loom::atomic::{
+    AtomicUsize,
+    Ordering::{self, AcqRel, Acquire},
 };
+use std::fmt;
 
 // The task is currently being run.
 pub const RUNNING: usize = 0b00;
@@ -0,0 +1,166 @@
+//! Model checks the task state machine against concurrent wake, abort and join.
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg nio_loom" cargo test -p nio-task --test loom --release
+//! ```
+//!
+//! A lost wakeup leaves a task or a `JoinHandle` waiting forever, which loom
+//! reports as a deadlock. A task scheduled twice is polled twice, which fails
+//! the state assertions of `Task::poll`.
+#![cfg(nio_loom)]
+
+use loom::{
+    future::block_on,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+use nio_task::{Status, Task};
+use std::{
+    collections::VecDeque,
+    future::{Future, poll_fn},
+    task::{Poll, Waker},
+};
+
+type Queue = Arc<Mutex<VecDeque<Task>>>;
+
+fn scheduler(queue: &Queue) -> impl Fn(Task) + Send + 'static {
+    let queue = queue.clone();
+    move |task| queue.lock().unwrap().push_back(task)
+}
+
+/// Polls scheduled tasks until the queue is empty.
+fn run(queue: &Queue) {
+    loop {
+        let Some(task) = queue.lock().unwrap().pop_front() else {
+            return;
+        };
+        match task.poll() {
+            Status::Yielded(task) => task.schedule(),
+            Status::Pending | Status::Complete(_) => {}
+        }
+    }
+}
+
+/// A one-shot event, woken from another thread.
+#[derive(Default)]
+struct Signal(Mutex<(bool, Option<Waker>)>);
+
+impl Signal {
+    fn notify(&self) {
+        let waker = {
+            let mut state = self.0.lock().unwrap();
+            state.0 = true;
+            state.1.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn wait(this: &Arc<Self>) -> impl Future<Output = ()> + Send + use<> {
+        let this = this.clone();
+        poll_fn(move |cx| {
+            let mut state = this.0.lock().unwrap();
+            if state.0 {
+                return Poll::Ready(());
+            }
+            state.1 = Some(cx.waker().clone());
+            Poll::Pending
+        })
+    }
+}
+
+#[test]
+fn wake_while_running() {
+    loom::model(|| {
+        let queue = Queue::default();
+        let signal = Arc::new(Signal::default());
+        let (task, join) = Task::new(Signal::wait(&signal), scheduler(&queue));
+        queue.lock().unwrap().push_back(task);
+
+        let waker = thread::spawn(move || signal.notify());
+        run(&queue);
+        waker.join().unwrap();
+
+        // Whatever the interleaving, the wake was not lost.
+        run(&queue);
+        assert!(join.is_finished());
+    });
+}
+
+#[test]
+fn wake_and_abort() {
+    loom::model(|| {
+        let queue = Queue::default();
+        let signal = Arc::new(Signal::default());
+        let (task, join) = Task::new(Signal::wait(&signal), scheduler(&queue));
+        queue.lock().unwrap().push_back(task);
+        run(&queue);
+
+        let abort = join.abort_handle();
+        let waker = thread::spawn(move || signal.notify());
+        let aborter = thread::spawn(move || abort.abort());
+        run(&queue);
+        waker.join().unwrap();
+        aborter.join().unwrap();
+
+        run(&queue);
+        assert!(join.is_finished());
+        if let Err(err) = block_on(join) {
+            assert!(err.is_cancelled());
+        }
+    });
+}
+
+#[test]
+fn join_while_completing() {
+    loom::model(|| {
+        let queue = Queue::default();
+        let signal = Arc::new(Signal::default());
+        let wait = Signal::wait(&signal);
+        let (task, join) = Task::new(
+            async move {
+                wait.await;
+                42
+            },
+            scheduler(&queue),
+        );
+        queue.lock().unwrap().push_back(task);
+        run(&queue);
+
+        let joiner = thread::spawn(move || block_on(join));
+        signal.notify();
+        run(&queue);
+
+        assert_eq!(joiner.join().unwrap().unwrap(), 42);
+    });
+}
+
+#[test]
+fn drop_join_handle_while_completing() {
+    struct Output(Arc<AtomicUsize>);
+
+    impl Drop for Output {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    loom::model(|| {
+        let queue = Queue::default();
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let output = Output(dropped.clone());
+        let (task, join) = Task::new(async move { output }, scheduler(&queue));
+        queue.lock().unwrap().push_back(task);
+
+        let dropper = thread::spawn(move || drop(join));
+        run(&queue);
+        dropper.join().unwrap();
+
+        // Dropped exactly once, either by the task or by the `JoinHandle`.
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    });
+}
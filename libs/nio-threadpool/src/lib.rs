@@ -1,15 +1,37 @@
 use mpmc_channel::MPMC;
-use std::{collections::VecDeque, io, num::NonZero, sync::Arc, thread, time::Duration};
+use std::{
+    collections::VecDeque,
+    io,
+    num::NonZero,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
 type Channel<Task> = Arc<MPMC<Queue<Task>>>;
 
 struct Queue<Task> {
-    tasks: VecDeque<Task>,
+    /// Tasks waiting for a thread, with the time they were queued.
+    tasks: VecDeque<(Task, Instant)>,
+    /// Queued and running tasks.
     count: usize,
 }
 
+#[derive(Default)]
+struct Stats {
+    idle: AtomicUsize,
+    executed: AtomicU64,
+    spawned: AtomicU64,
+    exited: AtomicU64,
+    max_queue_latency: AtomicU64,
+}
+
 pub struct ThreadPool<Task: Runnable> {
     channel: Channel<Task>,
+    stats: Arc<Stats>,
 
     // ----- config -----
     max_threads_limit: u16,
@@ -26,6 +48,7 @@ impl<T: Runnable> Default for ThreadPool<T> {
                 tasks: VecDeque::with_capacity(64),
                 count: 0,
             })),
+            stats: Arc::default(),
 
             timeout: Some(Duration::from_secs(7)),
             max_threads_limit: 32,
@@ -89,6 +112,48 @@ impl<Task: Runnable> ThreadPool<Task> {
         self.max_threads_limit
     }
 
+    // ---------------  Metrics  -----------------
+
+    /// Threads waiting for a task.
+    pub fn idle_thread_count(&self) -> usize {
+        self.stats.idle.load(Relaxed)
+    }
+
+    /// Threads running a task.
+    pub fn busy_thread_count(&self) -> usize {
+        self.thread_count().saturating_sub(self.idle_thread_count())
+    }
+
+    /// Tasks waiting for a thread.
+    pub fn queue_depth(&self) -> usize {
+        self.channel.consume().tasks.len()
+    }
+
+    /// Tasks queued or running, a task is counted until [`Runnable::run`] returns.
+    pub fn pending_count(&self) -> usize {
+        self.channel.consume().count
+    }
+
+    /// Tasks run to completion.
+    pub fn executed_count(&self) -> u64 {
+        self.stats.executed.load(Relaxed)
+    }
+
+    /// Threads spawned since the pool was created.
+    pub fn spawned_thread_count(&self) -> u64 {
+        self.stats.spawned.load(Relaxed)
+    }
+
+    /// Threads that exited after being idle for [`ThreadPool::get_timeout`].
+    pub fn exited_thread_count(&self) -> u64 {
+        self.stats.exited.load(Relaxed)
+    }
+
+    /// The longest time a task waited in the queue before a thread picked it up.
+    pub fn max_queue_latency(&self) -> Duration {
+        Duration::from_nanos(self.stats.max_queue_latency.load(Relaxed))
+    }
+
     // -------------------------------------------
 
     pub fn thread_count(&self) -> usize {
@@ -96,12 +161,12 @@ impl<Task: Runnable> ThreadPool<Task> {
     }
 
     pub fn is_thread_limit_reached(&self) -> bool {
-        self.thread_count() > self.get_max_threads_limit().into()
+        self.thread_count() >= self.get_max_threads_limit().into()
     }
 
     pub fn add_task_to_queue(&self, task: Task) -> usize {
         let mut tx = self.channel.produce();
-        tx.tasks.push_back(task);
+        tx.tasks.push_back((task, Instant::now()));
         tx.count += 1;
         let task_count = tx.count;
         tx.notify_one();
@@ -112,7 +177,7 @@ impl<Task: Runnable> ThreadPool<Task> {
         let task_count = self.add_task_to_queue(task);
 
         let thread_count = self.thread_count();
-        if thread_count > self.get_max_threads_limit().into() {
+        if thread_count >= self.get_max_threads_limit().into() {
             return;
         }
 
@@ -129,30 +194,46 @@ impl<Task: Runnable> ThreadPool<Task> {
     pub fn spawn(&self, thread_builder: thread::Builder) -> io::Result<thread::JoinHandle<()>> {
         let timeout = self.timeout;
         let channel = self.channel.clone();
+        let stats = self.stats.clone();
 
         let worker = move || {
             let mut rx = channel.consume();
             loop {
                 rx = match rx.tasks.pop_front() {
-                    Some(task) => {
+                    Some((task, queued_at)) => {
                         drop(rx);
+                        let latency = u64::try_from(queued_at.elapsed().as_nanos());
+                        stats
+                            .max_queue_latency
+                            .fetch_max(latency.unwrap_or(u64::MAX), Relaxed);
                         task.run();
+                        stats.executed.fetch_add(1, Relaxed);
 
                         let mut rx = channel.consume();
                         rx.count -= 1;
                         rx
                     }
-                    None => match timeout {
-                        None => rx.wait(),
-                        Some(dur) => match rx.wait_timeout(dur) {
+                    None => {
+                        stats.idle.fetch_add(1, Relaxed);
+                        let rx = match timeout {
+                            None => Ok(rx.wait()),
+                            Some(dur) => rx.wait_timeout(dur),
+                        };
+                        stats.idle.fetch_sub(1, Relaxed);
+                        match rx {
                             Ok(rx) => rx,
-                            Err(_) => break,
-                        },
-                    },
+                            Err(_) => {
+                                stats.exited.fetch_add(1, Relaxed);
+                                break;
+                            }
+                        }
+                    }
                 }
             }
         };
-        thread_builder.spawn(worker)
+        let handle = thread_builder.spawn(worker)?;
+        self.stats.spawned.fetch_add(1, Relaxed);
+        Ok(handle)
     }
 
     pub fn thread_builder(&self) -> thread::Builder {
@@ -167,3 +248,42 @@ impl<Task: Runnable> ThreadPool<Task> {
         thread
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// Blocks its thread until the sender is dropped.
+    struct Wait(mpsc::Receiver<()>);
+
+    impl Runnable for Wait {
+        fn run(self) {
+            let _ = self.0.recv();
+        }
+    }
+
+    #[test]
+    fn thread_limit() {
+        let pool = ThreadPool::new().max_threads_limit(1);
+        assert!(!pool.is_thread_limit_reached());
+
+        let (tx, rx) = mpsc::channel();
+        pool.execute(Wait(rx));
+        // The limit is reached with exactly `max_threads_limit` threads.
+        assert_eq!(pool.thread_count(), 1);
+        assert!(pool.is_thread_limit_reached());
+
+        // More tasks than the load factor allows per thread, but no thread over the limit.
+        let senders: Vec<_> = (0..4)
+            .map(|_| {
+                let (tx, rx) = mpsc::channel();
+                pool.execute(Wait(rx));
+                tx
+            })
+            .collect();
+        assert_eq!(pool.thread_count(), 1);
+
+        drop((tx, senders));
+    }
+}